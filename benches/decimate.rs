@@ -0,0 +1,82 @@
+// Raw vs decimated line drawing on a dense synthetic telemetry trace.
+//
+//     cargo bench --bench decimate
+//
+// Each case renders into an in-memory RGB buffer so only plotting cost is measured.
+
+use plotters::prelude::*;
+use rust_plot::decimate::{self, Decimation};
+use rust_plot::line::draw_line_with;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+const SIZE: (u32, u32) = (2400, 1600);
+const N_POINTS: usize = 5_000_000;
+const RUNS: u32 = 3;
+
+fn telemetry(n: usize) -> Vec<(f64, f64)> {
+    // Deterministic noisy signal with occasional spikes
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..n)
+        .map(|i| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let noise = (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5;
+            let t = i as f64 / n as f64;
+            let spike = if state.is_multiple_of(200_003) { 4.0 } else { 0.0 };
+            (t, (40.0 * t).sin() + 0.3 * noise + spike)
+        })
+        .collect()
+}
+
+fn render(points: &[(f64, f64)], decimation: Option<Decimation>) -> Result<Duration, Box<dyn Error>> {
+    let mut buf = vec![0u8; (SIZE.0 * SIZE.1 * 3) as usize];
+    let start = Instant::now();
+    {
+        let root = BitMapBackend::with_buffer(&mut buf, SIZE).into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .margin(20)
+            .x_label_area_size(60)
+            .y_label_area_size(70)
+            .build_cartesian_2d(0.0f64..1.0f64, -2.0f64..5.0f64)?;
+        chart.configure_mesh().draw()?;
+        draw_line_with(&mut chart, points, BLUE, decimation)?;
+        root.present()?;
+    }
+    Ok(start.elapsed())
+}
+
+fn best_of(label: &str, mut f: impl FnMut() -> Result<Duration, Box<dyn Error>>) -> Result<(), Box<dyn Error>> {
+    let mut best = Duration::MAX;
+    for _ in 0..RUNS {
+        best = best.min(f()?);
+    }
+    println!("{label:<28} {:>10.1} ms", best.as_secs_f64() * 1e3);
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let points = telemetry(N_POINTS);
+    println!("{N_POINTS} points, {}x{} canvas, best of {RUNS}", SIZE.0, SIZE.1);
+
+    best_of("raw LineSeries", || render(&points, None))?;
+    best_of("min/max per column (draw)", || render(&points, Some(Decimation::MinMax)))?;
+    best_of("LTTB (draw)", || render(&points, Some(Decimation::Lttb)))?;
+
+    best_of("min/max only", || {
+        let t = Instant::now();
+        let out = decimate::min_max(&points, (0.0, 1.0), SIZE.0);
+        std::hint::black_box(out);
+        Ok(t.elapsed())
+    })?;
+    best_of("LTTB only", || {
+        let t = Instant::now();
+        let out = decimate::lttb(&points, 2 * SIZE.0 as usize);
+        std::hint::black_box(out);
+        Ok(t.elapsed())
+    })?;
+
+    Ok(())
+}
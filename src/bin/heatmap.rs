@@ -0,0 +1,378 @@
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use rand_distr::{Distribution, Normal};
+use rust_plot::cli::{parse_limits, Args, ThemeArgs};
+use rust_plot::color::Colormap;
+use rust_plot::data::{Column, Table};
+use rust_plot::contour::{auto_levels, contour_lines, draw_contour_lines, draw_contour_plot, ContourStyle};
+use rust_plot::heatmap::{draw_heatmap, draw_matrix, ColorScale, Grid, HeatmapStyle};
+use rust_plot::histogram::linspace;
+use rust_plot::line::draw_line;
+use rust_plot::render::{save_figure, select_examples, Canvas, Example, Figure};
+use rust_plot::rng::{seed, seed_from_args, seeded_rng};
+use rust_plot::stats::correlation_matrix;
+use rust_plot::theme::{theme, with_theme, Theme};
+use rust_plot::ticks::TickFormat;
+use rust_plot::units::{check_dpi, pt, with_dpi, FigureSize, DEFAULT_DPI};
+use std::error::Error;
+use std::f64::consts::PI;
+
+const FIG: FigureSize = FigureSize::inches(8.0, 16.0 / 3.0);
+const MATRIX_FIG: FigureSize = FigureSize::inches(6.5, 5.5); // near-square cells for matrices
+const PAIR_FIG: FigureSize = FigureSize::inches(12.0, 5.0); // two heatmaps side by side
+
+// 1) A 2-D function sampled on a grid built from two linspace ranges
+fn example_1_function(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    canvas.draw("heatmap_1_function", FIG, |root| {
+        let grid = Grid::from_fn(linspace(-PI, PI, 160), linspace(-PI, PI, 120), |x, y| {
+            x.sin() * y.cos() + 0.25 * (2.0 * x + y).sin()
+        });
+        draw_heatmap(
+            root,
+            "z = sin(x)·cos(y) + sin(2x + y)/4",
+            "x",
+            "y",
+            &grid,
+            &HeatmapStyle::default().label("z"),
+        )?;
+        Ok(())
+    })
+}
+
+// 2) Correlation matrix of simulated measurements with annotated cells
+fn example_2_correlation(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let mut rng = seeded_rng("heatmap_2");
+    let noise = Normal::new(0.0, 1.0)?;
+    let n = 500;
+    let mut columns = vec![Vec::new(); 6];
+    for _ in 0..n {
+        let (size, temp) = (noise.sample(&mut rng), noise.sample(&mut rng));
+        let values = [
+            size,
+            0.9 * size + 0.45 * noise.sample(&mut rng),
+            -0.6 * size + 0.8 * noise.sample(&mut rng),
+            temp,
+            0.7 * temp - 0.3 * size + 0.6 * noise.sample(&mut rng),
+            noise.sample(&mut rng),
+        ];
+        for (col, v) in columns.iter_mut().zip(values) {
+            col.push(v);
+        }
+    }
+    let names = ["Length", "Weight", "Speed", "Temperature", "Humidity", "Noise"];
+    let corr = correlation_matrix(&columns);
+
+    canvas.draw("heatmap_2_correlation", MATRIX_FIG, |root| {
+        draw_matrix(
+            root,
+            "Correlation of simulated measurements",
+            &names,
+            &names,
+            &corr,
+            &HeatmapStyle::default()
+                .range(-1.0, 1.0)
+                .annotate(TickFormat::Fixed(2))
+                .label("Pearson r"),
+        )
+    })
+}
+
+// 3) Peaks spanning six decades with linear and logarithmic color scales
+fn example_3_log_scale(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let peaks = [(-1.5, -1.0, 1000.0), (1.2, 1.4, 10.0), (1.5, -1.5, 0.1)];
+    let grid = Grid::from_fn(linspace(-3.0, 3.0, 150), linspace(-3.0, 3.0, 150), |x, y| {
+        let bump = |(px, py, a): (f64, f64, f64)| a * (-((x - px).powi(2) + (y - py).powi(2)) / 0.5).exp();
+        1e-3 + peaks.iter().copied().map(bump).sum::<f64>()
+    });
+
+    canvas.draw("heatmap_3_log_scale", PAIR_FIG, |root| {
+        let areas = root.split_evenly((1, 2));
+        let style = HeatmapStyle::default().colormap(Colormap::MAGMA).label("Intensity");
+        draw_heatmap(&areas[0], "Linear color scale", "x", "y", &grid, &style)?;
+        draw_heatmap(&areas[1], "Log color scale", "x", "y", &grid, &style.clone().log())?;
+        Ok(())
+    })
+}
+
+// --- CSV helper ---
+fn download_csv_text(url: &str) -> Result<String, Box<dyn Error>> {
+    let resp = reqwest::blocking::get(url)?;
+    let text = resp.text()?;
+
+    // Normalize whitespace-separated records into newline-separated CSV rows if needed.
+    let normalized = text.split_whitespace().collect::<Vec<_>>().join("\n");
+    Ok(normalized)
+}
+
+// 4) Correlation matrix of the Iris measurements
+fn example_4_iris_correlation(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let url = "https://raw.githubusercontent.com/mohammadijoo/Datasets/refs/heads/main/iris.csv";
+
+    // If the CSV is on your local drive:
+    // let table = Table::from_path("path/to/iris.csv", true)?;
+
+    let table = Table::read(std::io::Cursor::new(download_csv_text(url)?), true)?;
+    let names = ["sepal_length", "sepal_width", "petal_length", "petal_width"];
+    let columns = names
+        .iter()
+        .map(|n| table.numeric(&Column::Name(n.to_string())))
+        .collect::<Result<Vec<_>, _>>()?;
+    let corr = correlation_matrix(&columns);
+
+    canvas.draw("heatmap_4_iris_correlation", MATRIX_FIG, |root| {
+        draw_matrix(
+            root,
+            "Correlation of the Iris measurements",
+            &names,
+            &names,
+            &corr,
+            &HeatmapStyle::default()
+                .colormap(Colormap::CIVIDIS)
+                .range(-1.0, 1.0)
+                .annotate(TickFormat::Fixed(2))
+                .label("Pearson r"),
+        )
+    })
+}
+
+// 5) Labelled contours of Himmelblau's function with a gradient-descent path drawn over them
+fn example_5_contours(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let f = |x: f64, y: f64| (x * x + y - 11.0).powi(2) + (x + y * y - 7.0).powi(2);
+    let grid = Grid::from_fn(linspace(-5.0, 5.0, 201), linspace(-5.0, 5.0, 201), f);
+    let levels = [2.0, 10.0, 30.0, 75.0, 150.0, 300.0, 500.0];
+
+    // Steepest descent from (-0.5, -4.5) into one of the four minima
+    let mut path = vec![(-0.5, -4.5)];
+    for _ in 0..200 {
+        let (x, y) = path[path.len() - 1];
+        let dx = 4.0 * x * (x * x + y - 11.0) + 2.0 * (x + y * y - 7.0);
+        let dy = 2.0 * (x * x + y - 11.0) + 4.0 * y * (x + y * y - 7.0);
+        path.push((x - 0.005 * dx, y - 0.005 * dy));
+    }
+
+    canvas.draw("heatmap_5_contours", MATRIX_FIG, |root| {
+        let mut chart = draw_contour_plot(
+            root,
+            "Himmelblau's function",
+            "x",
+            "y",
+            &grid,
+            &levels,
+            &ContourStyle::default().labels(TickFormat::Fixed(0)),
+        )?;
+        let theme = theme();
+        draw_line(&mut chart, &path, theme.color(1).stroke_width(theme.stroke()))?;
+        chart.draw_series(
+            [path[0], path[path.len() - 1]]
+                .map(|p| Circle::new(p, pt(3.0), theme.color(1).filled())),
+        )?;
+        Ok(())
+    })
+}
+
+// 6) Filled contours beside the same grid as a heatmap with contour lines on top
+fn example_6_filled_contours(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let grid = Grid::from_fn(linspace(-PI, PI, 160), linspace(-PI, PI, 120), |x, y| {
+        x.sin() * y.cos() + 0.25 * (2.0 * x + y).sin()
+    });
+    let levels = auto_levels(&grid, 10);
+
+    canvas.draw("heatmap_6_filled_contours", PAIR_FIG, |root| {
+        let areas = root.split_evenly((1, 2));
+        let style = ContourStyle::default().filled().hide_lines().label("z");
+        draw_contour_plot(&areas[0], "Filled contours", "x", "y", &grid, &levels, &style)?;
+
+        let norm = HeatmapStyle::default().norm(grid.z.iter().flatten().copied())?;
+        let mut chart = draw_heatmap(&areas[1], "Heatmap with contour lines", "x", "y", &grid, &HeatmapStyle::default().label("z"))?;
+        let lines = ContourStyle::default().color(WHITE).width(0.75);
+        draw_contour_lines(&mut chart, &contour_lines(&grid, &levels), &norm, &lines)?;
+        Ok(())
+    })
+}
+
+/// Every example in the order `main` runs them.
+pub const EXAMPLES: &[Example] = &[
+    Example::new("1_function", example_1_function),
+    Example::new("2_correlation", example_2_correlation),
+    Example::new("3_log_scale", example_3_log_scale),
+    Example::online("4_iris_correlation", example_4_iris_correlation),
+    Example::new("5_contours", example_5_contours),
+    Example::new("6_filled_contours", example_6_filled_contours),
+];
+
+const USAGE: &str = "\
+Usage:
+  heatmap [--example N] [--seed N]          run the bundled examples (all by default), writing to output/
+  heatmap <data.csv|-> [options]            draw CSV columns as a matrix of cells ('-' reads stdin)
+
+Options:
+  --columns COLS        comma-separated columns (default: every numeric column)
+  --rows NAME|INDEX     column of row labels (default: row numbers)
+  --corr                draw the correlation matrix of the columns instead of their values
+  --scale SCALE         linear or log color scale (default linear)
+  --zlim MIN:MAX        color limits; either side may be empty (default: the data range, -1:1 with --corr)
+  --annotate N          write each value in its cell with N decimals
+  --label TEXT          colorbar caption (default: none, \"Pearson r\" with --corr)
+  --no-colorbar         leave out the colorbar
+  --title TEXT          chart title (default: the file name)
+  --size WxH            figure size with an in/cm suffix, or in pixels (default 6.5x5.5in)
+  --dpi N               resolution written into the PNG (default 300)
+  --theme NAME          light, dark, print, presentation or publication (default light)
+  --colormap NAME       viridis, magma, cividis or turbo, _r to reverse (default: the theme's)
+  --out PATH            .png or .svg output (default heatmap.png)
+  --no-header           the CSV has no header row
+  --seed N              seed for the bundled examples' random data
+";
+
+/// A matrix of CSV values, or of their correlations, built from the command line.
+struct CsvHeatmap {
+    title: String,
+    rows: Vec<String>,
+    columns: Vec<String>,
+    values: Vec<Vec<f64>>,
+    style: HeatmapStyle,
+}
+
+impl CsvHeatmap {
+    fn from_args(args: &mut Args) -> Result<Self, Box<dyn Error>> {
+        let [path] = args.positional() else {
+            return Err("Expected exactly one input file (see --help)".into());
+        };
+        let path = path.clone();
+        let table = Table::from_path(&path, !args.flag("no-header"))?;
+        if table.rows.is_empty() {
+            return Err(format!("{} has no data rows", path).into());
+        }
+
+        let row_col = args.parsed::<Column>("rows")?;
+        let row_idx = row_col.as_ref().map(|c| table.index_of(c)).transpose()?;
+        let mut columns = Vec::new();
+        match args.value("columns")? {
+            Some(list) => {
+                for c in list.split(',') {
+                    let col = c.parse::<Column>()?;
+                    columns.push((table.name_of(&col)?.to_string(), table.column(&col)?));
+                }
+            }
+            None => {
+                for i in (0..table.headers.len()).filter(|&i| Some(i) != row_idx) {
+                    if let Ok(col) = table.column(&Column::Index(i)) {
+                        columns.push((table.headers[i].clone(), col));
+                    }
+                }
+            }
+        }
+        if columns.is_empty() {
+            return Err("No numeric columns to draw; choose them with --columns".into());
+        }
+
+        let corr = args.flag("corr");
+        let mut style = HeatmapStyle {
+            scale: args.parsed::<ColorScale>("scale")?.unwrap_or_default(),
+            ..HeatmapStyle::default()
+        };
+        let names = columns.iter().map(|(n, _)| n.clone()).collect::<Vec<_>>();
+        let (rows, values) = if corr {
+            // Pairwise complete rows would differ per pair, so use the rows complete in every column
+            let complete = (0..table.rows.len())
+                .filter(|&r| columns.iter().all(|(_, c)| c[r].is_some_and(f64::is_finite)))
+                .collect::<Vec<_>>();
+            let data = columns
+                .iter()
+                .map(|(_, c)| complete.iter().map(|&r| c[r].unwrap_or(f64::NAN)).collect())
+                .collect::<Vec<_>>();
+            style = style.range(-1.0, 1.0).label("Pearson r");
+            (names.clone(), correlation_matrix(&data))
+        } else {
+            let rows = match row_idx {
+                Some(i) => table.rows.iter().map(|r| r.get(i).cloned().unwrap_or_default()).collect(),
+                None => (1..=table.rows.len()).map(|r| r.to_string()).collect(),
+            };
+            let values = (0..table.rows.len())
+                .map(|r| columns.iter().map(|(_, c)| c[r].unwrap_or(f64::NAN)).collect())
+                .collect();
+            (rows, values)
+        };
+
+        if let Some(lim) = args.value("zlim")? {
+            let (lo, hi) = parse_limits(&lim)?;
+            style.limits = (lo.or(style.limits.0), hi.or(style.limits.1));
+        }
+        if let Some(decimals) = args.parsed::<usize>("annotate")? {
+            style = style.annotate(TickFormat::Fixed(decimals));
+        }
+        if let Some(label) = args.value("label")? {
+            style = style.label(&label);
+        }
+        if args.flag("no-colorbar") {
+            style = style.hide_colorbar();
+        }
+
+        let title = args.value("title")?.unwrap_or_else(|| {
+            if corr {
+                format!("Correlation matrix of {}", path)
+            } else {
+                path.clone()
+            }
+        });
+        Ok(Self {
+            title,
+            rows,
+            columns: names,
+            values,
+            style,
+        })
+    }
+}
+
+impl Figure for CsvHeatmap {
+    fn draw<DB>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        draw_matrix(root, &self.title, &self.rows, &self.columns, &self.values, &self.style)
+    }
+}
+
+fn run_cli(mut args: Args) -> Result<(), Box<dyn Error>> {
+    let dpi = args.parsed::<f64>("dpi")?.unwrap_or(DEFAULT_DPI);
+    let size = match args.value("size")? {
+        Some(s) => FigureSize::parse(&s, dpi)?,
+        None => MATRIX_FIG.with_dpi(check_dpi(dpi)?),
+    };
+    let heatmap = with_dpi(size.dpi, || CsvHeatmap::from_args(&mut args))?;
+    let out = args.value("out")?.unwrap_or_else(|| "heatmap.png".to_string());
+    args.finish()?;
+
+    save_figure(&heatmap, &out, size)?;
+    println!("Wrote {}", out);
+    Ok(())
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = Args::parse(
+        seed_from_args(std::env::args().skip(1))?,
+        &["help", "no-header", "corr", "no-colorbar"],
+    )?;
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+    let theme = ThemeArgs::parse(&mut args)?.apply(Theme::default());
+    with_theme(theme, || {
+        if args.positional().is_empty() {
+            let only = args.value("example")?;
+            args.finish()?;
+            let selected = select_examples(EXAMPLES, only.as_deref())?;
+            println!("Seed: {}", seed());
+            let mut canvas = Canvas::to_dir("output")?;
+            for example in selected {
+                (example.run)(&mut canvas)?;
+            }
+            Ok(())
+        } else {
+            run_cli(args)
+        }
+    })
+}
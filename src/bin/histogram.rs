@@ -1,226 +1,46 @@
 use plotters::coord::Shift;
-use plotters::coord::types::RangedCoordf64;
 use plotters::prelude::*;
-use rand::prelude::*;
-use rand_distr::{Distribution, Normal};
-use std::cmp::Ordering;
+use rand::Rng;
+use rand_distr::{Distribution, Gamma, LogNormal, Normal, Poisson};
+use rust_plot::histogram::{
+    bins_auto, bins_fd, bins_scott, bins_sqrt, bins_sturges, counts_from_edges, draw_histogram, draw_histogram_with_errors,
+    draw_hist_bins, draw_histogram_with_residuals, draw_histograms, edges_from_bins, expected_counts, histogram_chart,
+    linspace, normalized_heights, pdf_to_height, poisson_errors, weighted_counts_from_edges, weighted_heights_and_errors,
+    BinRule, HistDataset, HistLayout, HistStyle, Normalization, Residual, MAX_BINS,
+};
+use rust_plot::boxplot::{
+    draw_box_plots, draw_split_violins, draw_violins, kde, silverman_bandwidth, BoxStyle, Group, SplitGroup,
+};
+use rust_plot::cli::{print_color_check, Args, ThemeArgs};
+use rust_plot::data::{Column, Table};
+use rust_plot::ecdf::{
+    draw_ecdfs, draw_prob_plot, pp_points, pp_points_two_sample, qq_points, qq_points_two_sample, EcdfSeries, ProbPlot,
+};
+use rust_plot::fit::{draw_fits, fit, Family};
+use rust_plot::gof::{anderson_darling, chi_square, draw_gof_box, ks_test};
+use rust_plot::render::{save_figure, select_examples, Canvas, Example, Figure};
+use rust_plot::rng::{seed, seed_from_args, seeded_rng};
+use rust_plot::stats::{data_min_max, normal_cdf, normal_quantile, quantiles, QuantileMethod};
+use rust_plot::stream::{StreamingHistogram, TDigest};
+use rust_plot::theme::{theme, with_theme, Theme};
+use rust_plot::units::{check_dpi, pt, with_dpi, FigureSize, DEFAULT_DPI};
 use std::error::Error;
-use std::fs;
 use std::thread;
 use std::time::Duration;
 
-const FIG_300DPI: (u32, u32) = (2400, 1600);       // ~8x5.33 inches @ 300 DPI
-const GRID_2X3_300DPI: (u32, u32) = (3600, 2400);  // larger for 2x3 comparisons
+const FIG: FigureSize = FigureSize::inches(8.0, 16.0 / 3.0);
+const GRID_2X3: FigureSize = FigureSize::inches(12.0, 8.0); // larger for 2x3 comparisons
 
-fn ensure_output_dir() -> Result<(), Box<dyn Error>> {
-    fs::create_dir_all("output")?;
-    Ok(())
-}
-
-fn with_png_root(path: &str, size: (u32, u32)) -> Result<DrawingArea<BitMapBackend<'_>, Shift>, Box<dyn Error>> {
-    let root = BitMapBackend::new(path, size).into_drawing_area();
-    root.fill(&WHITE)?;
-    Ok(root)
-}
-
-fn randn(n: usize, mu: f64, sigma: f64) -> Vec<f64> {
-    let mut rng = rand::rng();
+fn randn<R: Rng + ?Sized>(rng: &mut R, n: usize, mu: f64, sigma: f64) -> Vec<f64> {
     let dist = Normal::new(mu, sigma).unwrap();
-    (0..n).map(|_| dist.sample(&mut rng)).collect()
-}
-
-fn mean(x: &[f64]) -> f64 {
-    x.iter().sum::<f64>() / (x.len().max(1) as f64)
-}
-
-fn std_dev(x: &[f64]) -> f64 {
-    if x.len() < 2 {
-        return 0.0;
-    }
-    let m = mean(x);
-    let var = x.iter().map(|v| (v - m) * (v - m)).sum::<f64>() / ((x.len() - 1) as f64);
-    var.sqrt()
-}
-
-fn quantile(sorted: &[f64], q: f64) -> f64 {
-    if sorted.is_empty() {
-        return f64::NAN;
-    }
-    let n = sorted.len() as f64;
-    let pos = (n - 1.0) * q;
-    let lo = pos.floor() as usize;
-    let hi = pos.ceil() as usize;
-    if lo == hi {
-        sorted[lo]
-    } else {
-        let w = pos - (lo as f64);
-        sorted[lo] * (1.0 - w) + sorted[hi] * w
-    }
-}
-
-fn iqr(x: &[f64]) -> f64 {
-    let mut v = x.to_vec();
-    v.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
-    quantile(&v, 0.75) - quantile(&v, 0.25)
-}
-
-fn data_min_max(x: &[f64]) -> (f64, f64) {
-    let mut mn = f64::INFINITY;
-    let mut mx = f64::NEG_INFINITY;
-    for &v in x {
-        mn = mn.min(v);
-        mx = mx.max(v);
-    }
-    if mn == mx {
-        (mn - 1.0, mx + 1.0)
-    } else {
-        (mn, mx)
-    }
-}
-
-fn linspace(start: f64, end: f64, n: usize) -> Vec<f64> {
-    if n == 0 {
-        return vec![];
-    }
-    if n == 1 {
-        return vec![start];
-    }
-    let step = (end - start) / ((n - 1) as f64);
-    (0..n).map(|i| start + (i as f64) * step).collect()
-}
-
-fn edges_from_bins(minv: f64, maxv: f64, bins: usize) -> Vec<f64> {
-    linspace(minv, maxv, bins + 1)
-}
-
-fn counts_from_edges(data: &[f64], edges: &[f64]) -> Vec<usize> {
-    let nb = edges.len().saturating_sub(1);
-    let mut counts = vec![0usize; nb];
-
-    for &v in data {
-        for i in 0..nb {
-            let lo = edges[i];
-            let hi = edges[i + 1];
-            let in_bin = if i == nb - 1 {
-                v >= lo && v <= hi
-            } else {
-                v >= lo && v < hi
-            };
-            if in_bin {
-                counts[i] += 1;
-                break;
-            }
-        }
-    }
-    counts
-}
-
-#[derive(Clone, Copy)]
-enum Normalization {
-    Count,
-    CountDensity,
-    Probability,
-    Pdf,
-}
-
-fn normalized_heights(counts: &[usize], edges: &[f64], norm: Normalization) -> Vec<f64> {
-    let n = counts.iter().sum::<usize>().max(1) as f64;
-    let mut heights = Vec::with_capacity(counts.len());
-
-    for i in 0..counts.len() {
-        let c = counts[i] as f64;
-        let w = (edges[i + 1] - edges[i]).abs().max(1e-12);
-        let h = match norm {
-            Normalization::Count => c,
-            Normalization::CountDensity => c / w,
-            Normalization::Probability => c / n,
-            Normalization::Pdf => c / (n * w),
-        };
-        heights.push(h);
-    }
-    heights
-}
-
-fn draw_histogram(
-    root: &DrawingArea<BitMapBackend<'_>, Shift>,
-    title: &str,
-    x_label: &str,
-    y_label: &str,
-    edges: &[f64],
-    heights: &[f64],
-    style: ShapeStyle,
-) -> Result<(), Box<dyn Error>> {
-    let (xmin, xmax) = (*edges.first().unwrap(), *edges.last().unwrap());
-    let ymax = heights
-        .iter()
-        .cloned()
-        .fold(0.0f64, |a, b| a.max(b))
-        .max(1e-12);
-
-    let mut chart = ChartBuilder::on(root)
-        .caption(title, ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(60)
-        .y_label_area_size(80)
-        .build_cartesian_2d(xmin..xmax, 0.0f64..(ymax * 1.1))?;
-
-    chart
-        .configure_mesh()
-        .x_desc(x_label)
-        .y_desc(y_label)
-        .label_style(("sans-serif", 24))
-        .axis_desc_style(("sans-serif", 26))
-        .draw()?;
-
-    for i in 0..heights.len() {
-        let x0 = edges[i];
-        let x1 = edges[i + 1];
-        let h = heights[i];
-        chart.draw_series(std::iter::once(Rectangle::new(
-            [(x0, 0.0), (x1, h)],
-            style.clone().filled(),
-        )))?;
-    }
-
-    Ok(())
-}
-
-fn bins_sturges(n: usize) -> usize {
-    let n = n.max(1) as f64;
-    (n.log2() + 1.0).ceil().max(1.0) as usize
-}
-
-fn bins_sqrt(n: usize) -> usize {
-    ((n.max(1) as f64).sqrt().ceil().max(1.0)) as usize
-}
-
-fn bins_scott(data: &[f64]) -> usize {
-    let n = data.len().max(2) as f64;
-    let (mn, mx) = data_min_max(data);
-    let sd = std_dev(data).max(1e-12);
-    let bw = 3.5 * sd / n.powf(1.0 / 3.0);
-    let k = ((mx - mn) / bw).ceil().max(1.0) as usize;
-    k
-}
-
-fn bins_fd(data: &[f64]) -> usize {
-    let n = data.len().max(2) as f64;
-    let (mn, mx) = data_min_max(data);
-    let i = iqr(data).max(1e-12);
-    let bw = 2.0 * i / n.powf(1.0 / 3.0);
-    let k = ((mx - mn) / bw).ceil().max(1.0) as usize;
-    k
-}
-
-fn bins_auto(data: &[f64]) -> usize {
-    let k1 = bins_sturges(data.len());
-    let k2 = bins_fd(data);
-    k1.max(k2).max(1)
+    (0..n).map(|_| dist.sample(rng)).collect()
 }
 
 // 1) Simple histogram of standard normal data with automatic binning
-fn example_1() -> Result<(), Box<dyn Error>> {
-    let x1 = randn(10_000, 0.0, 1.0);
+fn example_1(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let mut rng = seeded_rng("histogram_1");
+    let x1 = randn(&mut rng, 10_000, 0.0, 1.0);
     let (mn, mx) = data_min_max(&x1);
 
     let bins = bins_auto(&x1);
@@ -230,77 +50,88 @@ fn example_1() -> Result<(), Box<dyn Error>> {
     let counts = counts_from_edges(&x1, &edges);
     let heights = normalized_heights(&counts, &edges, Normalization::Count);
 
-    let root = with_png_root("output/histogram_1.png", FIG_300DPI)?;
-    draw_histogram(
-        &root,
-        "Histogram of standard normal data",
-        "Value",
-        "Frequency",
-        &edges,
-        &heights,
-        BLUE.mix(0.55).stroke_width(1),
-    )?;
-    root.present()?;
-    Ok(())
+    canvas.draw("histogram_1", FIG, |root| {
+        draw_histogram(
+            root,
+            "Histogram of standard normal data",
+            "Value",
+            "Frequency",
+            &edges,
+            &heights,
+            theme.color(0).mix(0.55).stroke_width(pt(0.25)),
+        )?;
+        Ok(())
+    })
 }
 
 // 2) Compare binning rules with 2x3 panels
-fn example_2() -> Result<(), Box<dyn Error>> {
-    let x2 = randn(10_000, 0.0, 1.0);
+fn example_2(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let mut rng = seeded_rng("histogram_2");
+    let x2 = randn(&mut rng, 10_000, 0.0, 1.0);
     let (mn, mx) = data_min_max(&x2);
 
-    let root = with_png_root("output/histogram_2.png", GRID_2X3_300DPI)?;
-    let areas = root.split_evenly((2, 3));
-
-    let rules: Vec<(&str, usize)> = vec![
-        ("Automatic binning", bins_auto(&x2)),
-        ("Scott's rule", bins_scott(&x2)),
-        ("Freedman-Diaconis rule", bins_fd(&x2)),
-        ("Integers rule", ((mx.ceil() - mn.floor()).max(1.0) as usize)),
-        ("Sturges' rule", bins_sturges(x2.len())),
-        ("Square root rule", bins_sqrt(x2.len())),
-    ];
+    canvas.draw("histogram_2", GRID_2X3, |root| {
+        let areas = root.split_evenly((2, 3));
+
+        let rules: Vec<(&str, usize)> = vec![
+            ("Automatic binning", bins_auto(&x2)),
+            ("Scott's rule", bins_scott(&x2)),
+            ("Freedman-Diaconis rule", bins_fd(&x2, QuantileMethod::Type7)),
+            ("Integers rule", ((mx.ceil() - mn.floor()).max(1.0) as usize)),
+            ("Sturges' rule", bins_sturges(x2.len())),
+            ("Square root rule", bins_sqrt(x2.len())),
+        ];
+
+        // Quantile definitions differ most on small samples
+        let small = &x2[..12];
+        for method in QuantileMethod::ALL {
+            let q = quantiles(small, &[0.1, 0.25, 0.5, 0.75, 0.9], method);
+            println!("{method:?}: quantiles of 12 values {q:.3?}, FD bins for all data {}", bins_fd(&x2, method));
+        }
 
-    for (i, (title, bins)) in rules.iter().enumerate() {
-        let edges = if *title == "Integers rule" {
-            let lo = mn.floor();
-            let hi = mx.ceil();
-            let mut e = Vec::new();
-            let mut v = lo;
-            while v <= hi + 1e-9 {
-                e.push(v);
-                v += 1.0;
-            }
-            if e.len() < 2 {
-                vec![lo, lo + 1.0]
+        for (i, (title, bins)) in rules.iter().enumerate() {
+            let edges = if *title == "Integers rule" {
+                let lo = mn.floor();
+                let hi = mx.ceil();
+                let mut e = Vec::new();
+                let mut v = lo;
+                while v <= hi + 1e-9 {
+                    e.push(v);
+                    v += 1.0;
+                }
+                if e.len() < 2 {
+                    vec![lo, lo + 1.0]
+                } else {
+                    e
+                }
             } else {
-                e
-            }
-        } else {
-            edges_from_bins(mn, mx, (*bins).max(1))
-        };
-
-        let counts = counts_from_edges(&x2, &edges);
-        let heights = normalized_heights(&counts, &edges, Normalization::Count);
+                edges_from_bins(mn, mx, (*bins).max(1))
+            };
 
-        draw_histogram(
-            &areas[i],
-            title,
-            "Value",
-            "Frequency",
-            &edges,
-            &heights,
-            Palette99::pick(i).mix(0.55).stroke_width(1),
-        )?;
-    }
+            let counts = counts_from_edges(&x2, &edges);
+            let heights = normalized_heights(&counts, &edges, Normalization::Count);
+
+            draw_histogram(
+                &areas[i],
+                title,
+                "Value",
+                "Frequency",
+                &edges,
+                &heights,
+                theme.color(i).mix(0.55).stroke_width(pt(0.25)),
+            )?;
+        }
 
-    root.present()?;
-    Ok(())
+        Ok(())
+    })
 }
 
 // 3) Demonstrate changing the number of bins (writes multiple images)
-fn example_3() -> Result<(), Box<dyn Error>> {
-    let x3 = randn(1000, 0.0, 1.0);
+fn example_3(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let mut rng = seeded_rng("histogram_3");
+    let x3 = randn(&mut rng, 1000, 0.0, 1.0);
     let (mn, mx) = data_min_max(&x3);
 
     // Initial (auto)
@@ -310,21 +141,22 @@ fn example_3() -> Result<(), Box<dyn Error>> {
         let counts = counts_from_edges(&x3, &edges);
         let heights = normalized_heights(&counts, &edges, Normalization::Count);
 
-        let root = with_png_root("output/histogram_3_step_0.png", FIG_300DPI)?;
-        draw_histogram(
-            &root,
-            &format!("{bins0} bins"),
-            "Value",
-            "Frequency",
-            &edges,
-            &heights,
-            BLUE.mix(0.55).stroke_width(1),
-        )?;
-        root.present()?;
+        canvas.draw("histogram_3_step_0", FIG, |root| {
+            draw_histogram(
+                root,
+                &format!("{bins0} bins"),
+                "Value",
+                "Frequency",
+                &edges,
+                &heights,
+                theme.color(0).mix(0.55).stroke_width(pt(0.25)),
+            )?;
+            Ok(())
+        })?;
     }
 
     // Pause briefly (optional)
-    thread::sleep(Duration::from_millis(800));
+    canvas.pause(Duration::from_millis(800));
 
     // Updated to fixed bin count (50)
     let bins1 = 50usize;
@@ -333,25 +165,28 @@ fn example_3() -> Result<(), Box<dyn Error>> {
         let counts = counts_from_edges(&x3, &edges);
         let heights = normalized_heights(&counts, &edges, Normalization::Count);
 
-        let root = with_png_root("output/histogram_3_step_1.png", FIG_300DPI)?;
-        draw_histogram(
-            &root,
-            &format!("{bins1} bins"),
-            "Value",
-            "Frequency",
-            &edges,
-            &heights,
-            RED.mix(0.55).stroke_width(1),
-        )?;
-        root.present()?;
+        canvas.draw("histogram_3_step_1", FIG, |root| {
+            draw_histogram(
+                root,
+                &format!("{bins1} bins"),
+                "Value",
+                "Frequency",
+                &edges,
+                &heights,
+                theme.color(1).mix(0.55).stroke_width(pt(0.25)),
+            )?;
+            Ok(())
+        })?;
     }
 
     Ok(())
 }
 
 // 4) Histogram with custom bin edges and count-density normalization
-fn example_4() -> Result<(), Box<dyn Error>> {
-    let x4 = randn(10_000, 0.0, 1.0);
+fn example_4(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let mut rng = seeded_rng("histogram_4");
+    let x4 = randn(&mut rng, 10_000, 0.0, 1.0);
 
     let edges: Vec<f64> = vec![
         -10.0, -2.0, -1.75, -1.5, -1.25, -1.0, -0.75, -0.5, -0.25, 0.0, 0.25, 0.5, 0.75, 1.0,
@@ -361,22 +196,23 @@ fn example_4() -> Result<(), Box<dyn Error>> {
     let counts = counts_from_edges(&x4, &edges);
     let heights = normalized_heights(&counts, &edges, Normalization::CountDensity);
 
-    let root = with_png_root("output/histogram_4.png", FIG_300DPI)?;
-    draw_histogram(
-        &root,
-        "Histogram with custom bin edges",
-        "Value",
-        "Count density",
-        &edges,
-        &heights,
-        GREEN.mix(0.55).stroke_width(1),
-    )?;
-    root.present()?;
-    Ok(())
+    canvas.draw("histogram_4", FIG, |root| {
+        draw_histogram(
+            root,
+            "Histogram with custom bin edges",
+            "Value",
+            "Count density",
+            &edges,
+            &heights,
+            theme.color(2).mix(0.55).stroke_width(pt(0.25)),
+        )?;
+        Ok(())
+    })
 }
 
 // 5) Categorical histogram (bar chart) for discrete string categories
-fn example_5() -> Result<(), Box<dyn Error>> {
+fn example_5(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
     let categories: Vec<&str> = vec![
         "no", "no", "yes", "yes", "yes", "no", "no", "no", "no", "undecided", "undecided", "yes",
         "no", "no", "no", "yes", "no", "yes", "no", "yes", "no", "no", "no", "yes", "yes", "yes",
@@ -396,44 +232,42 @@ fn example_5() -> Result<(), Box<dyn Error>> {
 
     let ymax = counts.iter().cloned().max().unwrap_or(1) as i32;
 
-    let root = with_png_root("output/histogram_5.png", FIG_300DPI)?;
-    let mut chart = ChartBuilder::on(&root)
-        .caption("Histogram of categorical responses", ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(60)
-        .y_label_area_size(80)
-        .build_cartesian_2d(0i32..(unique.len() as i32), 0i32..(ymax + 2))?;
-
-    chart
-        .configure_mesh()
-        .x_desc("Category")
-        .y_desc("Count")
-        .x_labels(unique.len())
-        .x_label_formatter(&|v| {
-            let i = (*v as usize).min(unique.len().saturating_sub(1));
-            unique[i].clone()
-        })
-        .label_style(("sans-serif", 24))
-        .axis_desc_style(("sans-serif", 26))
-        .draw()?;
-
-    for (i, &c) in counts.iter().enumerate() {
-        let x0 = i as i32;
-        let x1 = x0 + 1;
-        chart.draw_series(std::iter::once(Rectangle::new(
-            [(x0, 0), (x1, c as i32)],
-            BLUE.mix(0.55).filled(),
-        )))?;
-    }
+    canvas.draw("histogram_5", FIG, |root| {
+        let mut chart = theme
+            .chart(root)
+            .caption("Histogram of categorical responses", theme.title_font())
+            .build_cartesian_2d(0i32..(unique.len() as i32), 0i32..(ymax + 2))?;
+
+        theme
+            .mesh(&mut chart.configure_mesh())
+            .x_desc("Category")
+            .y_desc("Count")
+            .x_labels(unique.len())
+            .x_label_formatter(&|v| {
+                let i = (*v as usize).min(unique.len().saturating_sub(1));
+                unique[i].clone()
+            })
+            .draw()?;
 
-    root.present()?;
-    Ok(())
+        for (i, &c) in counts.iter().enumerate() {
+            let x0 = i as i32;
+            let x1 = x0 + 1;
+            chart.draw_series(std::iter::once(Rectangle::new(
+                [(x0, 0), (x1, c as i32)],
+                theme.color(0).mix(0.55).filled(),
+            )))?;
+        }
+
+        Ok(())
+    })
 }
 
 // 6) Overlay normalized histograms for two different normal distributions
-fn example_6() -> Result<(), Box<dyn Error>> {
-    let x = randn(2000, 0.0, 1.0);
-    let y = randn(5000, 1.0, 1.0);
+fn example_6(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let mut rng = seeded_rng("histogram_6");
+    let x = randn(&mut rng, 2000, 0.0, 1.0);
+    let y = randn(&mut rng, 5000, 1.0, 1.0);
 
     let (mn1, mx1) = data_min_max(&x);
     let (mn2, mx2) = data_min_max(&y);
@@ -454,73 +288,29 @@ fn example_6() -> Result<(), Box<dyn Error>> {
         edges = vec![lo, lo + bin_width];
     }
 
-    let cx = counts_from_edges(&x, &edges);
-    let cy = counts_from_edges(&y, &edges);
-
-    let hx = normalized_heights(&cx, &edges, Normalization::Probability);
-    let hy = normalized_heights(&cy, &edges, Normalization::Probability);
-
-    let root = with_png_root("output/histogram_6.png", FIG_300DPI)?;
-
-    // Draw first
-    draw_histogram(
-        &root,
-        "Overlaid normalized histograms",
-        "Value",
-        "Probability",
-        &edges,
-        &hx,
-        BLUE.mix(0.40).stroke_width(1),
-    )?;
-
-    // Overlay second by drawing rectangles again
-    {
-        let (xmin, xmax) = (*edges.first().unwrap(), *edges.last().unwrap());
-        let ymax = hx
-            .iter()
-            .chain(hy.iter())
-            .cloned()
-            .fold(0.0f64, |a, b| a.max(b))
-            .max(1e-12);
-
-        let mut chart = ChartBuilder::on(&root)
-            .caption("Overlaid normalized histograms", ("sans-serif", 40))
-            .margin(20)
-            .x_label_area_size(60)
-            .y_label_area_size(80)
-            .build_cartesian_2d(xmin..xmax, 0.0f64..(ymax * 1.1))?;
-
-        chart
-            .configure_mesh()
-            .x_desc("Value")
-            .y_desc("Probability")
-            .label_style(("sans-serif", 24))
-            .axis_desc_style(("sans-serif", 26))
-            .draw()?;
-
-        // Blue layer
-        for i in 0..hx.len() {
-            chart.draw_series(std::iter::once(Rectangle::new(
-                [(edges[i], 0.0), (edges[i + 1], hx[i])],
-                BLUE.mix(0.40).filled(),
-            )))?;
-        }
-        // Red layer
-        for i in 0..hy.len() {
-            chart.draw_series(std::iter::once(Rectangle::new(
-                [(edges[i], 0.0), (edges[i + 1], hy[i])],
-                RED.mix(0.40).filled(),
-            )))?;
-        }
-    }
+    canvas.draw("histogram_6", FIG, |root| {
+        draw_histograms(
+            root,
+            "Overlaid normalized histograms",
+            "Value",
+            "Probability",
+            &edges,
+            &[
+                HistDataset::from_data("N(0, 1), n = 2000", &x, &edges, Normalization::Probability, theme.color(0).mix(0.40)),
+                HistDataset::from_data("N(1, 1), n = 5000", &y, &edges, Normalization::Probability, theme.color(1).mix(0.40)),
+            ],
+            HistLayout::Overlay,
+        )?;
 
-    root.present()?;
-    Ok(())
+        Ok(())
+    })
 }
 
 // 7) Histogram normalized to PDF overlaid with theoretical normal distribution
-fn example_7() -> Result<(), Box<dyn Error>> {
-    let x = randn(5000, 5.0, 2.0);
+fn example_7(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let mut rng = seeded_rng("histogram_7");
+    let x = randn(&mut rng, 5000, 5.0, 2.0);
     let (mn, mx) = data_min_max(&x);
 
     let bins = bins_auto(&x);
@@ -532,48 +322,58 @@ fn example_7() -> Result<(), Box<dyn Error>> {
     let sigma = 2.0;
     let inv = 1.0 / (sigma * (2.0 * std::f64::consts::PI).sqrt());
 
-    let root = with_png_root("output/histogram_7.png", FIG_300DPI)?;
-    let (xmin, xmax) = (*edges.first().unwrap(), *edges.last().unwrap());
-    let ymax_hist = heights.iter().cloned().fold(0.0f64, |a, b| a.max(b));
-    let ymax_pdf = inv;
-    let ymax = ymax_hist.max(ymax_pdf).max(1e-12);
-
-    let mut chart = ChartBuilder::on(&root)
-        .caption("Histogram with theoretical normal PDF", ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(60)
-        .y_label_area_size(80)
-        .build_cartesian_2d(xmin..xmax, 0.0f64..(ymax * 1.2))?;
-
-    chart
-        .configure_mesh()
-        .x_desc("Value")
-        .y_desc("Probability density")
-        .label_style(("sans-serif", 24))
-        .axis_desc_style(("sans-serif", 26))
-        .draw()?;
-
-    // PDF-normalized histogram
-    for i in 0..heights.len() {
-        chart.draw_series(std::iter::once(Rectangle::new(
-            [(edges[i], 0.0), (edges[i + 1], heights[i])],
-            BLUE.mix(0.45).filled(),
-        )))?;
-    }
+    canvas.draw("histogram_7", FIG, |root| {
+        let (xmin, xmax) = (*edges.first().unwrap(), *edges.last().unwrap());
+        let ymax_hist = heights.iter().cloned().fold(0.0f64, |a, b| a.max(b));
+        let ymax_pdf = inv;
+        let ymax = ymax_hist.max(ymax_pdf).max(1e-12);
 
-    // Theoretical PDF curve
-    let npts = 600usize;
-    let series = (0..=npts).map(|i| {
-        let t = i as f64 / (npts as f64);
-        let xx = xmin + t * (xmax - xmin);
-        let z = (xx - mu) / sigma;
-        let yy = (-0.5 * z * z).exp() * inv;
-        (xx, yy)
-    });
-    chart.draw_series(LineSeries::new(series, RED.stroke_width(3)))?;
+        let mut chart = theme
+            .chart(root)
+            .caption("Histogram with theoretical normal PDF", theme.title_font())
+            .build_cartesian_2d(xmin..xmax, 0.0f64..(ymax * 1.2))?;
 
-    root.present()?;
-    Ok(())
+        theme
+            .mesh(&mut chart.configure_mesh())
+            .x_desc("Value")
+            .y_desc("Probability density")
+            .draw()?;
+
+        // PDF-normalized histogram
+        for i in 0..heights.len() {
+            chart.draw_series(std::iter::once(Rectangle::new(
+                [(edges[i], 0.0), (edges[i + 1], heights[i])],
+                theme.color(0).mix(0.45).filled(),
+            )))?;
+        }
+
+        // Theoretical PDF curve
+        let npts = 600usize;
+        let series = (0..=npts).map(|i| {
+            let t = i as f64 / (npts as f64);
+            let xx = xmin + t * (xmax - xmin);
+            let z = (xx - mu) / sigma;
+            let yy = (-0.5 * z * z).exp() * inv;
+            (xx, yy)
+        });
+        chart.draw_series(LineSeries::new(series, theme.color(1).stroke_width(theme.stroke())))?;
+
+        // Goodness of fit against the generating distribution
+        let cdf = |v: f64| normal_cdf(v, mu, sigma);
+        let pdf = |v: f64| (-0.5 * ((v - mu) / sigma).powi(2)).exp() * inv;
+        let expected = expected_counts(pdf, &edges, x.len() as f64);
+        let results = [
+            chi_square(&counts, &expected, 0),
+            ks_test(&x, cdf),
+            anderson_darling(&x, cdf),
+        ];
+        for r in &results {
+            println!("{}", r.summary());
+        }
+        draw_gof_box(&chart.plotting_area().strip_coord_spec(), &results, SeriesLabelPosition::UpperLeft)?;
+
+        Ok(())
+    })
 }
 
 // --- CSV helper ---
@@ -587,7 +387,8 @@ fn download_csv_text(url: &str) -> Result<String, Box<dyn Error>> {
 }
 
 // 8) CSV-driven histogram
-fn example_8_csv_hist() -> Result<(), Box<dyn Error>> {
+fn example_8_csv_hist(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
     let url = "https://raw.githubusercontent.com/mohammadijoo/Datasets/refs/heads/main/iris.csv";
 
     // If the CSV is on your local drive:
@@ -615,32 +416,673 @@ fn example_8_csv_hist() -> Result<(), Box<dyn Error>> {
     let counts = counts_from_edges(&values, &edges);
     let heights = normalized_heights(&counts, &edges, Normalization::Count);
 
-    let root = with_png_root("output/histogram_8_csv.png", FIG_300DPI)?;
-    draw_histogram(
-        &root,
-        "CSV histogram: sepal_length (Iris)",
-        "sepal_length",
-        "Frequency",
-        &edges,
-        &heights,
-        GREEN.mix(0.55).stroke_width(1),
-    )?;
-    root.present()?;
+    canvas.draw("histogram_8_csv", FIG, |root| {
+        draw_histogram(
+            root,
+            "CSV histogram: sepal_length (Iris)",
+            "sepal_length",
+            "Frequency",
+            &edges,
+            &heights,
+            theme.color(2).mix(0.55).stroke_width(pt(0.25)),
+        )?;
+        Ok(())
+    })?;
 
     Ok(())
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    ensure_output_dir()?;
+// 9) Three datasets on shared edges in each multi-dataset layout
+fn example_9_layouts(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let mut rng = seeded_rng("histogram_9");
+    let a = randn(&mut rng, 3000, -1.0, 0.8);
+    let b = randn(&mut rng, 2000, 0.5, 1.0);
+    let c = randn(&mut rng, 1000, 2.0, 0.6);
+
+    let (mn, mx) = data_min_max(&[a.as_slice(), b.as_slice(), c.as_slice()].concat());
+    let edges = edges_from_bins(mn, mx, 30);
+    let datasets = [
+        HistDataset::from_data("A", &a, &edges, Normalization::Count, theme.color(0).mix(0.55)),
+        HistDataset::from_data("B", &b, &edges, Normalization::Count, theme.color(1).mix(0.55)),
+        HistDataset::from_data("C", &c, &edges, Normalization::Count, theme.color(2).mix(0.55)),
+    ];
+
+    canvas.draw("histogram_9_layouts", GRID_2X3, |root| {
+        let areas = root.split_evenly((2, 2));
+        let layouts = [
+            ("Overlaid", HistLayout::Overlay),
+            ("Stacked", HistLayout::Stacked),
+            ("Side by side", HistLayout::Dodged),
+            ("Step outlines", HistLayout::Step),
+        ];
+        for (area, (title, layout)) in areas.iter().zip(layouts) {
+            draw_histograms(area, title, "Value", "Frequency", &edges, &datasets, layout)?;
+        }
 
-    example_1()?;
-    example_2()?;
-    example_3()?;
-    example_4()?;
-    example_5()?;
-    example_6()?;
-    example_7()?;
-    example_8_csv_hist()?;
+        Ok(())
+    })
+}
+
+// 10) Display styles: bars with edges and gaps, stairs, stairs with a faint fill
+fn example_10_display_styles(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let mut rng = seeded_rng("histogram_10");
+    let x = randn(&mut rng, 5000, 0.0, 1.0);
+    let (mn, mx) = data_min_max(&x);
+    let edges = edges_from_bins(mn, mx, 30);
+    let counts = counts_from_edges(&x, &edges);
+    let heights = normalized_heights(&counts, &edges, Normalization::Count);
+
+    canvas.draw("histogram_10_display_styles", FigureSize::inches(12.0, 4.0), |root| {
+        let areas = root.split_evenly((1, 3));
+        let styles = [
+            ("Bars with edges, 80% width", HistStyle::bars(theme.color(0).mix(0.55)).edge(theme.foreground.stroke_width(pt(0.5))).bar_width(0.8)),
+            ("Stairs", HistStyle::stairs(theme.color(0).stroke_width(theme.stroke()))),
+            ("Stairs with faint fill", HistStyle::stairs(theme.color(0).stroke_width(theme.stroke())).fill_alpha(0.15)),
+        ];
+        for (area, (title, style)) in areas.iter().zip(styles) {
+            draw_histogram(area, title, "Value", "Frequency", &edges, &heights, style)?;
+        }
+
+        Ok(())
+    })
+}
+
+// 11) Statistical uncertainties: multinomial errors on a PDF histogram, √Σw² on a weighted one
+fn example_11_error_bars(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let mut rng = seeded_rng("histogram_11");
+    let x = randn(&mut rng, 400, 0.0, 1.0);
+    let edges = edges_from_bins(-3.5, 3.5, 20);
+    let counts = counts_from_edges(&x, &edges);
+    let heights = normalized_heights(&counts, &edges, Normalization::Pdf);
+    let errors = poisson_errors(&counts, &edges, Normalization::Pdf);
+
+    // Detector-style efficiency weights that grow with the value
+    let w = x.iter().map(|v| 1.0 / (0.4 + 0.2 * (v + 3.5))).collect::<Vec<_>>();
+    let (sum_w, sum_w2) = weighted_counts_from_edges(&x, &w, &edges);
+    let (w_heights, w_errors) = weighted_heights_and_errors(&sum_w, &sum_w2, &edges, Normalization::Count);
+
+    canvas.draw("histogram_11_error_bars", FigureSize::inches(12.0, 14.0 / 3.0), |root| {
+        let areas = root.split_evenly((1, 2));
+        draw_histogram_with_errors(
+            &areas[0],
+            "PDF with multinomial errors, N = 400",
+            "Value",
+            "Density",
+            &edges,
+            &heights,
+            &errors,
+            HistStyle::bars(theme.color(0).mix(0.45)).edge(theme.foreground.stroke_width(pt(0.5))).bar_width(0.9),
+        )?;
+        draw_histogram_with_errors(
+            &areas[1],
+            "Weighted counts with √Σw² band",
+            "Value",
+            "Sum of weights",
+            &edges,
+            &w_heights,
+            &w_errors,
+            HistStyle::stairs(theme.color(1).stroke_width(theme.stroke())),
+        )?;
+
+        Ok(())
+    })
+}
+
+// 12) Data vs model with ratio and pull panels (same data and model as example 7)
+fn example_12_residuals(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let mut rng = seeded_rng("histogram_12");
+    let x = randn(&mut rng, 5000, 5.0, 2.0);
+    let (mn, mx) = data_min_max(&x);
+    let edges = edges_from_bins(mn, mx, bins_auto(&x));
+    let counts = counts_from_edges(&x, &edges);
+
+    let (mu, sigma) = (5.0, 2.0);
+    let pdf = |v: f64| {
+        let z = (v - mu) / sigma;
+        (-0.5 * z * z).exp() / (sigma * (2.0 * std::f64::consts::PI).sqrt())
+    };
+
+    canvas.draw("histogram_12_residuals", FigureSize::inches(12.0, 6.0), |root| {
+        let areas = root.split_evenly((1, 2));
+        draw_histogram_with_residuals(
+            &areas[0],
+            "Data vs N(5, 2): ratio",
+            "Value",
+            &edges,
+            &counts,
+            &pdf,
+            Normalization::Pdf,
+            Residual::Ratio,
+        )?;
+        draw_histogram_with_residuals(
+            &areas[1],
+            "Data vs N(5, 2): pulls",
+            "Value",
+            &edges,
+            &counts,
+            &pdf,
+            Normalization::Count,
+            Residual::Pull,
+        )?;
+
+        Ok(())
+    })
+}
+
+// 13) Maximum-likelihood fits overlaid on PDF-normalized histograms
+fn example_13_fits(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let mut rng = seeded_rng("histogram_13");
+    let normal = randn(&mut rng, 3000, 5.0, 2.0);
+    let gamma = Gamma::new(2.0, 1.5)?;
+    let waits = (0..3000).map(|_| gamma.sample(&mut rng)).collect::<Vec<f64>>();
+    let poisson = Poisson::new(4.2)?;
+    let events = (0..3000).map(|_| poisson.sample(&mut rng)).collect::<Vec<f64>>();
+
+    canvas.draw("histogram_13_fits", FigureSize::inches(12.0, 4.0), |root| {
+        let areas = root.split_evenly((1, 3));
+
+        // Normal data: fitted instead of assumed μ and σ (compare example 7)
+        let (mn, mx) = data_min_max(&normal);
+        let edges = edges_from_bins(mn, mx, bins_auto(&normal));
+        let fits = [fit(Family::Normal, &normal)?];
+        draw_fits(&areas[0], "Normal MLE", "Value", &normal, &edges, &fits)?;
+
+        // Skewed positive data: compare three candidate families
+        let (_, mx) = data_min_max(&waits);
+        let edges = edges_from_bins(0.0, mx, bins_fd(&waits, QuantileMethod::default()));
+        let fits = [
+            fit(Family::Gamma, &waits)?,
+            fit(Family::Weibull, &waits)?,
+            fit(Family::LogNormal, &waits)?,
+        ];
+        draw_fits(&areas[1], "Gamma vs Weibull vs lognormal", "Waiting time", &waits, &edges, &fits)?;
+
+        // Counts: unit-width bins centered on the integers
+        let (_, mx) = data_min_max(&events);
+        let edges = edges_from_bins(-0.5, mx + 0.5, mx as usize + 1);
+        let fits = [fit(Family::Poisson, &events)?];
+        draw_fits(&areas[2], "Poisson MLE", "Events per interval", &events, &edges, &fits)?;
+
+        for f in [fit(Family::Gamma, &waits)?, fit(Family::Exponential, &waits)?] {
+            println!("{}", f.summary_lines().join(", "));
+        }
+
+        Ok(())
+    })
+}
+
+// 14) Distribution comparisons without binning: ECDFs with DKW bands, Q-Q and P-P plots
+fn example_14_ecdf_qq(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let mut rng = seeded_rng("histogram_14");
+    let a = randn(&mut rng, 300, 0.0, 1.0);
+    let gamma = Gamma::new(3.0, 1.0)?;
+    let b = (0..300).map(|_| gamma.sample(&mut rng) - 3.0).collect::<Vec<f64>>();
+
+    canvas.draw("histogram_14_ecdf_qq", GRID_2X3, |root| {
+        let areas = root.split_evenly((2, 3));
+
+        let series = [
+            EcdfSeries::new("Normal(0, 1)", a.clone(), theme.color(0)),
+            EcdfSeries::new("Gamma(3, 1) − 3", b.clone(), theme.color(1)),
+        ];
+        draw_ecdfs(&areas[0], "Empirical CDFs", "Value", &series, Some(0.05))?;
+
+        let std_normal = |p: f64| normal_quantile(p, 0.0, 1.0);
+        draw_prob_plot(
+            &areas[1],
+            "Normal Q-Q: normal sample",
+            "Theoretical quantile",
+            "Sample quantile",
+            &qq_points(&a, std_normal),
+            ProbPlot::QQ,
+        )?;
+        draw_prob_plot(
+            &areas[2],
+            "Normal Q-Q: skewed sample",
+            "Theoretical quantile",
+            "Sample quantile",
+            &qq_points(&b, std_normal),
+            ProbPlot::QQ,
+        )?;
+        draw_prob_plot(
+            &areas[3],
+            "P-P: skewed sample vs N(0, √3)",
+            "Theoretical CDF",
+            "Empirical CDF",
+            &pp_points(&b, |v| normal_cdf(v, 0.0, 3f64.sqrt())),
+            ProbPlot::PP,
+        )?;
+
+        draw_prob_plot(
+            &areas[4],
+            "Two-sample Q-Q",
+            "Normal sample quantile",
+            "Skewed sample quantile",
+            &qq_points_two_sample(&a, &b),
+            ProbPlot::QQ,
+        )?;
+        draw_prob_plot(
+            &areas[5],
+            "Two-sample P-P",
+            "Normal sample ECDF",
+            "Skewed sample ECDF",
+            &pp_points_two_sample(&a, &b),
+            ProbPlot::PP,
+        )?;
+
+        Ok(())
+    })
+}
+
+// 15) Box plots and violin plots comparing several groups
+fn example_15_box_violin(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let mut rng = seeded_rng("histogram_15");
+    let gamma = Gamma::new(2.0, 1.0)?;
+    let skewed = (0..400).map(|_| gamma.sample(&mut rng) + 2.0).collect::<Vec<f64>>();
+    let mut bimodal = randn(&mut rng, 200, 2.5, 0.6);
+    bimodal.extend(randn(&mut rng, 200, 6.0, 0.8));
+    let mut heavy = randn(&mut rng, 390, 4.0, 1.0);
+    heavy.extend([9.5, 10.2, -1.5, 11.0, -2.2, 9.0, 10.8, -0.8, 12.1, 8.9]);
+
+    let groups = [
+        Group::new("Normal", randn(&mut rng, 400, 4.0, 1.2), theme.color(0)),
+        Group::new("Skewed", skewed, theme.color(1)),
+        Group::new("Bimodal", bimodal, theme.color(2)),
+        Group::new("Heavy tails", heavy, theme.color(3)),
+    ];
+
+    let doses = ["Low", "Medium", "High"];
+    let split = doses
+        .iter()
+        .enumerate()
+        .map(|(i, d)| SplitGroup::new(d, randn(&mut rng, 300, 5.0, 1.0), randn(&mut rng, 300, 5.0 + 0.8 * (i + 1) as f64, 1.0 + 0.3 * i as f64)))
+        .collect::<Vec<_>>();
+
+    canvas.draw("histogram_15_box_violin", FigureSize::inches(12.0, 4.0), |root| {
+        let areas = root.split_evenly((1, 3));
+        draw_box_plots(
+            &areas[0],
+            "Notched box plots (▲ = mean)",
+            "Value",
+            &groups,
+            &BoxStyle::default().notched().mean_marker(),
+        )?;
+        draw_violins(&areas[1], "Violin plots", "Value", &groups, 0.8)?;
+        draw_split_violins(
+            &areas[2],
+            "Split violins by dose",
+            "Response",
+            &split,
+            ["Control", "Treatment"],
+            [theme.color(0).into(), theme.color(1).into()],
+        )?;
+
+        Ok(())
+    })
+}
 
+// 16) Streaming: per-thread accumulators over "files" of latencies, merged at the end
+fn example_16_streaming(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    let lognormal = LogNormal::new(3.0, 0.6)?;
+    let files = (0..4)
+        .map(|i| {
+            let mut rng = seeded_rng(&format!("histogram_16_file_{}", i));
+            (0..250_000).map(|_| lognormal.sample(&mut rng)).collect::<Vec<f64>>()
+        })
+        .collect::<Vec<_>>();
+
+    // Pass 1: auto-expanding bins and a t-digest per file
+    let partials = thread::scope(|s| {
+        let handles = files
+            .iter()
+            .map(|file| {
+                s.spawn(move || {
+                    let mut hist = StreamingHistogram::auto(0.5).max_bins(200);
+                    let mut digest = TDigest::default();
+                    for &v in file {
+                        hist.push(v);
+                        digest.push(v);
+                    }
+                    (hist, digest)
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>()
+    });
+    let mut hist = StreamingHistogram::auto(0.5);
+    let mut digest = TDigest::default();
+    for (h, d) in &partials {
+        hist.merge(h)?;
+        digest.merge(d);
+    }
+
+    println!("Example 16: {} values from {} files, mean = {:.2}, sd = {:.2}", hist.len(), files.len(), hist.mean(), hist.std_dev());
+    let all = files.concat();
+    let exact = quantiles(&all, &[0.5, 0.9, 0.99, 0.999], QuantileMethod::default());
+    for (q, e) in [0.5, 0.9, 0.99, 0.999].iter().zip(&exact) {
+        println!("  p{:<5} t-digest = {:8.3}  exact = {:8.3}", q * 100.0, digest.quantile(*q), e);
+    }
+
+    // Pass 2: Freedman–Diaconis edges chosen from the sketch, up to p99.9
+    let (lo, _) = digest.min_max();
+    let hi = digest.quantile(0.999);
+    let bins = ((hi - lo) / digest.fd_bin_width()).ceil().clamp(1.0, MAX_BINS as f64) as usize;
+    let edges = edges_from_bins(lo, hi, bins);
+    let fixed = thread::scope(|s| {
+        let handles = files
+            .iter()
+            .map(|file| {
+                let edges = edges.clone();
+                s.spawn(move || {
+                    let mut h = StreamingHistogram::fixed(edges);
+                    h.extend(file.iter().copied());
+                    h
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>()
+    });
+    let mut fd = StreamingHistogram::fixed(edges.clone());
+    for h in &fixed {
+        fd.merge(h)?;
+    }
+    println!("  FD from sketch: {} bins, {} values above p99.9", bins, fd.out_of_range().1);
+
+    canvas.draw("histogram_16_streaming", FigureSize::inches(12.0, 4.0), |root| {
+        let areas = root.split_evenly((1, 2));
+        let auto_edges = hist.edges();
+        draw_histogram(
+            &areas[0],
+            &format!("Merged auto-expanding bins (width {:.1})", auto_edges[1] - auto_edges[0]),
+            "Latency (ms)",
+            "Count",
+            &auto_edges,
+            &hist.heights(Normalization::Count),
+            theme.color(0).mix(0.6).filled(),
+        )?;
+        draw_histogram(
+            &areas[1],
+            &format!("FD bins from t-digest IQR ({} bins, ≤ p99.9)", bins),
+            "Latency (ms)",
+            "Density",
+            &edges,
+            &fd.heights(Normalization::Pdf),
+            HistStyle::stairs(theme.color(1).stroke_width(theme.stroke())),
+        )?;
+
+        Ok(())
+    })
+}
+
+const USAGE: &str = "\
+Usage:
+  histogram [--example N] [--seed N]        run the bundled examples (all by default), writing to output/
+  histogram <data.csv|-> [options]          plot one CSV column ('-' reads stdin)
+
+Options:
+  --column NAME|INDEX   column to plot, by header name or 0-based index (default 0)
+  --bins RULE           auto, sturges, sqrt, scott, fd, a bin count, or width=W (default auto)
+  --norm NORM           count, count-density, probability or pdf (default count)
+  --overlay DIST        fitted curve: normal, lognormal, exponential, gamma, weibull, poisson
+                        or kde; repeat or comma-separate for several
+  --style STYLE         bars or stairs (default bars)
+  --title TEXT          chart title (default \"Histogram of <column>\")
+  --xlabel TEXT         x-axis label (default: column name)
+  --ylabel TEXT         y-axis label (default: from --norm)
+  --size WxH            figure size with an in/cm suffix, or in pixels (default 8x5.333in)
+  --dpi N               resolution written into the PNG (default 300)
+  --theme NAME          light, dark, print, presentation or publication (default light)
+  --palette NAME        series colors: basic, dark, gray, okabe-ito or tableau10 (default: the theme's)
+  --colormap NAME       viridis, magma, cividis or turbo, _r to reverse (default: the theme's)
+  --check-colors        print whether the series colors stay distinguishable with color blindness
+  --out PATH            .png or .svg output (default histogram.png)
+  --no-header           the CSV has no header row
+  --seed N              seed for the bundled examples' random data
+";
+
+/// A fitted or smoothed curve drawn over the CLI histogram.
+enum Overlay {
+    Fit(Family),
+    Kde,
+}
+
+impl std::str::FromStr for Overlay {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("kde") {
+            Ok(Overlay::Kde)
+        } else {
+            Ok(Overlay::Fit(s.parse()?))
+        }
+    }
+}
+
+/// An overlay curve, already scaled to the histogram's normalization.
+struct Curve {
+    label: String,
+    points: Vec<(f64, f64)>,
+    /// Drawn as points at the integers instead of a line.
+    discrete: bool,
+}
+
+/// Everything the CLI needs to draw one histogram.
+struct CsvHistogram {
+    title: String,
+    x_label: String,
+    y_label: String,
+    edges: Vec<f64>,
+    heights: Vec<f64>,
+    style: HistStyle,
+    curves: Vec<Curve>,
+}
+
+impl CsvHistogram {
+    fn from_args(args: &mut Args) -> Result<Self, Box<dyn Error>> {
+        let [path] = args.positional() else {
+            return Err("Expected exactly one input file (see --help)".into());
+        };
+        let path = path.clone();
+        let has_headers = !args.flag("no-header");
+        let table = Table::from_path(&path, has_headers)?;
+        let column = args.parsed::<Column>("column")?.unwrap_or(Column::Index(0));
+        let data = table.numeric(&column)?;
+        if data.is_empty() {
+            return Err(format!("Column {} has no numeric values", column).into());
+        }
+
+        let rule = args.parsed::<BinRule>("bins")?.unwrap_or(BinRule::Auto);
+        let norm = args.parsed::<Normalization>("norm")?.unwrap_or(Normalization::Count);
+        let edges = rule.edges(&data)?;
+        let counts = counts_from_edges(&data, &edges);
+        let heights = normalized_heights(&counts, &edges, norm);
+
+        let theme = theme();
+        let style = match args.value("style")?.as_deref() {
+            None | Some("bars") => HistStyle::bars(theme.color(0).mix(0.55).filled()).edge(theme.color(0).stroke_width(pt(0.25))),
+            Some("stairs") => HistStyle::stairs(theme.color(0).stroke_width(theme.stroke())),
+            Some(other) => return Err(format!("Unknown --style '{}' (bars, stairs)", other).into()),
+        };
+
+        let mut overlays = Vec::new();
+        for value in args.values("overlay")? {
+            for name in value.split(',').filter(|n| !n.trim().is_empty()) {
+                overlays.push(name.parse::<Overlay>()?);
+            }
+        }
+        let width = edges[1] - edges[0];
+        let n = data.len() as f64;
+        let (lo, hi) = (edges[0], edges[edges.len() - 1]);
+        let grid = linspace(lo, hi, 600);
+        let scale = |pts: Vec<(f64, f64)>| {
+            pts.into_iter()
+                .map(|(x, p)| (x, pdf_to_height(p, norm, n, width)))
+                .collect::<Vec<_>>()
+        };
+        let mut curves = Vec::new();
+        for overlay in overlays {
+            match overlay {
+                Overlay::Kde => {
+                    let bw = silverman_bandwidth(&data);
+                    let density = kde(&data, bw, &grid);
+                    curves.push(Curve {
+                        label: format!("KDE (bw = {:.3})", bw),
+                        points: scale(grid.iter().copied().zip(density).collect()),
+                        discrete: false,
+                    });
+                }
+                Overlay::Fit(family) => {
+                    let fitted = fit(family, &data)?;
+                    let params = fitted
+                        .params
+                        .iter()
+                        .map(|p| format!("{} = {:.3}", p.name, p.value))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let pts = if family.is_discrete() {
+                        (lo.ceil().max(0.0) as i64..=hi.floor() as i64)
+                            .map(|k| (k as f64, fitted.pdf(k as f64)))
+                            .collect()
+                    } else {
+                        grid.iter().map(|&x| (x, fitted.pdf(x))).collect()
+                    };
+                    curves.push(Curve {
+                        label: format!("{} fit ({})", family.name(), params),
+                        points: scale(pts),
+                        discrete: family.is_discrete(),
+                    });
+                }
+            }
+        }
+
+        let name = table.name_of(&column)?.to_string();
+        Ok(Self {
+            title: args.value("title")?.unwrap_or_else(|| format!("Histogram of {}", name)),
+            y_label: args.value("ylabel")?.unwrap_or_else(|| norm.axis_label().to_string()),
+            x_label: args.value("xlabel")?.unwrap_or(name),
+            edges,
+            heights,
+            style,
+            curves,
+        })
+    }
+
+    /// The bar color, then the overlay curve colors.
+    fn colors(&self) -> Vec<RGBColor> {
+        let (r, g, b) = self.style.color.color.rgb();
+        let theme = theme();
+        std::iter::once(RGBColor(r, g, b))
+            .chain((0..self.curves.len()).map(|i| theme.color(i + 1)))
+            .collect()
+    }
+}
+
+impl Figure for CsvHistogram {
+    fn draw<DB>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        let ymax = self
+            .heights
+            .iter()
+            .chain(self.curves.iter().flat_map(|c| c.points.iter().map(|p| &p.1)))
+            .cloned()
+            .filter(|v| v.is_finite())
+            .fold(0.0f64, f64::max);
+        let mut chart = histogram_chart(root, &self.title, &self.x_label, &self.y_label, &self.edges, ymax)?;
+        draw_hist_bins(&mut chart, &self.edges, &self.heights, &self.style)?;
+
+        // The first palette color is taken by the histogram
+        let theme = theme();
+        let len = pt(7.0) as i32;
+        for (i, curve) in self.curves.iter().enumerate() {
+            let style = theme.color(i + 1).stroke_width(theme.stroke());
+            let anno = if curve.discrete {
+                chart.draw_series(curve.points.iter().map(|&p| Circle::new(p, pt(1.5), style.filled())))?
+            } else {
+                chart.draw_series(LineSeries::new(curve.points.iter().copied(), style))?
+            };
+            anno.label(curve.label.as_str())
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + len, y)], style));
+        }
+        if !self.curves.is_empty() {
+            theme
+                .legend(&mut chart.configure_series_labels())
+                .position(SeriesLabelPosition::UpperRight)
+                .draw()?;
+        }
+        Ok(())
+    }
+}
+
+fn run_cli(mut args: Args) -> Result<(), Box<dyn Error>> {
+    let dpi = args.parsed::<f64>("dpi")?.unwrap_or(DEFAULT_DPI);
+    let size = match args.value("size")? {
+        Some(s) => FigureSize::parse(&s, dpi)?,
+        None => FIG.with_dpi(check_dpi(dpi)?),
+    };
+    // Styles are sized in points, so build them at the output's DPI
+    let hist = with_dpi(size.dpi, || CsvHistogram::from_args(&mut args))?;
+    let out = args.value("out")?.unwrap_or_else(|| "histogram.png".to_string());
+    let check_colors = args.flag("check-colors");
+    args.finish()?;
+
+    save_figure(&hist, &out, size)?;
+    println!("Wrote {}", out);
+    if check_colors {
+        println!("Bar and curve colors:");
+        print_color_check(&hist.colors());
+    }
     Ok(())
 }
+
+/// Every example in the order `main` runs them.
+pub const EXAMPLES: &[Example] = &[
+    Example::new("1", example_1),
+    Example::new("2", example_2),
+    Example::new("3", example_3),
+    Example::new("4", example_4),
+    Example::new("5", example_5),
+    Example::new("6", example_6),
+    Example::new("7", example_7),
+    Example::online("8_csv_hist", example_8_csv_hist),
+    Example::new("9_layouts", example_9_layouts),
+    Example::new("10_display_styles", example_10_display_styles),
+    Example::new("11_error_bars", example_11_error_bars),
+    Example::new("12_residuals", example_12_residuals),
+    Example::new("13_fits", example_13_fits),
+    Example::new("14_ecdf_qq", example_14_ecdf_qq),
+    Example::new("15_box_violin", example_15_box_violin),
+    Example::new("16_streaming", example_16_streaming),
+];
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = Args::parse(seed_from_args(std::env::args().skip(1))?, &["help", "no-header", "check-colors"])?;
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+    let theme = ThemeArgs::parse(&mut args)?.apply(Theme::default());
+    with_theme(theme, || {
+        if args.positional().is_empty() {
+            let only = args.value("example")?;
+            args.finish()?;
+            let selected = select_examples(EXAMPLES, only.as_deref())?;
+            println!("Seed: {}", seed());
+            let mut canvas = Canvas::to_dir("output")?;
+            for example in selected {
+                (example.run)(&mut canvas)?;
+            }
+            Ok(())
+        } else {
+            run_cli(args)
+        }
+    })
+}
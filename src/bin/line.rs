@@ -1,13 +1,25 @@
 use plotters::coord::Shift;
-use plotters::coord::types::RangedCoordf64;
 use plotters::prelude::*;
 use rand::prelude::*;
+use rust_plot::cli::{parse_legend, parse_limits, print_color_check, Args, ThemeArgs};
+use rust_plot::color::{Colormap, Vision, BASIC_PALETTE, OKABE_ITO, TABLEAU_10};
+use rust_plot::data::{Column, Table};
+use rust_plot::line::{
+    draw_dual_axis_lines, draw_lines, draw_mesh_f64, padded_range, AxisScale, AxisSpec, LineFormat, LineSpec, YAxis,
+};
+use rust_plot::markers::{MarkerPlacement, MarkerShape, Markers};
+use rust_plot::render::{save_figure, select_examples, Canvas, Example, Figure};
+use rust_plot::rng::{seed, seed_from_args, seeded_rng};
+use rust_plot::ticks::TickFormat;
+use rust_plot::time::{draw_time_mesh, parse_timestamp, TimeAxis};
+use rust_plot::theme::{theme, with_theme, Theme};
+use rust_plot::units::{check_dpi, pt, with_dpi, FigureSize, DEFAULT_DPI};
 use std::error::Error;
 use std::f64::consts::PI;
-use std::fs;
+use std::ops::Range;
 
-const FIG_300DPI: (u32, u32) = (2400, 1600); // ~8x5.33 inches @ 300 DPI
-const BIG_GRID_300DPI: (u32, u32) = (3600, 2400); // larger canvas for multi-panel figures
+const FIG: FigureSize = FigureSize::inches(8.0, 16.0 / 3.0);
+const BIG_GRID: FigureSize = FigureSize::inches(12.0, 8.0); // larger canvas for multi-panel figures
 
 fn linspace(start: f64, end: f64, n: usize) -> Vec<f64> {
     if n == 0 {
@@ -20,410 +32,345 @@ fn linspace(start: f64, end: f64, n: usize) -> Vec<f64> {
     (0..n).map(|i| start + (i as f64) * step).collect()
 }
 
-fn ensure_output_dir() -> Result<(), Box<dyn Error>> {
-    fs::create_dir_all("output")?;
-    Ok(())
-}
+// 1) Multiple line plots on same axes
+fn example_1(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    canvas.draw("line_1_multiple", FIG, |root| {
+        let x = linspace(0.0, 2.0 * PI, 200);
+        let y = x.iter().map(|&v| v.sin()).collect::<Vec<_>>();
 
-fn with_png_root(path: &str, size: (u32, u32)) -> Result<DrawingArea<BitMapBackend<'_>, Shift>, Box<dyn Error>> {
-    let root = BitMapBackend::new(path, size).into_drawing_area();
-    root.fill(&WHITE)?;
-    Ok(root)
-}
+        let y_neg = y.iter().map(|&v| -v).collect::<Vec<_>>();
+        let y_lin = x.iter().map(|&v| v / PI - 1.0).collect::<Vec<_>>();
 
-fn draw_mesh_f64(
-    chart: &mut ChartContext<'_, BitMapBackend<'_>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
-    x_desc: &str,
-    y_desc: &str,
-) -> Result<(), Box<dyn Error>> {
-    chart
-        .configure_mesh()
-        .x_desc(x_desc)
-        .y_desc(y_desc)
-        .label_style(("sans-serif", 24))
-        .axis_desc_style(("sans-serif", 26))
-        .draw()?;
-    Ok(())
-}
+        let y_k = [1.0, 0.7, 0.4, 0.0, -0.4, -0.7, -1.0];
+        let x_k = (0..y_k.len()).map(|i| i as f64).collect::<Vec<_>>();
 
-// 1) Multiple line plots on same axes
-fn example_1() -> Result<(), Box<dyn Error>> {
-    let root = with_png_root("output/line_1_multiple.png", FIG_300DPI)?;
-    let x = linspace(0.0, 2.0 * PI, 200);
-    let y = x.iter().map(|&v| v.sin()).collect::<Vec<_>>();
-
-    let y_neg = y.iter().map(|&v| -v).collect::<Vec<_>>();
-    let y_lin = x.iter().map(|&v| v / PI - 1.0).collect::<Vec<_>>();
-
-    let y_k = vec![1.0, 0.7, 0.4, 0.0, -0.4, -0.7, -1.0];
-    let x_k = (0..y_k.len()).map(|i| i as f64).collect::<Vec<_>>();
-
-    let mut chart = ChartBuilder::on(&root)
-        .caption("Multiple line plots", ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(60)
-        .y_label_area_size(70)
-        .build_cartesian_2d(0.0f64..(2.0 * PI), -1.5f64..1.5f64)?;
-
-    draw_mesh_f64(&mut chart, "x", "y")?;
-
-    // sin(x) + markers
-    chart.draw_series(LineSeries::new(
-        x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
-        &BLUE,
-    ))?;
-    chart.draw_series(
-        x.iter()
-            .zip(y.iter())
-            .enumerate()
-            .filter(|(i, _)| i % 10 == 0)
-            .map(|(_, (&a, &b))| Circle::new((a, b), 5, BLUE.filled())),
-    )?;
+        let mut chart = theme
+            .chart(root)
+            .caption("Multiple line plots", theme.title_font())
+            .build_cartesian_2d(0.0f64..(2.0 * PI), -1.5f64..1.5f64)?;
 
-    // -sin(x) (different color)
-    chart.draw_series(LineSeries::new(
-        x.iter().zip(y_neg.iter()).map(|(&a, &b)| (a, b)),
-        &RED,
-    ))?;
-
-    // linear transform
-    chart.draw_series(LineSeries::new(
-        x.iter().zip(y_lin.iter()).map(|(&a, &b)| (a, b)),
-        &GREEN,
-    ))?;
-
-    // short manual series (black)
-    chart.draw_series(LineSeries::new(
-        x_k.iter().zip(y_k.iter()).map(|(&a, &b)| (a, b)),
-        &BLACK,
-    ))?;
-
-    root.present()?;
-    Ok(())
-}
+        draw_mesh_f64(&mut chart, "x", "y")?;
 
-// 2) Plot from collection of vectors
-fn example_2() -> Result<(), Box<dyn Error>> {
-    let root = with_png_root("output/line_2_vectors.png", FIG_300DPI)?;
-
-    let ys: Vec<Vec<f64>> = vec![
-        vec![16.0, 5.0, 9.0, 4.0],
-        vec![2.0, 11.0, 7.0, 14.0],
-        vec![3.0, 10.0, 6.0, 15.0],
-        vec![13.0, 8.0, 12.0, 1.0],
-    ];
-
-    let mut chart = ChartBuilder::on(&root)
-        .caption("Multiple line plots (collection of vectors)", ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(60)
-        .y_label_area_size(70)
-        .build_cartesian_2d(0.0f64..3.0f64, 0.0f64..18.0f64)?;
-
-    draw_mesh_f64(&mut chart, "x", "y")?;
-
-    for (idx, yv) in ys.iter().enumerate() {
-        let x = (0..yv.len()).map(|i| i as f64).collect::<Vec<_>>();
-        let style = Palette99::pick(idx).stroke_width(3);
+        // sin(x) + markers
         chart.draw_series(LineSeries::new(
-            x.iter().zip(yv.iter()).map(|(&a, &b)| (a, b)),
-            style,
+            x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
+            theme.color(0).stroke_width(theme.stroke()),
         ))?;
-    }
-
-    root.present()?;
-    Ok(())
-}
-
-// 3) Sin function line plots
-fn example_3() -> Result<(), Box<dyn Error>> {
-    let root = with_png_root("output/line_3_sin_family.png", FIG_300DPI)?;
-    let x = linspace(0.0, 2.0 * PI, 300);
-
-    let y1 = x.iter().map(|&v| v.sin()).collect::<Vec<_>>();
-    let y2 = x.iter().map(|&v| (v - 0.25).sin()).collect::<Vec<_>>();
-    let y3 = x.iter().map(|&v| (v - 0.5).sin()).collect::<Vec<_>>();
-
-    let mut chart = ChartBuilder::on(&root)
-        .caption("Sin() function line plots", ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(60)
-        .y_label_area_size(70)
-        .build_cartesian_2d(0.0f64..(2.0 * PI), -1.3f64..1.3f64)?;
-
-    draw_mesh_f64(&mut chart, "x", "y")?;
-
-    chart.draw_series(LineSeries::new(x.iter().zip(y1.iter()).map(|(&a, &b)| (a, b)), &BLUE))?;
-    chart.draw_series(LineSeries::new(x.iter().zip(y2.iter()).map(|(&a, &b)| (a, b)), &RED))?;
-    chart.draw_series(LineSeries::new(x.iter().zip(y3.iter()).map(|(&a, &b)| (a, b)), &GREEN))?;
-
-    root.present()?;
-    Ok(())
-}
-
-// 4) Sin function line plots with markers
-fn example_4() -> Result<(), Box<dyn Error>> {
-    let root = with_png_root("output/line_4_sin_markers.png", FIG_300DPI)?;
-    let x = linspace(0.0, 2.0 * PI, 220);
-
-    let y1 = x.iter().map(|&v| v.sin()).collect::<Vec<_>>();
-    let y2 = x.iter().map(|&v| (v - 0.25).sin()).collect::<Vec<_>>();
-    let y3 = x.iter().map(|&v| (v - 0.5).sin()).collect::<Vec<_>>();
-
-    let mut chart = ChartBuilder::on(&root)
-        .caption("Sin() function line plots with markers", ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(60)
-        .y_label_area_size(70)
-        .build_cartesian_2d(0.0f64..(2.0 * PI), -1.3f64..1.3f64)?;
-
-    draw_mesh_f64(&mut chart, "x", "y")?;
-
-    chart.draw_series(LineSeries::new(x.iter().zip(y1.iter()).map(|(&a, &b)| (a, b)), &GREEN))?;
-    chart.draw_series(LineSeries::new(x.iter().zip(y2.iter()).map(|(&a, &b)| (a, b)), &BLUE))?;
-    chart.draw_series(LineSeries::new(x.iter().zip(y3.iter()).map(|(&a, &b)| (a, b)), &CYAN))?;
-
-    // Markers on each series
-    chart.draw_series(
-        x.iter()
-            .zip(y1.iter())
-            .enumerate()
-            .filter(|(i, _)| i % 12 == 0)
-            .map(|(_, (&a, &b))| Circle::new((a, b), 5, GREEN.filled())),
-    )?;
-    chart.draw_series(
-        x.iter()
-            .zip(y2.iter())
-            .enumerate()
-            .filter(|(i, _)| i % 12 == 0)
-            .map(|(_, (&a, &b))| TriangleMarker::new((a, b), 6, BLUE.filled())),
-    )?;
-    chart.draw_series(
-        x.iter()
-            .zip(y3.iter())
-            .enumerate()
-            .filter(|(i, _)| i % 12 == 0)
-            .map(|(_, (&a, &b))| Cross::new((a, b), 6, CYAN.filled())),
-    )?;
+        Markers::new(MarkerShape::Circle, MarkerPlacement::EveryN(10), theme.color(0).filled())
+            .draw(&mut chart, x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)))?;
 
-    root.present()?;
-    Ok(())
-}
-
-// 5) Simple 2x1 layout (separate figure)
-fn example_5() -> Result<(), Box<dyn Error>> {
-    let root = with_png_root("output/line_5_tiled.png", (2400, 2200))?;
-    let areas = root.split_evenly((2, 1));
-
-    let x = linspace(0.0, 3.0, 250);
-    let y1 = x.iter().map(|&v| (5.0 * v).sin()).collect::<Vec<_>>();
-    let y2 = x.iter().map(|&v| (15.0 * v).sin()).collect::<Vec<_>>();
-
-    // Top
-    {
-        let mut chart = ChartBuilder::on(&areas[0])
-            .caption("Top Plot", ("sans-serif", 34))
-            .margin(15)
-            .x_label_area_size(45)
-            .y_label_area_size(60)
-            .build_cartesian_2d(0.0f64..3.0f64, -1.3f64..1.3f64)?;
-        chart
-            .configure_mesh()
-            .x_desc("x")
-            .y_desc("sin(5x)")
-            .label_style(("sans-serif", 22))
-            .axis_desc_style(("sans-serif", 24))
-            .draw()?;
+        // -sin(x) (different color)
         chart.draw_series(LineSeries::new(
-            x.iter().zip(y1.iter()).map(|(&a, &b)| (a, b)),
-            &BLUE,
+            x.iter().zip(y_neg.iter()).map(|(&a, &b)| (a, b)),
+            theme.color(1).stroke_width(theme.stroke()),
         ))?;
-    }
 
-    // Bottom
-    {
-        let mut chart = ChartBuilder::on(&areas[1])
-            .caption("Bottom Plot", ("sans-serif", 34))
-            .margin(15)
-            .x_label_area_size(45)
-            .y_label_area_size(60)
-            .build_cartesian_2d(0.0f64..3.0f64, -1.3f64..1.3f64)?;
-        chart
-            .configure_mesh()
-            .x_desc("x")
-            .y_desc("sin(15x)")
-            .label_style(("sans-serif", 22))
-            .axis_desc_style(("sans-serif", 24))
-            .draw()?;
+        // linear transform
         chart.draw_series(LineSeries::new(
-            x.iter().zip(y2.iter()).map(|(&a, &b)| (a, b)),
-            &RED,
+            x.iter().zip(y_lin.iter()).map(|(&a, &b)| (a, b)),
+            theme.color(2).stroke_width(theme.stroke()),
         ))?;
-    }
-
-    root.present()?;
-    Ok(())
-}
-
-// 6) 3x2 subplots in a single figure (6 subplots total)
-fn example_6() -> Result<(), Box<dyn Error>> {
-    let root = with_png_root("output/line_6_grid_3x2.png", BIG_GRID_300DPI)?;
-    let areas = root.split_evenly((3, 2));
-
-    // (0,0): sin(x) with marker indices
-    {
-        let x = linspace(0.0, 10.0, 100);
-        let y = x.iter().map(|&v| v.sin()).collect::<Vec<_>>();
-        let mut chart = ChartBuilder::on(&areas[0])
-            .caption("sin(x) with marker indices", ("sans-serif", 26))
-            .margin(10)
-            .x_label_area_size(40)
-            .y_label_area_size(50)
-            .build_cartesian_2d(0.0f64..10.0f64, -1.3f64..1.3f64)?;
-        draw_mesh_f64(&mut chart, "x", "sin(x)")?;
 
+        // short manual series (black)
         chart.draw_series(LineSeries::new(
-            x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
-            &BLUE,
+            x_k.iter().zip(y_k.iter()).map(|(&a, &b)| (a, b)),
+            theme.foreground.stroke_width(theme.stroke()),
         ))?;
 
-        let marker_indices: Vec<usize> = (0..100).step_by(5).collect();
-        chart.draw_series(marker_indices.into_iter().map(|i| {
-            Circle::new((x[i], y[i]), 5, BLUE.filled())
-        }))?;
-    }
+        Ok(())
+    })
+}
 
-    // (0,1): tan(sin(x)) - sin(tan(x))
-    {
-        let x = linspace(-PI, PI, 20);
-        let y = x
-            .iter()
-            .map(|&v| (v.sin()).tan() - (v.tan()).sin())
-            .collect::<Vec<_>>();
+// 2) Plot from collection of vectors
+fn example_2(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    canvas.draw("line_2_vectors", FIG, |root| {
+        let ys: Vec<Vec<f64>> = vec![
+            vec![16.0, 5.0, 9.0, 4.0],
+            vec![2.0, 11.0, 7.0, 14.0],
+            vec![3.0, 10.0, 6.0, 15.0],
+            vec![13.0, 8.0, 12.0, 1.0],
+        ];
+
+        let mut chart = theme
+            .chart(root)
+            .caption("Multiple line plots (collection of vectors)", theme.title_font())
+            .build_cartesian_2d(0.0f64..3.0f64, 0.0f64..18.0f64)?;
 
-        let mut chart = ChartBuilder::on(&areas[1])
-            .caption("tan(sin(x)) - sin(tan(x))", ("sans-serif", 26))
-            .margin(10)
-            .x_label_area_size(40)
-            .y_label_area_size(50)
-            .build_cartesian_2d(-PI..PI, -5.0f64..5.0f64)?;
         draw_mesh_f64(&mut chart, "x", "y")?;
 
-        chart.draw_series(LineSeries::new(
-            x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
-            GREEN.stroke_width(3),
-        ))?;
-        chart.draw_series(
-            x.iter()
-                .zip(y.iter())
-                .map(|(&a, &b)| Circle::new((a, b), 3, BLACK.filled())),
-        )?;
-    }
+        for (idx, yv) in ys.iter().enumerate() {
+            let x = (0..yv.len()).map(|i| i as f64).collect::<Vec<_>>();
+            let style = theme.color(idx).stroke_width(theme.stroke());
+            chart.draw_series(LineSeries::new(
+                x.iter().zip(yv.iter()).map(|(&a, &b)| (a, b)),
+                style,
+            ))?;
+        }
+
+        Ok(())
+    })
+}
 
-    // (1,0): cos(5x)
-    {
-        let x = linspace(0.0, 10.0, 150);
-        let y = x.iter().map(|&v| (5.0 * v).cos()).collect::<Vec<_>>();
-
-        let mut chart = ChartBuilder::on(&areas[2])
-            .caption("2-D Line Plot", ("sans-serif", 26))
-            .margin(10)
-            .x_label_area_size(40)
-            .y_label_area_size(50)
-            .build_cartesian_2d(0.0f64..10.0f64, -1.3f64..1.3f64)?;
-        draw_mesh_f64(&mut chart, "x", "cos(5x)")?;
-        chart.draw_series(LineSeries::new(
-            x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
-            CYAN.stroke_width(3),
-        ))?;
-    }
+// 3) Sin function line plots
+fn example_3(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    canvas.draw("line_3_sin_family", FIG, |root| {
+        let x = linspace(0.0, 2.0 * PI, 300);
 
-    // (1,1): time plot with custom tick labels
-    {
-        let x: Vec<i32> = vec![0, 30, 60, 90, 120, 150, 180];
-        let y: Vec<f64> = vec![0.8, 0.9, 0.1, 0.9, 0.6, 0.1, 0.3];
-
-        let mut chart = ChartBuilder::on(&areas[3])
-            .caption("Time Plot", ("sans-serif", 26))
-            .margin(10)
-            .x_label_area_size(40)
-            .y_label_area_size(50)
-            .build_cartesian_2d(0i32..180i32, 0.0f64..1.0f64)?;
-
-        chart
-            .configure_mesh()
-            .x_desc("Time")
-            .y_desc("Value")
-            .x_labels(7)
-            .x_label_formatter(&|v| match *v {
-                0 => "00:00s".to_string(),
-                30 => "30:00".to_string(),
-                60 => "01:00".to_string(),
-                90 => "01:30".to_string(),
-                120 => "02:00".to_string(),
-                150 => "02:30".to_string(),
-                180 => "03:00".to_string(),
-                _ => format!("{v}"),
-            })
-            .label_style(("sans-serif", 20))
-            .axis_desc_style(("sans-serif", 24))
-            .draw()?;
+        let y1 = x.iter().map(|&v| v.sin()).collect::<Vec<_>>();
+        let y2 = x.iter().map(|&v| (v - 0.25).sin()).collect::<Vec<_>>();
+        let y3 = x.iter().map(|&v| (v - 0.5).sin()).collect::<Vec<_>>();
 
-        chart.draw_series(LineSeries::new(
-            x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
-            BLUE.stroke_width(3),
-        ))?;
-        chart.draw_series(x.iter().zip(y.iter()).map(|(&a, &b)| Circle::new((a, b), 5, RED.filled())))?;
-    }
+        let mut chart = theme
+            .chart(root)
+            .caption("Sin() function line plots", theme.title_font())
+            .build_cartesian_2d(0.0f64..(2.0 * PI), -1.3f64..1.3f64)?;
 
-    // (2,0): sin(5x)
-    {
-        let x = linspace(0.0, 3.0, 200);
-        let y = x.iter().map(|&v| (5.0 * v).sin()).collect::<Vec<_>>();
-
-        let mut chart = ChartBuilder::on(&areas[4])
-            .caption("sin(5x)", ("sans-serif", 26))
-            .margin(10)
-            .x_label_area_size(40)
-            .y_label_area_size(50)
-            .build_cartesian_2d(0.0f64..3.0f64, -1.3f64..1.3f64)?;
         draw_mesh_f64(&mut chart, "x", "y")?;
-        chart.draw_series(LineSeries::new(
-            x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
-            BLACK.stroke_width(3),
-        ))?;
-    }
 
-    // (2,1): circle
-    {
-        let r = 2.0;
-        let xc = 4.0;
-        let yc = 3.0;
+        let width = theme.stroke();
+        chart.draw_series(LineSeries::new(x.iter().zip(y1.iter()).map(|(&a, &b)| (a, b)), theme.color(0).stroke_width(width)))?;
+        chart.draw_series(LineSeries::new(x.iter().zip(y2.iter()).map(|(&a, &b)| (a, b)), theme.color(1).stroke_width(width)))?;
+        chart.draw_series(LineSeries::new(x.iter().zip(y3.iter()).map(|(&a, &b)| (a, b)), theme.color(2).stroke_width(width)))?;
+
+        Ok(())
+    })
+}
 
-        let theta = linspace(0.0, 2.0 * PI, 400);
-        let xs = theta.iter().map(|&t| r * t.cos() + xc).collect::<Vec<_>>();
-        let ys = theta.iter().map(|&t| r * t.sin() + yc).collect::<Vec<_>>();
+// 4) Sin function line plots with markers
+fn example_4(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    canvas.draw("line_4_sin_markers", FIG, |root| {
+        let x = linspace(0.0, 2.0 * PI, 220);
 
-        // This panel is wider than it is tall; choose axis ranges that compensate to keep the circle visually round.
-        let y_span = 4.0;
-        let x_span = 2.0 * y_span;
+        let y1 = x.iter().map(|&v| v.sin()).collect::<Vec<_>>();
+        let y2 = x.iter().map(|&v| (v - 0.25).sin()).collect::<Vec<_>>();
+        let y3 = x.iter().map(|&v| (v - 0.5).sin()).collect::<Vec<_>>();
 
-        let mut chart = ChartBuilder::on(&areas[5])
-            .caption("Circle", ("sans-serif", 26))
-            .margin(10)
-            .x_label_area_size(40)
-            .y_label_area_size(50)
-            .build_cartesian_2d((xc - x_span)..(xc + x_span), (yc - y_span)..(yc + y_span))?;
+        let mut chart = theme
+            .chart(root)
+            .caption("Sin() function line plots with markers", theme.title_font())
+            .build_cartesian_2d(0.0f64..(2.0 * PI), -1.3f64..1.3f64)?;
 
         draw_mesh_f64(&mut chart, "x", "y")?;
-        chart.draw_series(LineSeries::new(
-            xs.iter().zip(ys.iter()).map(|(&a, &b)| (a, b)),
-            BLUE.stroke_width(3),
-        ))?;
-    }
 
-    root.present()?;
-    Ok(())
+        let width = theme.stroke();
+        chart.draw_series(LineSeries::new(x.iter().zip(y1.iter()).map(|(&a, &b)| (a, b)), theme.color(2).stroke_width(width)))?;
+        chart.draw_series(LineSeries::new(x.iter().zip(y2.iter()).map(|(&a, &b)| (a, b)), theme.color(0).stroke_width(width)))?;
+        chart.draw_series(LineSeries::new(x.iter().zip(y3.iter()).map(|(&a, &b)| (a, b)), theme.color(4).stroke_width(width)))?;
+
+        // Markers on each series
+        Markers::new(MarkerShape::Circle, MarkerPlacement::EveryN(12), theme.color(2).filled())
+            .draw(&mut chart, x.iter().zip(y1.iter()).map(|(&a, &b)| (a, b)))?;
+        Markers::new(MarkerShape::Triangle, MarkerPlacement::EveryN(12), theme.color(0).filled())
+            .size(1.5)
+            .draw(&mut chart, x.iter().zip(y2.iter()).map(|(&a, &b)| (a, b)))?;
+        Markers::new(MarkerShape::Cross, MarkerPlacement::EveryN(12), theme.color(4).filled())
+            .size(1.5)
+            .draw(&mut chart, x.iter().zip(y3.iter()).map(|(&a, &b)| (a, b)))?;
+
+        Ok(())
+    })
+}
+
+// 5) Simple 2x1 layout (separate figure)
+fn example_5(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    canvas.draw("line_5_tiled", FigureSize::inches(8.0, 22.0 / 3.0), |root| {
+        let areas = root.split_evenly((2, 1));
+
+        let x = linspace(0.0, 3.0, 250);
+        let y1 = x.iter().map(|&v| (5.0 * v).sin()).collect::<Vec<_>>();
+        let y2 = x.iter().map(|&v| (15.0 * v).sin()).collect::<Vec<_>>();
+
+        // Top
+        {
+            let mut chart = ChartBuilder::on(&areas[0])
+                .caption("Top Plot", theme.text(8.0))
+                .margin(pt(3.5))
+                .x_label_area_size(pt(11.0))
+                .y_label_area_size(pt(14.5))
+                .build_cartesian_2d(0.0f64..3.0f64, -1.3f64..1.3f64)?;
+            theme
+                .mesh(&mut chart.configure_mesh())
+                .x_desc("x")
+                .y_desc("sin(5x)")
+                .label_style(theme.text(5.5))
+                .axis_desc_style(theme.text(6.0))
+                .draw()?;
+            chart.draw_series(LineSeries::new(
+                x.iter().zip(y1.iter()).map(|(&a, &b)| (a, b)),
+                theme.color(0).stroke_width(theme.stroke()),
+            ))?;
+        }
+
+        // Bottom
+        {
+            let mut chart = ChartBuilder::on(&areas[1])
+                .caption("Bottom Plot", theme.text(8.0))
+                .margin(pt(3.5))
+                .x_label_area_size(pt(11.0))
+                .y_label_area_size(pt(14.5))
+                .build_cartesian_2d(0.0f64..3.0f64, -1.3f64..1.3f64)?;
+            theme
+                .mesh(&mut chart.configure_mesh())
+                .x_desc("x")
+                .y_desc("sin(15x)")
+                .label_style(theme.text(5.5))
+                .axis_desc_style(theme.text(6.0))
+                .draw()?;
+            chart.draw_series(LineSeries::new(
+                x.iter().zip(y2.iter()).map(|(&a, &b)| (a, b)),
+                theme.color(1).stroke_width(theme.stroke()),
+            ))?;
+        }
+
+        Ok(())
+    })
+}
+
+// 6) 3x2 subplots in a single figure (6 subplots total)
+fn example_6(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    canvas.draw("line_6_grid_3x2", BIG_GRID, |root| {
+        let areas = root.split_evenly((3, 2));
+
+        // (0,0): sin(x) with marker indices
+        {
+            let x = linspace(0.0, 10.0, 100);
+            let y = x.iter().map(|&v| v.sin()).collect::<Vec<_>>();
+            let mut chart = ChartBuilder::on(&areas[0])
+                .caption("sin(x) with marker indices", theme.text(6.5))
+                .margin(pt(2.5))
+                .x_label_area_size(pt(9.5))
+                .y_label_area_size(pt(12.0))
+                .build_cartesian_2d(0.0f64..10.0f64, -1.3f64..1.3f64)?;
+            draw_mesh_f64(&mut chart, "x", "sin(x)")?;
+
+            chart.draw_series(LineSeries::new(
+                x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
+                theme.color(0).stroke_width(theme.stroke()),
+            ))?;
+
+            let marker_indices: Vec<usize> = (0..100).step_by(5).collect();
+            Markers::new(MarkerShape::Circle, MarkerPlacement::Indices(marker_indices), theme.color(0).filled())
+                .draw(&mut chart, x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)))?;
+        }
+
+        // (0,1): tan(sin(x)) - sin(tan(x))
+        {
+            let x = linspace(-PI, PI, 20);
+            let y = x
+                .iter()
+                .map(|&v| (v.sin()).tan() - (v.tan()).sin())
+                .collect::<Vec<_>>();
+
+            let mut chart = ChartBuilder::on(&areas[1])
+                .caption("tan(sin(x)) - sin(tan(x))", theme.text(6.5))
+                .margin(pt(2.5))
+                .x_label_area_size(pt(9.5))
+                .y_label_area_size(pt(12.0))
+                .build_cartesian_2d(-PI..PI, -5.0f64..5.0f64)?;
+            draw_mesh_f64(&mut chart, "x", "y")?;
+
+            chart.draw_series(LineSeries::new(
+                x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
+                theme.color(2).stroke_width(theme.stroke()),
+            ))?;
+            Markers::new(MarkerShape::Circle, MarkerPlacement::DataPoints, theme.foreground.filled())
+                .size(0.75)
+                .draw(&mut chart, x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)))?;
+        }
+
+        // (1,0): cos(5x)
+        {
+            let x = linspace(0.0, 10.0, 150);
+            let y = x.iter().map(|&v| (5.0 * v).cos()).collect::<Vec<_>>();
+
+            let mut chart = ChartBuilder::on(&areas[2])
+                .caption("2-D Line Plot", theme.text(6.5))
+                .margin(pt(2.5))
+                .x_label_area_size(pt(9.5))
+                .y_label_area_size(pt(12.0))
+                .build_cartesian_2d(0.0f64..10.0f64, -1.3f64..1.3f64)?;
+            draw_mesh_f64(&mut chart, "x", "cos(5x)")?;
+            chart.draw_series(LineSeries::new(
+                x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
+                theme.color(4).stroke_width(theme.stroke()),
+            ))?;
+        }
+
+        // (1,1): time plot on a duration axis (elapsed seconds labelled mm:ss)
+        {
+            let x: Vec<f64> = vec![0.0, 30.0, 60.0, 90.0, 120.0, 150.0, 180.0];
+            let y: Vec<f64> = vec![0.8, 0.9, 0.1, 0.9, 0.6, 0.1, 0.3];
+
+            let mut chart = ChartBuilder::on(&areas[3])
+                .caption("Time Plot", theme.text(6.5))
+                .margin(pt(2.5))
+                .x_label_area_size(pt(9.5))
+                .y_label_area_size(pt(12.0))
+                .build_cartesian_2d(TimeAxis::durations(0.0..180.0), 0.0f64..1.0f64)?;
+            draw_time_mesh(&mut chart, "Time", "Value")?;
+
+            chart.draw_series(LineSeries::new(
+                x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
+                theme.color(0).stroke_width(theme.stroke()),
+            ))?;
+            Markers::new(MarkerShape::Circle, MarkerPlacement::DataPoints, theme.color(1).filled())
+                .draw(&mut chart, x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)))?;
+        }
+
+        // (2,0): sin(5x)
+        {
+            let x = linspace(0.0, 3.0, 200);
+            let y = x.iter().map(|&v| (5.0 * v).sin()).collect::<Vec<_>>();
+
+            let mut chart = ChartBuilder::on(&areas[4])
+                .caption("sin(5x)", theme.text(6.5))
+                .margin(pt(2.5))
+                .x_label_area_size(pt(9.5))
+                .y_label_area_size(pt(12.0))
+                .build_cartesian_2d(0.0f64..3.0f64, -1.3f64..1.3f64)?;
+            draw_mesh_f64(&mut chart, "x", "y")?;
+            chart.draw_series(LineSeries::new(
+                x.iter().zip(y.iter()).map(|(&a, &b)| (a, b)),
+                theme.foreground.stroke_width(theme.stroke()),
+            ))?;
+        }
+
+        // (2,1): circle
+        {
+            let r = 2.0;
+            let xc = 4.0;
+            let yc = 3.0;
+
+            let theta = linspace(0.0, 2.0 * PI, 400);
+            let xs = theta.iter().map(|&t| r * t.cos() + xc).collect::<Vec<_>>();
+            let ys = theta.iter().map(|&t| r * t.sin() + yc).collect::<Vec<_>>();
+
+            // This panel is wider than it is tall; choose axis ranges that compensate to keep the circle visually round.
+            let y_span = 4.0;
+            let x_span = 2.0 * y_span;
+
+            let mut chart = ChartBuilder::on(&areas[5])
+                .caption("Circle", theme.text(6.5))
+                .margin(pt(2.5))
+                .x_label_area_size(pt(9.5))
+                .y_label_area_size(pt(12.0))
+                .build_cartesian_2d((xc - x_span)..(xc + x_span), (yc - y_span)..(yc + y_span))?;
+
+            draw_mesh_f64(&mut chart, "x", "y")?;
+            chart.draw_series(LineSeries::new(
+                xs.iter().zip(ys.iter()).map(|(&a, &b)| (a, b)),
+                theme.color(0).stroke_width(theme.stroke()),
+            ))?;
+        }
+
+        Ok(())
+    })
 }
 
 // --- CSV helper ---
@@ -437,71 +384,445 @@ fn download_csv_text(url: &str) -> Result<String, Box<dyn Error>> {
     Ok(normalized)
 }
 
-fn example_7_csv_plot() -> Result<(), Box<dyn Error>> {
-    let root = with_png_root("output/line_7_csv_scatter.png", FIG_300DPI)?;
+fn example_7_csv_plot(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    canvas.draw("line_7_csv_scatter", FIG, |root| {
+        let url = "https://raw.githubusercontent.com/mohammadijoo/Datasets/refs/heads/main/iris.csv";
 
-    let url = "https://raw.githubusercontent.com/mohammadijoo/Datasets/refs/heads/main/iris.csv";
+        // If the CSV is on your local drive:
+        // let mut rdr = csv::Reader::from_path("path/to/iris.csv")?;
 
-    // If the CSV is on your local drive:
-    // let mut rdr = csv::Reader::from_path("path/to/iris.csv")?;
+        let csv_text = download_csv_text(url)?;
+        let mut rdr = csv::Reader::from_reader(std::io::Cursor::new(csv_text));
 
-    let csv_text = download_csv_text(url)?;
-    let mut rdr = csv::Reader::from_reader(std::io::Cursor::new(csv_text));
+        let headers = rdr.headers()?.clone();
+        let idx_x = headers
+            .iter()
+            .position(|h| h == "sepal_length")
+            .ok_or("Missing column: sepal_length")?;
+        let idx_y = headers
+            .iter()
+            .position(|h| h == "petal_length")
+            .ok_or("Missing column: petal_length")?;
+
+        let mut xs = Vec::<f64>::new();
+        let mut ys = Vec::<f64>::new();
+
+        for rec in rdr.records() {
+            let rec = rec?;
+            let x: f64 = rec.get(idx_x).ok_or("Bad record")?.trim().parse()?;
+            let y: f64 = rec.get(idx_y).ok_or("Bad record")?.trim().parse()?;
+            xs.push(x);
+            ys.push(y);
+        }
+
+        let (xmin, xmax) = xs.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(mn, mx), &v| (mn.min(v), mx.max(v)));
+        let (ymin, ymax) = ys.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(mn, mx), &v| (mn.min(v), mx.max(v)));
+
+        let mut chart = theme
+            .chart(root)
+            .caption("CSV scatter: sepal_length vs petal_length (Iris)", theme.title_font())
+            .build_cartesian_2d((xmin - 0.2)..(xmax + 0.2), (ymin - 0.2)..(ymax + 0.2))?;
+
+        draw_mesh_f64(&mut chart, "sepal_length", "petal_length")?;
+
+        let mut rng = seeded_rng("line_7");
+        chart.draw_series(xs.iter().zip(ys.iter()).map(|(&x, &y)| {
+            // Slight random styling variation so the plot has more depth
+            let radius = pt(0.75 + 0.25 * rng.random_range(0..=2) as f64) as i32;
+            Circle::new((x, y), radius, theme.color(0).mix(0.55).filled())
+        }))?;
 
-    let headers = rdr.headers()?.clone();
-    let idx_x = headers
-        .iter()
-        .position(|h| h == "sepal_length")
-        .ok_or("Missing column: sepal_length")?;
-    let idx_y = headers
-        .iter()
-        .position(|h| h == "petal_length")
-        .ok_or("Missing column: petal_length")?;
+        Ok(())
+    })
+}
 
-    let mut xs = Vec::<f64>::new();
-    let mut ys = Vec::<f64>::new();
+// 8) Temperature and pressure on separate y axes sharing a time axis
+fn example_8_dual_axis(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    canvas.draw("line_8_dual_axis", FIG, |root| {
+        let t = linspace(0.0, 24.0, 289);
+        let temp = t
+            .iter()
+            .map(|&h| (h, 18.0 + 6.0 * ((h - 9.0) * PI / 12.0).sin()))
+            .collect::<Vec<_>>();
+        let pressure = t
+            .iter()
+            .map(|&h| (h, 1013.0 - 4.0 * (h / 24.0) + 1.5 * (h * PI / 6.0).cos()))
+            .collect::<Vec<_>>();
 
-    for rec in rdr.records() {
-        let rec = rec?;
-        let x: f64 = rec.get(idx_x).ok_or("Bad record")?.trim().parse()?;
-        let y: f64 = rec.get(idx_y).ok_or("Bad record")?.trim().parse()?;
-        xs.push(x);
-        ys.push(y);
-    }
+        draw_dual_axis_lines(
+            root,
+            "Temperature and pressure (dual y axes)",
+            &AxisSpec::new(0.0..24.0, "Time [h]"),
+            &AxisSpec::new(10.0..26.0, "Temperature [°C]"),
+            &AxisSpec::new(1005.0..1017.0, "Pressure").tick_format(TickFormat::Fixed(0).with_unit(" hPa")),
+            &[
+                LineSpec::new("Temperature", temp, theme.color(1).stroke_width(theme.stroke())),
+                LineSpec::new("Pressure", pressure, theme.color(0).stroke_width(theme.stroke()))
+                    .axis(YAxis::Secondary)
+                    .markers(Markers::new(MarkerShape::Square, MarkerPlacement::Count(13), theme.color(0).filled())),
+            ],
+        )?;
 
-    let (xmin, xmax) = xs.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(mn, mx), &v| (mn.min(v), mx.max(v)));
-    let (ymin, ymax) = ys.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(mn, mx), &v| (mn.min(v), mx.max(v)));
+        Ok(())
+    })
+}
 
-    let mut chart = ChartBuilder::on(&root)
-        .caption("CSV scatter: sepal_length vs petal_length (Iris)", ("sans-serif", 40))
-        .margin(20)
-        .x_label_area_size(60)
-        .y_label_area_size(70)
-        .build_cartesian_2d((xmin - 0.2)..(xmax + 0.2), (ymin - 0.2)..(ymax + 0.2))?;
+// Irregularly sampled sensor log with ISO-8601 timestamps
+const SENSOR_LOG_CSV: &str = "timestamp,temperature
+2024-03-01T00:00:00Z,6.1
+2024-03-01T09:30:00Z,11.4
+2024-03-01T15:00:00Z,14.2
+2024-03-02T03:00:00Z,5.3
+2024-03-02T12:00:00Z,13.8
+2024-03-03T06:00:00Z,7.9
+2024-03-03 14:00:00,16.5
+2024-03-04 02:30:00,8.2
+2024-03-04 13:00:00,17.1
+2024-03-05 08:00:00,10.4
+2024-03-05 16:45:00,15.0
+2024-03-06,7.2
+";
+
+// 9) Time series with real timestamps parsed from a CSV date column
+fn example_9_time_series(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    canvas.draw("line_9_time_series", FIG, |root| {
+        let mut rdr = csv::Reader::from_reader(SENSOR_LOG_CSV.as_bytes());
+        let mut points = Vec::<(f64, f64)>::new();
+        for rec in rdr.records() {
+            let rec = rec?;
+            let t = parse_timestamp(rec.get(0).ok_or("Bad record")?)?;
+            let v: f64 = rec.get(1).ok_or("Bad record")?.trim().parse()?;
+            points.push((t, v));
+        }
+
+        // Pad by half a day so the first and last date labels are not clipped
+        let (t0, t1) = (points[0].0 - 43_200.0, points[points.len() - 1].0 + 43_200.0);
+        let mut chart = theme
+            .chart(root)
+            .caption("Sensor log (timestamp axis)", theme.title_font())
+            .build_cartesian_2d(TimeAxis::timestamps(t0..t1), 0.0f64..20.0f64)?;
+        draw_time_mesh(&mut chart, "Date (UTC)", "Temperature [°C]")?;
+
+        chart.draw_series(LineSeries::new(points.iter().copied(), theme.color(0).stroke_width(theme.stroke())))?;
+        Markers::new(MarkerShape::Diamond, MarkerPlacement::DataPoints, theme.color(0).filled())
+            .size(1.75)
+            .draw(&mut chart, points.iter().copied())?;
+
+        Ok(())
+    })
+}
 
-    draw_mesh_f64(&mut chart, "sepal_length", "petal_length")?;
+// 10) Colormaps with their reversed variants, and palettes as seen with each color vision
+fn example_10_colors(canvas: &mut Canvas) -> Result<(), Box<dyn Error>> {
+    let theme = theme();
+    canvas.draw("line_10_colors", BIG_GRID, |root| {
+        let areas = root.split_evenly((1, 2));
 
-    let mut rng = rand::rng();
-    chart.draw_series(xs.iter().zip(ys.iter()).map(|(&x, &y)| {
-        // Slight random styling variation so the plot has more depth
-        let radius = 3 + (rng.random_range(0..=2) as i32);
-        Circle::new((x, y), radius, BLUE.mix(0.55).filled())
-    }))?;
+        // Rows are listed top to bottom; segment 0 is at the bottom of the chart
+        let maps = Colormap::ALL
+            .into_iter()
+            .flat_map(|m| [m, m.reversed()])
+            .collect::<Vec<_>>();
+        let n = maps.len();
+        let mut chart = theme
+            .chart(&areas[0])
+            .caption("Colormaps", theme.title_font())
+            .y_label_area_size(pt(36.0))
+            .build_cartesian_2d(0.0f64..1.0f64, (0..n - 1).into_segmented())?;
+        theme
+            .mesh(&mut chart.configure_mesh())
+            .disable_mesh()
+            .y_labels(n)
+            .y_label_formatter(&|v| match v {
+                SegmentValue::CenterOf(i) if *i < n => maps[n - 1 - i].to_string(),
+                _ => String::new(),
+            })
+            .x_desc("t")
+            .draw()?;
+        for (row, map) in maps.iter().enumerate() {
+            let y = n - 1 - row;
+            chart.draw_series((0..256).map(|k| {
+                let (t0, t1) = (k as f64 / 256.0, (k + 1) as f64 / 256.0);
+                let top = SegmentValue::Exact(y + 1);
+                Rectangle::new([(t0, SegmentValue::Exact(y)), (t1, top)], map.at((t0 + t1) / 2.0).filled())
+            }))?;
+        }
+
+        let palettes = [("okabe-ito", &OKABE_ITO[..]), ("tableau10", &TABLEAU_10), ("basic", &BASIC_PALETTE)];
+        let rows = palettes
+            .iter()
+            .flat_map(|&(name, colors)| Vision::ALL.map(|vision| (name, colors, vision)))
+            .collect::<Vec<_>>();
+        let n = rows.len();
+        let mut chart = theme
+            .chart(&areas[1])
+            .caption("Palettes with simulated color vision", theme.title_font())
+            .y_label_area_size(pt(60.0))
+            .build_cartesian_2d(0.0f64..10.0f64, (0..n - 1).into_segmented())?;
+        theme
+            .mesh(&mut chart.configure_mesh())
+            .disable_mesh()
+            .y_labels(n)
+            .y_label_formatter(&|v| match v {
+                SegmentValue::CenterOf(i) if *i < n => format!("{} ({})", rows[n - 1 - i].0, rows[n - 1 - i].2),
+                _ => String::new(),
+            })
+            .x_labels(0)
+            .draw()?;
+        for (row, (_, colors, vision)) in rows.iter().enumerate() {
+            let y = n - 1 - row;
+            chart.draw_series(colors.iter().enumerate().map(|(i, &c)| {
+                let (x0, x1) = (i as f64 + 0.05, i as f64 + 0.95);
+                Rectangle::new(
+                    [(x0, SegmentValue::Exact(y)), (x1, SegmentValue::Exact(y + 1))],
+                    vision.simulate(c).filled(),
+                )
+            }))?;
+        }
+
+        Ok(())
+    })
+}
 
-    root.present()?;
-    Ok(())
+/// Every example in the order `main` runs them.
+pub const EXAMPLES: &[Example] = &[
+    Example::new("1", example_1),
+    Example::new("2", example_2),
+    Example::new("3", example_3),
+    Example::new("4", example_4),
+    Example::new("5", example_5),
+    Example::new("6", example_6),
+    Example::online("7_csv_plot", example_7_csv_plot),
+    Example::new("8_dual_axis", example_8_dual_axis),
+    Example::new("9_time_series", example_9_time_series),
+    Example::new("10_colors", example_10_colors),
+];
+
+const USAGE: &str = "\
+Usage:
+  line [--example N] [--seed N]             run the bundled examples (all by default), writing to output/
+  line <data.csv|-> [options]               plot CSV columns as lines ('-' reads stdin)
+
+Options:
+  --x NAME|INDEX        x column, numeric or timestamps (default: row number)
+  --y COLS              comma-separated y columns; repeatable (default: every other numeric column)
+  --style FMTS          comma-separated format strings per y column, e.g. \"r--o,b:\"
+                        (color b g r c m y k w, line - -- :, marker o ^ x s d *)
+  --labels NAMES        comma-separated legend labels (default: column names)
+  --legend POS          upper-right, lower-left, ... or none (default upper-right)
+  --logx, --logy        logarithmic axes; non-positive values are skipped
+  --time                parse x as timestamps (detected automatically when x is not numeric)
+  --xlim MIN:MAX        x-axis limits; either side may be empty
+  --ylim MIN:MAX        y-axis limits; either side may be empty
+  --title TEXT          chart title (default \"<y> vs <x>\")
+  --xlabel TEXT         x-axis label (default: x column name)
+  --ylabel TEXT         y-axis label (default: the y column name, or \"Value\")
+  --width PT            line width in points (default 0.75)
+  --size WxH            figure size with an in/cm suffix, or in pixels (default 8x5.333in)
+  --dpi N               resolution written into the PNG (default 300)
+  --theme NAME          light, dark, print, presentation or publication (default light)
+  --palette NAME        series colors: basic, dark, gray, okabe-ito or tableau10 (default: the theme's)
+  --colormap NAME       viridis, magma, cividis or turbo, _r to reverse (default: the theme's)
+  --check-colors        print whether the series colors stay distinguishable with color blindness
+  --out PATH            .png or .svg output (default plot.png)
+  --no-header           the CSV has no header row
+  --seed N              seed for the bundled examples' random data
+";
+
+/// Line chart of CSV columns built from the command line.
+struct CsvLines {
+    title: String,
+    x: (Range<f64>, String, AxisScale),
+    y: (Range<f64>, String, AxisScale),
+    series: Vec<LineSpec>,
+    legend: Option<SeriesLabelPosition>,
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    ensure_output_dir()?;
+impl CsvLines {
+    fn from_args(args: &mut Args) -> Result<Self, Box<dyn Error>> {
+        let [path] = args.positional() else {
+            return Err("Expected exactly one input file (see --help)".into());
+        };
+        let path = path.clone();
+        let table = Table::from_path(&path, !args.flag("no-header"))?;
+        if table.rows.is_empty() {
+            return Err(format!("{} has no data rows", path).into());
+        }
+
+        // x: a numeric or timestamp column, or the row number
+        let force_time = args.flag("time");
+        let x_col = args.parsed::<Column>("x")?;
+        let (xs, x_name, is_time) = match &x_col {
+            None => ((0..table.rows.len()).map(|i| Some(i as f64)).collect(), "Row".to_string(), false),
+            Some(col) => {
+                let name = table.name_of(col)?.to_string();
+                if force_time || table.has_timestamps(col) {
+                    (table.timestamps(col)?, name, true)
+                } else {
+                    (table.column(col)?, name, false)
+                }
+            }
+        };
+
+        let mut y_cols = Vec::new();
+        for list in args.values("y")? {
+            for c in list.split(',') {
+                y_cols.push(c.parse::<Column>()?);
+            }
+        }
+        let x_idx = x_col.as_ref().map(|c| table.index_of(c)).transpose()?;
+        let mut ys = Vec::new();
+        if y_cols.is_empty() {
+            for i in (0..table.headers.len()).filter(|&i| Some(i) != x_idx) {
+                if let Ok(col) = table.column(&Column::Index(i)) {
+                    ys.push((table.headers[i].clone(), col));
+                }
+            }
+            if ys.is_empty() {
+                return Err("No numeric columns to plot; choose them with --y".into());
+            }
+        } else {
+            for col in &y_cols {
+                ys.push((table.name_of(col)?.to_string(), table.column(col)?));
+            }
+        }
+
+        let styles = match args.value("style")? {
+            Some(s) => s.split(',').map(str::parse::<LineFormat>).collect::<Result<Vec<_>, _>>()?,
+            None => Vec::new(),
+        };
+        if styles.len() > ys.len() {
+            return Err(format!("{} styles given for {} y columns", styles.len(), ys.len()).into());
+        }
+        let labels = args
+            .value("labels")?
+            .map(|s| s.split(',').map(|l| l.trim().to_string()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        if labels.len() > ys.len() {
+            return Err(format!("{} labels given for {} y columns", labels.len(), ys.len()).into());
+        }
+        let width = args.parsed::<f64>("width")?.unwrap_or(theme().line_width);
+
+        let series = ys
+            .iter()
+            .enumerate()
+            .map(|(i, (name, col))| {
+                let points = xs
+                    .iter()
+                    .zip(col)
+                    .filter_map(|(x, y)| Some(((*x)?, (*y)?)))
+                    .collect::<Vec<_>>();
+                let fmt = styles.get(i).copied().unwrap_or_default();
+                fmt.series(labels.get(i).unwrap_or(name), points, theme().color(i), width)
+            })
+            .collect::<Vec<_>>();
+
+        let x_scale = if args.flag("logx") {
+            AxisScale::Log
+        } else if is_time {
+            AxisScale::Time
+        } else {
+            AxisScale::Linear
+        };
+        let y_scale = if args.flag("logy") { AxisScale::Log } else { AxisScale::Linear };
+        let xlim = args.value("xlim")?.map(|s| parse_limits(&s)).transpose()?.unwrap_or_default();
+        let ylim = args.value("ylim")?.map(|s| parse_limits(&s)).transpose()?.unwrap_or_default();
+        let x_range = padded_range(series.iter().flat_map(|s| s.points.iter().map(|p| p.0)), x_scale, xlim)?;
+        let y_range = padded_range(series.iter().flat_map(|s| s.points.iter().map(|p| p.1)), y_scale, ylim)?;
+
+        let y_names = ys.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>();
+        let title = args
+            .value("title")?
+            .unwrap_or_else(|| format!("{} vs {}", y_names.join(", "), x_name));
+        let x_label = args.value("xlabel")?.unwrap_or(x_name);
+        let y_label = args.value("ylabel")?.unwrap_or_else(|| match y_names.as_slice() {
+            [one] => one.to_string(),
+            _ => "Value".to_string(),
+        });
+        let legend = match args.value("legend")? {
+            Some(s) => parse_legend(&s)?,
+            None => Some(SeriesLabelPosition::UpperRight),
+        };
+
+        Ok(Self {
+            title,
+            x: (x_range, x_label, x_scale),
+            y: (y_range, y_label, y_scale),
+            series,
+            legend,
+        })
+    }
+
+    fn colors(&self) -> Vec<RGBColor> {
+        self.series
+            .iter()
+            .map(|s| {
+                let (r, g, b) = s.style.color.rgb();
+                RGBColor(r, g, b)
+            })
+            .collect()
+    }
+}
 
-    example_1()?;
-    example_2()?;
-    example_3()?;
-    example_4()?;
-    example_5()?;
-    example_6()?;
-    example_7_csv_plot()?;
+impl Figure for CsvLines {
+    fn draw<DB>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        let x = AxisSpec::new(self.x.0.clone(), &self.x.1).scale(self.x.2);
+        let y = AxisSpec::new(self.y.0.clone(), &self.y.1).scale(self.y.2);
+        draw_lines(root, &self.title, &x, &y, &self.series, self.legend.clone())
+    }
+}
 
+fn run_cli(mut args: Args) -> Result<(), Box<dyn Error>> {
+    let dpi = args.parsed::<f64>("dpi")?.unwrap_or(DEFAULT_DPI);
+    let size = match args.value("size")? {
+        Some(s) => FigureSize::parse(&s, dpi)?,
+        None => FIG.with_dpi(check_dpi(dpi)?),
+    };
+    // Styles are sized in points, so build them at the output's DPI
+    let lines = with_dpi(size.dpi, || CsvLines::from_args(&mut args))?;
+    let out = args.value("out")?.unwrap_or_else(|| "plot.png".to_string());
+    let check_colors = args.flag("check-colors");
+    args.finish()?;
+
+    save_figure(&lines, &out, size)?;
+    println!("Wrote {}", out);
+    if check_colors {
+        println!("Series colors:");
+        print_color_check(&lines.colors());
+    }
     Ok(())
 }
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = Args::parse(
+        seed_from_args(std::env::args().skip(1))?,
+        &["help", "no-header", "check-colors", "logx", "logy", "time"],
+    )?;
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+    let theme = ThemeArgs::parse(&mut args)?.apply(Theme::default());
+    with_theme(theme, || {
+        if args.positional().is_empty() {
+            let only = args.value("example")?;
+            args.finish()?;
+            let selected = select_examples(EXAMPLES, only.as_deref())?;
+            println!("Seed: {}", seed());
+            let mut canvas = Canvas::to_dir("output")?;
+            for example in selected {
+                (example.run)(&mut canvas)?;
+            }
+            Ok(())
+        } else {
+            run_cli(args)
+        }
+    })
+}
@@ -0,0 +1,69 @@
+use rust_plot::cli::{print_color_check, Args, ThemeArgs};
+use rust_plot::render::save_figure;
+use rust_plot::spec::PlotSpec;
+use rust_plot::units::{check_dpi, FigureSize};
+use std::error::Error;
+
+const USAGE: &str = "\
+Usage:
+  plot <spec.toml|spec.json>... [options]   render each plot specification to its output file
+
+Options:
+  --out PATH            .png or .svg output, overriding the spec's (one spec only)
+  --size WxH            figure size with an in/cm suffix, or in pixels, overriding the spec's
+  --dpi N               resolution, overriding the spec's (the size in inches is kept)
+  --theme NAME          light, dark, print, presentation or publication, overriding the spec's
+  --palette NAME        basic, dark, gray, okabe-ito or tableau10, overriding the theme's
+  --colormap NAME       viridis, magma, cividis or turbo (_r to reverse), overriding the theme's
+  --check               read the specs and their data without writing images, and check
+                        that each panel's colors stay distinguishable with color blindness
+
+See specs/sensors.toml for an example and the README for every key.
+";
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = Args::parse(std::env::args().skip(1), &["help", "check"])?;
+    if args.flag("help") || args.positional().is_empty() {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+    let check = args.flag("check");
+    let out = args.value("out")?;
+    let dpi = args.parsed::<f64>("dpi")?.map(check_dpi).transpose()?;
+    let size = args.value("size")?;
+    let theme = ThemeArgs::parse(&mut args)?;
+    let specs = args.positional().to_vec();
+    args.finish()?;
+    if out.is_some() && specs.len() > 1 {
+        return Err("--out needs a single spec".into());
+    }
+
+    for path in &specs {
+        let mut spec = PlotSpec::from_path(path)?;
+        spec.size = match (&size, dpi) {
+            (Some(s), dpi) => FigureSize::parse(s, dpi.unwrap_or(spec.size.dpi))?,
+            (None, Some(dpi)) => spec.size.with_dpi(dpi),
+            (None, None) => spec.size,
+        };
+        spec.theme = theme.apply(spec.theme);
+        let plot = spec.load().map_err(|e| format!("{}: {}", path, e))?;
+        if check {
+            println!("{}: ok", path);
+            for (title, colors) in plot.panel_colors().iter().filter(|(_, c)| c.len() > 1) {
+                println!("{} colors:", title);
+                print_color_check(colors);
+            }
+            continue;
+        }
+        let out = match &out {
+            Some(out) => out.into(),
+            None => plot.output().to_path_buf(),
+        };
+        if let Some(dir) = out.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        save_figure(&plot, &out.to_string_lossy(), plot.size())?;
+        println!("Wrote {}", out.display());
+    }
+    Ok(())
+}
@@ -0,0 +1,512 @@
+// Box-and-whisker and violin plots for comparing several datasets side by side.
+
+use crate::category::CategoryAxis;
+use crate::stats::{mean, quantile, sorted, std_dev};
+use crate::theme::theme;
+use crate::units::pt;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::error::Error;
+
+/// One dataset drawn at its own category position.
+#[derive(Clone, Debug)]
+pub struct Group {
+    pub label: String,
+    pub data: Vec<f64>,
+    pub style: ShapeStyle,
+}
+
+impl Group {
+    pub fn new<S: Into<ShapeStyle>>(label: &str, data: Vec<f64>, style: S) -> Self {
+        Self {
+            label: label.to_string(),
+            data,
+            style: style.into(),
+        }
+    }
+}
+
+/// Five-number summary with Tukey whiskers, outliers, notch and mean.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoxStats {
+    pub n: usize,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    /// Lowest value within `q1 − k·IQR`.
+    pub whisker_lo: f64,
+    /// Highest value within `q3 + k·IQR`.
+    pub whisker_hi: f64,
+    /// Values beyond the whiskers.
+    pub outliers: Vec<f64>,
+    pub mean: f64,
+    /// Approximate 95% confidence interval of the median: `median ± 1.57·IQR/√n`.
+    pub notch: (f64, f64),
+}
+
+/// Box plot statistics of `data` with whiskers at `k`·IQR (Tukey: `k = 1.5`).
+/// Every statistic is NaN for empty data.
+pub fn box_stats(data: &[f64], k: f64) -> BoxStats {
+    let v = sorted(data);
+    if v.is_empty() {
+        return BoxStats {
+            n: 0,
+            q1: f64::NAN,
+            median: f64::NAN,
+            q3: f64::NAN,
+            whisker_lo: f64::NAN,
+            whisker_hi: f64::NAN,
+            outliers: vec![],
+            mean: f64::NAN,
+            notch: (f64::NAN, f64::NAN),
+        };
+    }
+    let (q1, median, q3) = (quantile(&v, 0.25), quantile(&v, 0.5), quantile(&v, 0.75));
+    let iqr = q3 - q1;
+    let (lo_fence, hi_fence) = (q1 - k * iqr, q3 + k * iqr);
+
+    let inside = v.iter().copied().filter(|x| (lo_fence..=hi_fence).contains(x));
+    let (whisker_lo, whisker_hi) = inside.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), x| (lo.min(x), hi.max(x)));
+    let half = 1.57 * iqr / (v.len() as f64).sqrt();
+
+    BoxStats {
+        n: v.len(),
+        q1,
+        median,
+        q3,
+        whisker_lo: whisker_lo.min(q1),
+        whisker_hi: whisker_hi.max(q3),
+        outliers: v.iter().copied().filter(|x| *x < lo_fence || *x > hi_fence).collect(),
+        mean: mean(&v),
+        notch: (median - half, median + half),
+    }
+}
+
+/// Options for [`draw_box_plots`].
+#[derive(Clone, Copy, Debug)]
+pub struct BoxStyle {
+    /// Box width as a fraction of the category spacing.
+    pub width: f64,
+    /// Whisker reach in IQRs.
+    pub whisker: f64,
+    pub notch: bool,
+    pub mean_marker: bool,
+    pub outliers: bool,
+}
+
+impl Default for BoxStyle {
+    fn default() -> Self {
+        Self {
+            width: 0.6,
+            whisker: 1.5,
+            notch: false,
+            mean_marker: false,
+            outliers: true,
+        }
+    }
+}
+
+impl BoxStyle {
+    pub fn width(mut self, fraction: f64) -> Self {
+        self.width = fraction.clamp(0.05, 1.0);
+        self
+    }
+
+    pub fn whisker(mut self, k: f64) -> Self {
+        self.whisker = k;
+        self
+    }
+
+    pub fn notched(mut self) -> Self {
+        self.notch = true;
+        self
+    }
+
+    pub fn mean_marker(mut self) -> Self {
+        self.mean_marker = true;
+        self
+    }
+
+    pub fn hide_outliers(mut self) -> Self {
+        self.outliers = false;
+        self
+    }
+}
+
+type CategoryChart<'a, DB> = ChartContext<'a, DB, Cartesian2d<CategoryAxis, RangedCoordf64>>;
+
+// Chart with one category per label and the given y range, padded by 5%
+// (-1..1 when there is no data at all)
+fn category_chart<'a, DB>(
+    root: &'a DrawingArea<DB, Shift>,
+    title: &str,
+    y_label: &str,
+    labels: &[&str],
+    yr: Option<(f64, f64)>,
+) -> Result<CategoryChart<'a, DB>, Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let (ymin, ymax) = yr.unwrap_or((-1.0, 1.0));
+    let pad = ((ymax - ymin) * 0.05).max(1e-9);
+    let theme = theme();
+    let mut chart = theme
+        .chart(root)
+        .caption(title, theme.title_font())
+        .build_cartesian_2d(CategoryAxis::new(labels), (ymin - pad)..(ymax + pad))?;
+
+    theme
+        .mesh(&mut chart.configure_mesh())
+        .disable_x_mesh()
+        .x_labels(labels.len())
+        .y_desc(y_label)
+        .draw()?;
+    Ok(chart)
+}
+
+// Smallest and largest finite value, if there is any
+fn data_range<'a, I: IntoIterator<Item = &'a f64>>(values: I) -> Option<(f64, f64)> {
+    values
+        .into_iter()
+        .filter(|v| v.is_finite())
+        .fold(None, |range, &v| match range {
+            None => Some((v, v)),
+            Some((lo, hi)) => Some((v.min(lo), v.max(hi))),
+        })
+}
+
+/// Draws one box-and-whisker plot per group, side by side with categorical x labels.
+pub fn draw_box_plots<DB>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    y_label: &str,
+    groups: &[Group],
+    style: &BoxStyle,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let labels = groups.iter().map(|g| g.label.as_str()).collect::<Vec<_>>();
+    let yr = data_range(groups.iter().flat_map(|g| g.data.iter()));
+    let mut chart = category_chart(root, title, y_label, &labels, yr)?;
+    let theme = theme();
+
+    for (i, g) in groups.iter().enumerate() {
+        if g.data.is_empty() {
+            continue;
+        }
+        let s = box_stats(&g.data, style.whisker);
+        let x = i as f64;
+        let hw = style.width / 2.0;
+        let line = theme.foreground.stroke_width(pt(0.5));
+
+        // Box outline; notched boxes pinch in to half width around the median CI
+        let outline = if style.notch {
+            let (n_lo, n_hi) = (s.notch.0.max(s.q1), s.notch.1.min(s.q3));
+            vec![
+                (x - hw, s.q1),
+                (x - hw, n_lo),
+                (x - hw / 2.0, s.median),
+                (x - hw, n_hi),
+                (x - hw, s.q3),
+                (x + hw, s.q3),
+                (x + hw, n_hi),
+                (x + hw / 2.0, s.median),
+                (x + hw, n_lo),
+                (x + hw, s.q1),
+            ]
+        } else {
+            vec![(x - hw, s.q1), (x - hw, s.q3), (x + hw, s.q3), (x + hw, s.q1)]
+        };
+        chart.draw_series(std::iter::once(Polygon::new(outline.clone(), g.style.color.mix(0.55).filled())))?;
+        let mut closed = outline;
+        closed.push(closed[0]);
+        chart.draw_series(std::iter::once(PathElement::new(closed, line)))?;
+
+        let mhw = if style.notch { hw / 2.0 } else { hw };
+        chart.draw_series(std::iter::once(PathElement::new(
+            vec![(x - mhw, s.median), (x + mhw, s.median)],
+            theme.foreground.stroke_width(pt(1.0)),
+        )))?;
+
+        // Whiskers with caps
+        let cap = hw / 2.0;
+        chart.draw_series([
+            PathElement::new(vec![(x, s.q3), (x, s.whisker_hi)], line),
+            PathElement::new(vec![(x, s.q1), (x, s.whisker_lo)], line),
+            PathElement::new(vec![(x - cap, s.whisker_hi), (x + cap, s.whisker_hi)], line),
+            PathElement::new(vec![(x - cap, s.whisker_lo), (x + cap, s.whisker_lo)], line),
+        ])?;
+
+        if style.outliers {
+            chart.draw_series(s.outliers.iter().map(|&v| Circle::new((x, v), pt(1.25), line)))?;
+        }
+        if style.mean_marker {
+            chart.draw_series(std::iter::once(TriangleMarker::new((x, s.mean), pt(2.0), theme.background.filled())))?;
+            chart.draw_series(std::iter::once(TriangleMarker::new((x, s.mean), pt(2.0), line)))?;
+        }
+    }
+    Ok(())
+}
+
+/// Silverman's rule-of-thumb bandwidth for a Gaussian KDE.
+pub fn silverman_bandwidth(data: &[f64]) -> f64 {
+    let v = sorted(data);
+    let iqr = quantile(&v, 0.75) - quantile(&v, 0.25);
+    let spread = std_dev(&v).min(iqr / 1.34);
+    let spread = if spread > 0.0 { spread } else { std_dev(&v).max(1e-12) };
+    0.9 * spread * (v.len().max(1) as f64).powf(-0.2)
+}
+
+/// Gaussian kernel density estimate of `data` at each point of `grid`.
+pub fn kde(data: &[f64], bandwidth: f64, grid: &[f64]) -> Vec<f64> {
+    let h = bandwidth.max(1e-12);
+    let norm = 1.0 / (data.len().max(1) as f64 * h * (2.0 * std::f64::consts::PI).sqrt());
+    grid.iter()
+        .map(|&g| {
+            data.iter()
+                .map(|&x| {
+                    let z = (g - x) / h;
+                    (-0.5 * z * z).exp()
+                })
+                .sum::<f64>()
+                * norm
+        })
+        .collect()
+}
+
+// KDE of `data` over its range extended by two bandwidths, as (y, density) pairs
+fn violin_profile(data: &[f64]) -> Vec<(f64, f64)> {
+    let Some((lo, hi)) = data_range(data) else {
+        return vec![];
+    };
+    let h = silverman_bandwidth(data);
+    let (lo, hi) = (lo - 2.0 * h, hi + 2.0 * h);
+    let steps = 200;
+    let grid = (0..=steps).map(|i| lo + (hi - lo) * i as f64 / steps as f64).collect::<Vec<_>>();
+    let dens = kde(data, h, &grid);
+    grid.into_iter().zip(dens).collect()
+}
+
+/// Which side(s) of the category center a violin occupies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Side {
+    Both,
+    Left,
+    Right,
+}
+
+// Filled violin outline plus quartile/median ticks; `scale` maps density to half-width
+fn draw_violin<DB>(
+    chart: &mut CategoryChart<'_, DB>,
+    x: f64,
+    profile: &[(f64, f64)],
+    scale: f64,
+    data: &[f64],
+    side: Side,
+    style: ShapeStyle,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    if profile.is_empty() {
+        return Ok(());
+    }
+    let right = profile.iter().map(|&(y, d)| (x + d * scale, y));
+    let left = profile.iter().rev().map(|&(y, d)| (x - d * scale, y));
+    let outline = match side {
+        Side::Both => right.chain(left).collect::<Vec<_>>(),
+        Side::Right => right.chain([(x, profile[profile.len() - 1].0), (x, profile[0].0)]).collect(),
+        Side::Left => left.chain([(x, profile[0].0), (x, profile[profile.len() - 1].0)]).collect(),
+    };
+    chart.draw_series(std::iter::once(Polygon::new(outline.clone(), style.color.mix(0.55).filled())))?;
+    let mut closed = outline;
+    closed.push(closed[0]);
+    chart.draw_series(std::iter::once(PathElement::new(closed, style.color.stroke_width(pt(0.5)))))?;
+
+    // Median (solid) and quartiles (thin) across the violin's own width
+    let v = sorted(data);
+    let width_at = |y: f64| {
+        let i = profile.partition_point(|p| p.0 < y).min(profile.len() - 1);
+        profile[i].1 * scale
+    };
+    let color = theme().foreground;
+    for (q, w) in [(0.25, 0.25), (0.5, 0.75), (0.75, 0.25)] {
+        let y = quantile(&v, q);
+        let half = width_at(y);
+        let (x0, x1) = match side {
+            Side::Both => (x - half, x + half),
+            Side::Left => (x - half, x),
+            Side::Right => (x, x + half),
+        };
+        chart.draw_series(std::iter::once(PathElement::new(vec![(x0, y), (x1, y)], color.stroke_width(pt(w)))))?;
+    }
+    Ok(())
+}
+
+/// Draws a KDE-shaped violin per group (Gaussian kernel, Silverman bandwidth)
+/// with median and quartile lines. All violins share one density scale, with
+/// the widest reaching `width` of the category spacing.
+pub fn draw_violins<DB>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    y_label: &str,
+    groups: &[Group],
+    width: f64,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let labels = groups.iter().map(|g| g.label.as_str()).collect::<Vec<_>>();
+    let profiles = groups.iter().map(|g| violin_profile(&g.data)).collect::<Vec<_>>();
+    let yr = data_range(profiles.iter().flatten().map(|p| &p.0));
+    let dmax = profiles.iter().flatten().map(|p| p.1).fold(1e-12, f64::max);
+    let scale = width.clamp(0.05, 1.0) / 2.0 / dmax;
+
+    let mut chart = category_chart(root, title, y_label, &labels, yr)?;
+    for (i, (g, p)) in groups.iter().zip(&profiles).enumerate() {
+        draw_violin(&mut chart, i as f64, p, scale, &g.data, Side::Both, g.style)?;
+    }
+    Ok(())
+}
+
+/// Two datasets compared within one category, drawn as the halves of a split violin.
+#[derive(Clone, Debug)]
+pub struct SplitGroup {
+    pub label: String,
+    pub left: Vec<f64>,
+    pub right: Vec<f64>,
+}
+
+impl SplitGroup {
+    pub fn new(label: &str, left: Vec<f64>, right: Vec<f64>) -> Self {
+        Self {
+            label: label.to_string(),
+            left,
+            right,
+        }
+    }
+}
+
+/// Split violins: per category, `left` data on the left half and `right` data
+/// on the right half, with a legend naming the two sides.
+pub fn draw_split_violins<DB>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    y_label: &str,
+    groups: &[SplitGroup],
+    names: [&str; 2],
+    styles: [ShapeStyle; 2],
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let labels = groups.iter().map(|g| g.label.as_str()).collect::<Vec<_>>();
+    let profiles = groups
+        .iter()
+        .map(|g| (violin_profile(&g.left), violin_profile(&g.right)))
+        .collect::<Vec<_>>();
+    let all = || profiles.iter().flat_map(|(l, r)| l.iter().chain(r));
+    let yr = data_range(all().map(|p| &p.0));
+    let dmax = all().map(|p| p.1).fold(1e-12, f64::max);
+    let scale = 0.45 / dmax;
+
+    let mut chart = category_chart(root, title, y_label, &labels, yr)?;
+    for (i, (g, (pl, pr))) in groups.iter().zip(&profiles).enumerate() {
+        draw_violin(&mut chart, i as f64, pl, scale, &g.left, Side::Left, styles[0])?;
+        draw_violin(&mut chart, i as f64, pr, scale, &g.right, Side::Right, styles[1])?;
+    }
+
+    for (name, style) in names.iter().zip(styles) {
+        let (w, h) = (pt(6.0) as i32, pt(2.0) as i32);
+        chart
+            .draw_series(std::iter::empty::<Rectangle<(f64, f64)>>())?
+            .label(*name)
+            .legend(move |(x, y)| Rectangle::new([(x, y - h), (x + w, y + h)], style.color.mix(0.55).filled()));
+    }
+    theme()
+        .legend(&mut chart.configure_series_labels())
+        .position(SeriesLabelPosition::UpperRight)
+        .draw()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::backend::BitMapBackend;
+
+    fn close(a: f64, b: f64, tol: f64) {
+        assert!((a - b).abs() <= tol, "{} differs from {} by more than {}", a, b, tol);
+    }
+
+    fn render(draw: impl FnOnce(&DrawingArea<BitMapBackend<'_>, Shift>) -> Result<(), Box<dyn Error>>) {
+        let mut buf = vec![0; 320 * 240 * 3];
+        let root = BitMapBackend::with_buffer(&mut buf, (320, 240)).into_drawing_area();
+        draw(&root).unwrap();
+        root.present().unwrap();
+    }
+
+    #[test]
+    fn box_stats_with_outlier() {
+        let mut data = (1..=9).map(f64::from).collect::<Vec<_>>();
+        data.push(100.0);
+        let s = box_stats(&data, 1.5);
+        assert_eq!((s.n, s.q1, s.median, s.q3), (10, 3.25, 5.5, 7.75));
+        // Fences at 3.25 − 6.75 and 7.75 + 6.75
+        assert_eq!((s.whisker_lo, s.whisker_hi), (1.0, 9.0));
+        assert_eq!(s.outliers, [100.0]);
+        assert_eq!(s.mean, 14.5);
+        close(s.notch.1 - s.median, 1.57 * 4.5 / 10f64.sqrt(), 1e-12);
+    }
+
+    #[test]
+    fn box_stats_of_tiny_samples() {
+        let s = box_stats(&[], 1.5);
+        assert_eq!(s.n, 0);
+        assert!(s.outliers.is_empty());
+        for v in [s.q1, s.median, s.q3, s.whisker_lo, s.whisker_hi, s.mean, s.notch.0, s.notch.1] {
+            assert!(v.is_nan());
+        }
+
+        let s = box_stats(&[3.0], 1.5);
+        assert_eq!((s.q1, s.median, s.q3, s.whisker_lo, s.whisker_hi, s.mean), (3.0, 3.0, 3.0, 3.0, 3.0, 3.0));
+        assert_eq!(s.notch, (3.0, 3.0));
+    }
+
+    #[test]
+    fn silverman_bandwidth_matches_bw_nrd0() {
+        // bw.nrd0(1:10) = 0.9 · min(sd, IQR/1.34) · n^(−1/5)
+        let data = (1..=10).map(f64::from).collect::<Vec<_>>();
+        close(silverman_bandwidth(&data), 1.719_286_404_692_283, 1e-12);
+        // Constant data still get a usable bandwidth
+        let h = silverman_bandwidth(&[2.0, 2.0, 2.0]);
+        assert!(h > 0.0 && h.is_finite());
+    }
+
+    #[test]
+    fn kde_is_a_density() {
+        let data = [0.0, 1.0, 3.0];
+        let dx = 0.01;
+        let grid = (0..=1300).map(|i| -5.0 + i as f64 * dx).collect::<Vec<_>>();
+        let area = kde(&data, 0.5, &grid).iter().sum::<f64>() * dx;
+        close(area, 1.0, 1e-9);
+        close(kde(&[0.0], 1.0, &[0.0])[0], 1.0 / (2.0 * std::f64::consts::PI).sqrt(), 1e-15);
+    }
+
+    #[test]
+    fn empty_groups_draw_without_data() {
+        let empty = || Group::new("empty", vec![], BLUE);
+        render(|root| draw_box_plots(root, "", "y", &[empty(), empty()], &BoxStyle::default()));
+        render(|root| draw_box_plots(root, "", "y", &[empty(), Group::new("one", vec![1.0, f64::NAN, 2.0], RED)], &BoxStyle::default()));
+        render(|root| draw_violins(root, "", "y", &[empty()], 0.8));
+        let split = SplitGroup::new("split", vec![], vec![]);
+        render(|root| draw_split_violins(root, "", "y", &[split], ["l", "r"], [BLUE.into(), RED.into()]));
+    }
+}
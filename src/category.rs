@@ -0,0 +1,86 @@
+// Categorical axes whose values stay `f64`.
+//
+// Category `i` sits at `x = i` on a range of `-0.5..n - 0.5`, so elements can
+// be offset within a category (box widths, split violins, dodged bars) while
+// ticks and labels land exactly on the category centers.
+
+use plotters::coord::ranged1d::{KeyPointHint, NoDefaultFormatting, Ranged, ValueFormatter};
+use plotters::coord::types::RangedCoordf64;
+use std::ops::Range;
+
+/// An axis of named categories, usable in `build_cartesian_2d`.
+#[derive(Clone, Debug)]
+pub struct CategoryAxis {
+    names: Vec<String>,
+}
+
+impl CategoryAxis {
+    pub fn new<S: AsRef<str>>(names: &[S]) -> Self {
+        Self {
+            names: names.iter().map(|s| s.as_ref().to_string()).collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Name of the category nearest to `v` (empty outside the axis).
+    pub fn name(&self, v: f64) -> &str {
+        let i = v.round();
+        if i < 0.0 || i >= self.names.len() as f64 {
+            return "";
+        }
+        &self.names[i as usize]
+    }
+}
+
+impl Ranged for CategoryAxis {
+    type FormatOption = NoDefaultFormatting;
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        RangedCoordf64::from(self.range()).map(value, limit)
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<f64> {
+        // Every category when there is room, otherwise every k-th
+        let n = self.names.len();
+        let step = n.div_ceil(hint.max_num_points().max(1)).max(1);
+        (0..n).step_by(step).map(|i| i as f64).collect()
+    }
+
+    fn range(&self) -> Range<f64> {
+        -0.5..(self.names.len().max(1) as f64 - 0.5)
+    }
+}
+
+impl ValueFormatter<f64> for CategoryAxis {
+    fn format_ext(&self, value: &f64) -> String {
+        self.name(*value).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn category_axis_positions_and_names() {
+        let axis = CategoryAxis::new(&["a", "b", "c", "d", "e"]);
+        assert_eq!(axis.range(), -0.5..4.5);
+        assert_eq!((axis.name(0.4), axis.name(1.6), axis.name(-0.6), axis.name(4.5)), ("a", "c", "", ""));
+        assert_eq!(axis.key_points(10), [0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(axis.key_points(2), [0.0, 3.0]);
+        assert_eq!(axis.map(&2.0, (0, 100)), 50);
+
+        let empty = CategoryAxis::new::<&str>(&[]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.range(), -0.5..0.5);
+        assert!(empty.key_points(5).is_empty());
+    }
+}
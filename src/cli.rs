@@ -0,0 +1,158 @@
+// Minimal command-line parsing shared by the binaries.
+//
+// Options are `--name value`, `--name=value` or bare `--flag`s; everything
+// else is positional. Accessors remove what they read, so `finish` can
+// reject anything left over.
+
+use crate::color::{check_colors, palette, Colormap};
+use crate::theme::Theme;
+use plotters::chart::SeriesLabelPosition;
+use plotters::style::RGBColor;
+use std::error::Error;
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Default)]
+pub struct Args {
+    options: Vec<(String, Option<String>)>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    /// Splits `args` (without the program name). `flags` lists the options
+    /// that take no value; every other `--option` consumes the next argument.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I, flags: &[&str]) -> Result<Self, Box<dyn Error>> {
+        let mut out = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--").or_else(|| (arg == "-h").then_some("help")) else {
+                out.positional.push(arg);
+                continue;
+            };
+            if let Some((name, value)) = name.split_once('=') {
+                out.options.push((name.to_string(), Some(value.to_string())));
+            } else if flags.contains(&name) {
+                out.options.push((name.to_string(), None));
+            } else {
+                let value = args.next().ok_or_else(|| format!("--{} needs a value", name))?;
+                out.options.push((name.to_string(), Some(value)));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Whether `--name` was given.
+    pub fn flag(&mut self, name: &str) -> bool {
+        let before = self.options.len();
+        self.options.retain(|(n, _)| n != name);
+        self.options.len() != before
+    }
+
+    /// All values of a repeatable `--name`, in order.
+    pub fn values(&mut self, name: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut values = Vec::new();
+        let mut rest = Vec::new();
+        for (n, v) in self.options.drain(..) {
+            if n == name {
+                values.push(v.ok_or_else(|| format!("--{} needs a value", name))?);
+            } else {
+                rest.push((n, v));
+            }
+        }
+        self.options = rest;
+        Ok(values)
+    }
+
+    /// The last value of `--name`, if given.
+    pub fn value(&mut self, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+        Ok(self.values(name)?.pop())
+    }
+
+    /// The last value of `--name` parsed as `T`.
+    pub fn parsed<T>(&mut self, name: &str) -> Result<Option<T>, Box<dyn Error>>
+    where
+        T: FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.value(name)?
+            .map(|v| v.parse::<T>().map_err(|e| format!("Invalid --{} '{}': {}", name, v, e).into()))
+            .transpose()
+    }
+
+    /// Positional arguments, in order.
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+
+    /// Fails if any option was not read.
+    pub fn finish(self) -> Result<(), Box<dyn Error>> {
+        match self.options.first() {
+            Some((name, _)) => Err(format!("Unknown option --{} (see --help)", name).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Parses `MIN:MAX` (e.g. `-5:5`); either side may be empty to leave it automatic.
+pub fn parse_limits(s: &str) -> Result<(Option<f64>, Option<f64>), Box<dyn Error>> {
+    let (lo, hi) = s.split_once(':').ok_or_else(|| format!("Expected MIN:MAX, got '{}'", s))?;
+    let side = |v: &str| -> Result<Option<f64>, Box<dyn Error>> {
+        let v = v.trim();
+        Ok(if v.is_empty() { None } else { Some(v.parse()?) })
+    };
+    Ok((side(lo)?, side(hi)?))
+}
+
+/// Parses a legend position (`upper-right`, `lower-left`, `middle-middle`, ...);
+/// `none` hides the legend.
+pub fn parse_legend(s: &str) -> Result<Option<SeriesLabelPosition>, Box<dyn Error>> {
+    use SeriesLabelPosition::*;
+    Ok(Some(match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+        "none" | "off" => return Ok(None),
+        "upper-left" => UpperLeft,
+        "upper-middle" | "upper-center" => UpperMiddle,
+        "upper-right" => UpperRight,
+        "middle-left" => MiddleLeft,
+        "middle-middle" | "center" => MiddleMiddle,
+        "middle-right" => MiddleRight,
+        "lower-left" => LowerLeft,
+        "lower-middle" | "lower-center" => LowerMiddle,
+        "lower-right" => LowerRight,
+        other => return Err(format!("Unknown legend position '{}'", other).into()),
+    }))
+}
+
+/// `--theme`, `--palette` and `--colormap`, applied over a default theme.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThemeArgs {
+    theme: Option<Theme>,
+    palette: Option<&'static [RGBColor]>,
+    colormap: Option<Colormap>,
+}
+
+impl ThemeArgs {
+    pub fn parse(args: &mut Args) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            theme: args.parsed("theme")?,
+            palette: args.value("palette")?.map(|p| palette(&p)).transpose()?,
+            colormap: args.parsed("colormap")?,
+        })
+    }
+
+    /// `base`, or the `--theme` given, with the `--palette` and `--colormap` given.
+    pub fn apply(&self, base: Theme) -> Theme {
+        let theme = self.theme.unwrap_or(base);
+        let theme = self.palette.map_or(theme, |p| theme.with_palette(p));
+        self.colormap.map_or(theme, |c| theme.with_colormap(c))
+    }
+}
+
+/// Prints how close the closest two of `colors` come with each kind of color
+/// vision, flagging the ones that are hard to tell apart.
+pub fn print_color_check(colors: &[RGBColor]) {
+    if colors.len() < 2 {
+        println!("  one color, nothing to compare");
+    }
+    for check in check_colors(colors) {
+        println!("  {} {}", if check.passes() { "ok  " } else { "FAIL" }, check);
+    }
+}
@@ -0,0 +1,535 @@
+// Colormaps, qualitative palettes and color-vision-deficiency checks.
+//
+// A `Colormap` maps [0, 1] to a color by interpolating evenly spaced stops
+// taken from the published tables; `reversed` flips it. Palettes are plain
+// `&'static [RGBColor]` slices so a `Theme` can hold one. `Vision` simulates
+// the three dichromacies with the full-severity matrices of Machado, Oliveira
+// and Fernandes (2009), applied in linear RGB, and `check_colors` reports how
+// close the two closest colors of a set come under each, in CIEDE2000.
+
+use plotters::prelude::*;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// Series colors of the light theme.
+pub const BASIC_PALETTE: [RGBColor; 6] = [BLUE, RED, GREEN, MAGENTA, CYAN, BLACK];
+
+/// Light, saturated series colors that stand out on a dark background.
+pub const DARK_PALETTE: [RGBColor; 6] = [
+    RGBColor(86, 180, 233),
+    RGBColor(255, 120, 100),
+    RGBColor(120, 220, 120),
+    RGBColor(240, 228, 66),
+    RGBColor(204, 153, 255),
+    RGBColor(230, 230, 230),
+];
+
+/// Gray levels far enough apart to survive printing in black and white.
+pub const GRAY_PALETTE: [RGBColor; 4] = [BLACK, RGBColor(110, 110, 110), RGBColor(165, 165, 165), RGBColor(60, 60, 60)];
+
+/// The Okabe–Ito palette, distinguishable under every common color-vision deficiency.
+pub const OKABE_ITO: [RGBColor; 8] = [
+    RGBColor(0, 0, 0),       // black
+    RGBColor(230, 159, 0),   // orange
+    RGBColor(86, 180, 233),  // sky blue
+    RGBColor(0, 158, 115),   // bluish green
+    RGBColor(240, 228, 66),  // yellow
+    RGBColor(0, 114, 178),   // blue
+    RGBColor(213, 94, 0),    // vermillion
+    RGBColor(204, 121, 167), // reddish purple
+];
+
+/// Tableau 10, matplotlib's default color cycle (`tab10`).
+pub const TABLEAU_10: [RGBColor; 10] = [
+    RGBColor(31, 119, 180),
+    RGBColor(255, 127, 14),
+    RGBColor(44, 160, 44),
+    RGBColor(214, 39, 40),
+    RGBColor(148, 103, 189),
+    RGBColor(140, 86, 75),
+    RGBColor(227, 119, 194),
+    RGBColor(127, 127, 127),
+    RGBColor(188, 189, 34),
+    RGBColor(23, 190, 207),
+];
+
+/// Names accepted by [`palette`].
+pub const PALETTE_NAMES: [&str; 5] = ["basic", "dark", "gray", "okabe-ito", "tableau10"];
+
+/// The palette called `name` (see [`PALETTE_NAMES`]).
+pub fn palette(name: &str) -> Result<&'static [RGBColor], Box<dyn Error>> {
+    match name.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+        "basic" | "light" => Ok(&BASIC_PALETTE),
+        "dark" => Ok(&DARK_PALETTE),
+        "gray" | "grey" | "print" => Ok(&GRAY_PALETTE),
+        "okabe-ito" | "okabeito" => Ok(&OKABE_ITO),
+        "tableau10" | "tableau-10" | "tab10" => Ok(&TABLEAU_10),
+        _ => Err(format!("Unknown palette '{}' (expected one of: {})", name, PALETTE_NAMES.join(", ")).into()),
+    }
+}
+
+const VIRIDIS: [RGBColor; 10] = [
+    RGBColor(0x44, 0x01, 0x54),
+    RGBColor(0x48, 0x28, 0x78),
+    RGBColor(0x3e, 0x4a, 0x89),
+    RGBColor(0x31, 0x68, 0x8e),
+    RGBColor(0x26, 0x82, 0x8e),
+    RGBColor(0x1f, 0x9e, 0x89),
+    RGBColor(0x35, 0xb7, 0x79),
+    RGBColor(0x6d, 0xcd, 0x59),
+    RGBColor(0xb4, 0xde, 0x2c),
+    RGBColor(0xfd, 0xe7, 0x25),
+];
+
+const MAGMA: [RGBColor; 10] = [
+    RGBColor(0x00, 0x00, 0x04),
+    RGBColor(0x18, 0x0f, 0x3e),
+    RGBColor(0x45, 0x10, 0x77),
+    RGBColor(0x72, 0x1f, 0x81),
+    RGBColor(0x9f, 0x2f, 0x7f),
+    RGBColor(0xcd, 0x40, 0x71),
+    RGBColor(0xf1, 0x60, 0x5d),
+    RGBColor(0xfd, 0x95, 0x67),
+    RGBColor(0xfe, 0xc9, 0x8d),
+    RGBColor(0xfc, 0xfd, 0xbf),
+];
+
+const CIVIDIS: [RGBColor; 10] = [
+    RGBColor(0x00, 0x20, 0x4d),
+    RGBColor(0x00, 0x33, 0x6f),
+    RGBColor(0x39, 0x48, 0x6b),
+    RGBColor(0x57, 0x5c, 0x6d),
+    RGBColor(0x70, 0x71, 0x73),
+    RGBColor(0x8a, 0x87, 0x79),
+    RGBColor(0xa6, 0x9d, 0x75),
+    RGBColor(0xc4, 0xb5, 0x6c),
+    RGBColor(0xe4, 0xcf, 0x5b),
+    RGBColor(0xff, 0xea, 0x46),
+];
+
+// Sampled from Google's polynomial approximation of Turbo (the one d3 uses)
+const TURBO: [RGBColor; 17] = [
+    RGBColor(35, 23, 27),
+    RGBColor(73, 62, 175),
+    RGBColor(68, 106, 238),
+    RGBColor(50, 149, 247),
+    RGBColor(38, 189, 225),
+    RGBColor(41, 221, 187),
+    RGBColor(64, 243, 146),
+    RGBColor(102, 253, 109),
+    RGBColor(150, 250, 80),
+    RGBColor(198, 235, 59),
+    RGBColor(238, 208, 45),
+    RGBColor(255, 171, 36),
+    RGBColor(255, 128, 29),
+    RGBColor(238, 84, 21),
+    RGBColor(201, 45, 12),
+    RGBColor(161, 18, 2),
+    RGBColor(144, 13, 0),
+];
+
+/// A continuous colormap from [0, 1] to colors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Colormap {
+    name: &'static str,
+    stops: &'static [RGBColor],
+    reversed: bool,
+}
+
+impl Colormap {
+    /// Perceptually uniform, dark purple to yellow; matplotlib's default.
+    pub const VIRIDIS: Self = Self::new("viridis", &VIRIDIS);
+    /// Perceptually uniform, black through purple and orange to pale yellow.
+    pub const MAGMA: Self = Self::new("magma", &MAGMA);
+    /// Viridis adjusted to look the same with red-green color blindness, dark blue to yellow.
+    pub const CIVIDIS: Self = Self::new("cividis", &CIVIDIS);
+    /// A smooth rainbow for data where telling nearby values apart matters more than uniformity.
+    pub const TURBO: Self = Self::new("turbo", &TURBO);
+
+    pub const ALL: [Self; 4] = [Self::VIRIDIS, Self::MAGMA, Self::CIVIDIS, Self::TURBO];
+
+    const fn new(name: &'static str, stops: &'static [RGBColor]) -> Self {
+        Self {
+            name,
+            stops,
+            reversed: false,
+        }
+    }
+
+    /// The same colormap running the other way (`viridis_r`).
+    pub const fn reversed(self) -> Self {
+        Self {
+            reversed: !self.reversed,
+            ..self
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
+    /// The color at `t`, clamped to [0, 1]; NaN maps to the start.
+    pub fn at(&self, t: f64) -> RGBColor {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let t = if self.reversed { 1.0 - t } else { t };
+        let pos = t * (self.stops.len() - 1) as f64;
+        let i = (pos.floor() as usize).min(self.stops.len() - 2);
+        let f = pos - i as f64;
+        let (a, b) = (self.stops[i], self.stops[i + 1]);
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * f).round() as u8;
+        RGBColor(mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+    }
+
+    /// The color of `value` on a scale from `min` to `max`.
+    pub fn normalized(&self, value: f64, min: f64, max: f64) -> RGBColor {
+        self.at(if max > min { (value - min) / (max - min) } else { 0.5 })
+    }
+
+    /// `n` colors spread evenly from one end to the other.
+    pub fn sample(&self, n: usize) -> Vec<RGBColor> {
+        match n {
+            0 => Vec::new(),
+            1 => vec![self.at(0.5)],
+            _ => (0..n).map(|i| self.at(i as f64 / (n - 1) as f64)).collect(),
+        }
+    }
+}
+
+impl Default for Colormap {
+    fn default() -> Self {
+        Self::VIRIDIS
+    }
+}
+
+impl fmt::Display for Colormap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.name, if self.reversed { "_r" } else { "" })
+    }
+}
+
+impl FromStr for Colormap {
+    type Err = Box<dyn Error>;
+
+    /// A colormap name, with an `_r` suffix for the reversed variant.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        let (base, reversed) = match name.strip_suffix("_r").or_else(|| name.strip_suffix("-r")) {
+            Some(base) => (base, true),
+            None => (name.as_str(), false),
+        };
+        let map = Self::ALL.into_iter().find(|m| m.name == base).ok_or_else(|| {
+            let names = Self::ALL.map(|m| m.name).join(", ");
+            format!("Unknown colormap '{}' (expected one of: {}, with _r to reverse)", s, names)
+        })?;
+        Ok(if reversed { map.reversed() } else { map })
+    }
+}
+
+impl ColorMap<RGBColor, f64> for Colormap {
+    fn get_color_normalized(&self, h: f64, min: f64, max: f64) -> RGBColor {
+        self.normalized(h, min, max)
+    }
+}
+
+/// Color vision, normal or with one of the dichromacies.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Vision {
+    Normal,
+    /// No long-wavelength (red) cones.
+    Protanopia,
+    /// No medium-wavelength (green) cones; the most common.
+    Deuteranopia,
+    /// No short-wavelength (blue) cones.
+    Tritanopia,
+}
+
+impl Vision {
+    pub const ALL: [Self; 4] = [Self::Normal, Self::Protanopia, Self::Deuteranopia, Self::Tritanopia];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Protanopia => "protanopia",
+            Self::Deuteranopia => "deuteranopia",
+            Self::Tritanopia => "tritanopia",
+        }
+    }
+
+    fn matrix(self) -> Option<[[f64; 3]; 3]> {
+        match self {
+            Self::Normal => None,
+            Self::Protanopia => Some([
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ]),
+            Self::Deuteranopia => Some([
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ]),
+            Self::Tritanopia => Some([
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ]),
+        }
+    }
+
+    /// How `color` looks with this vision.
+    pub fn simulate(self, color: RGBColor) -> RGBColor {
+        let Some(m) = self.matrix() else { return color };
+        let lin = [to_linear(color.0), to_linear(color.1), to_linear(color.2)];
+        let [r, g, b] = m.map(|row| from_linear(row[0] * lin[0] + row[1] * lin[1] + row[2] * lin[2]));
+        RGBColor(r, g, b)
+    }
+
+    /// Simulates this vision on packed 8-bit RGB pixels in place, e.g. a
+    /// [`crate::render::RenderedImage`] before writing it out.
+    pub fn simulate_rgb(self, rgb: &mut [u8]) {
+        let Some(m) = self.matrix() else { return };
+        let linear: [f64; 256] = std::array::from_fn(|v| to_linear(v as u8));
+        for px in rgb.chunks_exact_mut(3) {
+            let lin = [linear[px[0] as usize], linear[px[1] as usize], linear[px[2] as usize]];
+            for (out, row) in px.iter_mut().zip(m) {
+                *out = from_linear(row[0] * lin[0] + row[1] * lin[1] + row[2] * lin[2]);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Vision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Vision {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "normal" | "none" => Ok(Self::Normal),
+            "protanopia" | "protan" => Ok(Self::Protanopia),
+            "deuteranopia" | "deutan" => Ok(Self::Deuteranopia),
+            "tritanopia" | "tritan" => Ok(Self::Tritanopia),
+            _ => Err(format!("Unknown vision '{}' (expected normal, protanopia, deuteranopia or tritanopia)", s).into()),
+        }
+    }
+}
+
+/// The CIEDE2000 difference below which two series colors are easily confused.
+pub const MIN_DELTA_E: f64 = 10.0;
+
+/// The closest pair of a set of colors under one kind of vision.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VisionCheck {
+    pub vision: Vision,
+    /// Indices of the two closest colors.
+    pub closest: (usize, usize),
+    /// Their CIEDE2000 difference.
+    pub delta_e: f64,
+}
+
+impl VisionCheck {
+    pub fn passes(&self) -> bool {
+        self.delta_e >= MIN_DELTA_E
+    }
+}
+
+impl fmt::Display for VisionCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: closest colors {} and {}, ΔE2000 {:.1} (at least {} needed)",
+            self.vision, self.closest.0, self.closest.1, self.delta_e, MIN_DELTA_E
+        )
+    }
+}
+
+/// The closest pair of `colors` under each [`Vision`]; empty for fewer than two colors.
+pub fn check_colors(colors: &[RGBColor]) -> Vec<VisionCheck> {
+    if colors.len() < 2 {
+        return Vec::new();
+    }
+    Vision::ALL
+        .into_iter()
+        .map(|vision| {
+            let lab = colors.iter().map(|&c| to_lab(vision.simulate(c))).collect::<Vec<_>>();
+            let mut worst = VisionCheck {
+                vision,
+                closest: (0, 1),
+                delta_e: f64::INFINITY,
+            };
+            for i in 0..lab.len() {
+                for j in i + 1..lab.len() {
+                    let delta_e = ciede2000(lab[i], lab[j]);
+                    if delta_e < worst.delta_e {
+                        worst.closest = (i, j);
+                        worst.delta_e = delta_e;
+                    }
+                }
+            }
+            worst
+        })
+        .collect()
+}
+
+/// The checks of [`check_colors`] that fail, i.e. where some pair is hard to tell apart.
+pub fn color_conflicts(colors: &[RGBColor]) -> Vec<VisionCheck> {
+    check_colors(colors).into_iter().filter(|c| !c.passes()).collect()
+}
+
+/// The CIEDE2000 color difference between two colors.
+pub fn delta_e(a: RGBColor, b: RGBColor) -> f64 {
+    ciede2000(to_lab(a), to_lab(b))
+}
+
+fn to_linear(v: u8) -> f64 {
+    let c = v as f64 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(c: f64) -> u8 {
+    let c = c.clamp(0.0, 1.0);
+    let v = if c <= 0.0031308 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    (v * 255.0).round() as u8
+}
+
+// CIE L*a*b* under D65
+fn to_lab(c: RGBColor) -> [f64; 3] {
+    let (r, g, b) = (to_linear(c.0), to_linear(c.1), to_linear(c.2));
+    let x = (0.4124564 * r + 0.3575761 * g + 0.1804375 * b) / 0.95047;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+    let z = (0.0193339 * r + 0.1191920 * g + 0.9503041 * b) / 1.08883;
+    let f = |t: f64| {
+        const D: f64 = 6.0 / 29.0;
+        if t > D * D * D {
+            t.cbrt()
+        } else {
+            t / (3.0 * D * D) + 4.0 / 29.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+// Sharma, Wu and Dalal's formulation of CIEDE2000
+fn ciede2000([l1, a1, b1]: [f64; 3], [l2, a2, b2]: [f64; 3]) -> f64 {
+    const POW25_7: f64 = 6_103_515_625.0; // 25^7
+    let c_bar = (a1.hypot(b1) + a2.hypot(b2)) / 2.0;
+    let g = 0.5 * (1.0 - (c_bar.powi(7) / (c_bar.powi(7) + POW25_7)).sqrt());
+    let (a1, a2) = ((1.0 + g) * a1, (1.0 + g) * a2);
+    let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+    let hue = |b: f64, a: f64| if a == 0.0 && b == 0.0 { 0.0 } else { b.atan2(a).to_degrees().rem_euclid(360.0) };
+    let (h1, h2) = (hue(b1, a1), hue(b2, a2));
+
+    let dl = l2 - l1;
+    let dc = c2 - c1;
+    let dh = if c1 * c2 == 0.0 {
+        0.0
+    } else if (h2 - h1).abs() <= 180.0 {
+        h2 - h1
+    } else if h2 - h1 > 180.0 {
+        h2 - h1 - 360.0
+    } else {
+        h2 - h1 + 360.0
+    };
+    let dh = 2.0 * (c1 * c2).sqrt() * (dh.to_radians() / 2.0).sin();
+
+    let l_bar = (l1 + l2) / 2.0;
+    let c_bar = (c1 + c2) / 2.0;
+    let h_bar = if c1 * c2 == 0.0 {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180.0 {
+        (h1 + h2) / 2.0
+    } else if h1 + h2 < 360.0 {
+        (h1 + h2 + 360.0) / 2.0
+    } else {
+        (h1 + h2 - 360.0) / 2.0
+    };
+    let cos = |deg: f64| deg.to_radians().cos();
+    let t = 1.0 - 0.17 * cos(h_bar - 30.0) + 0.24 * cos(2.0 * h_bar) + 0.32 * cos(3.0 * h_bar + 6.0)
+        - 0.20 * cos(4.0 * h_bar - 63.0);
+    let d_theta = 30.0 * (-((h_bar - 275.0) / 25.0).powi(2)).exp();
+    let rc = 2.0 * (c_bar.powi(7) / (c_bar.powi(7) + POW25_7)).sqrt();
+    let sl = 1.0 + 0.015 * (l_bar - 50.0).powi(2) / (20.0 + (l_bar - 50.0).powi(2)).sqrt();
+    let sc = 1.0 + 0.045 * c_bar;
+    let sh = 1.0 + 0.015 * c_bar * t;
+    let rt = -(2.0 * d_theta).to_radians().sin() * rc;
+
+    let (l, c, h) = (dl / sl, dc / sc, dh / sh);
+    (l * l + c * c + h * h + rt * c * h).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ciede2000_matches_sharma_test_data() {
+        // Pairs from Sharma, Wu & Dalal (2005), Table 1: L*a*b* of both colors and ΔE00
+        let pairs = [
+            ([50.0, 2.6772, -79.7751], [50.0, 0.0, -82.7485], 2.0425),
+            ([50.0, 3.1571, -77.2803], [50.0, 0.0, -82.7485], 2.8615),
+            ([50.0, 2.8361, -74.0200], [50.0, 0.0, -82.7485], 3.4412),
+            ([50.0, -1.3802, -84.2814], [50.0, 0.0, -82.7485], 1.0000),
+            ([50.0, 0.0, 0.0], [50.0, -1.0, 2.0], 2.3669),
+            ([50.0, -0.001, 2.49], [50.0, 0.0009, -2.49], 4.8045),
+            ([50.0, 2.49, -0.001], [50.0, -2.49, 0.0009], 7.1792),
+            ([50.0, 2.49, -0.001], [50.0, -2.49, 0.0011], 7.2195),
+            ([50.0, 2.5, 0.0], [50.0, 0.0, -2.5], 4.3065),
+            ([50.0, 2.5, 0.0], [73.0, 25.0, -18.0], 27.1492),
+            ([50.0, 2.5, 0.0], [61.0, -5.0, 29.0], 22.8977),
+            ([50.0, 2.5, 0.0], [56.0, -27.0, -3.0], 31.9030),
+            ([50.0, 2.5, 0.0], [58.0, 24.0, 15.0], 19.4535),
+            ([60.2574, -34.0099, 36.2677], [60.4626, -34.1751, 39.4387], 1.2644),
+            ([63.0109, -31.0961, -5.8663], [62.8187, -29.7946, -4.0864], 1.2630),
+            ([35.0831, -44.1164, 3.7933], [35.0232, -40.0716, 1.5901], 1.8645),
+        ];
+        for (a, b, expected) in pairs {
+            let d = ciede2000(a, b);
+            assert!((d - expected).abs() < 1e-4, "{:?} vs {:?}: {} instead of {}", a, b, d, expected);
+            assert!((ciede2000(b, a) - d).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn delta_e_of_identical_colors_is_zero() {
+        let c = RGBColor(31, 119, 180);
+        assert!(delta_e(c, c).abs() < 1e-9);
+        assert!(delta_e(BLACK, WHITE) > 99.0);
+    }
+
+    #[test]
+    fn dichromat_simulation_maps_gray_to_gray() {
+        for vision in Vision::ALL {
+            for v in [0, 32, 100, 128, 200, 255] {
+                let RGBColor(r, g, b) = vision.simulate(RGBColor(v, v, v));
+                for c in [r, g, b] {
+                    assert!(c.abs_diff(v) <= 1, "{} turns gray {} into ({}, {}, {})", vision, v, r, g, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn simulate_rgb_matches_simulate() {
+        let colors = [RGBColor(230, 159, 0), RGBColor(86, 180, 233), RGBColor(204, 121, 167)];
+        let mut rgb = colors.iter().flat_map(|c| [c.0, c.1, c.2]).collect::<Vec<_>>();
+        Vision::Deuteranopia.simulate_rgb(&mut rgb);
+        for (px, &c) in rgb.chunks_exact(3).zip(&colors) {
+            let s = Vision::Deuteranopia.simulate(c);
+            assert_eq!(px, [s.0, s.1, s.2]);
+        }
+    }
+}
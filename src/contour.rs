@@ -0,0 +1,604 @@
+// Contour plots: lines of constant value through a `Grid`, and filled bands
+// between consecutive levels.
+//
+// `contour_lines` runs marching squares over every grid cell: each corner is
+// above or below the level, the crossing on each edge is placed by linear
+// interpolation, and ambiguous saddle cells are resolved by the average of the
+// four corners. The cell segments are then joined into polylines, open where
+// they leave the grid and closed otherwise. `contour_bands` cuts cells that a
+// band boundary crosses into four triangles about the cell center and clips
+// each one to the band, so the polygons of neighbouring bands meet exactly.
+//
+// Both draw on a `Chart2d`, the same axes as line plots, so `draw_contour_plot`
+// returns its chart for overlays and the lines can be drawn over a heatmap.
+
+use crate::color::Colormap;
+use crate::heatmap::{colorbar_width, draw_colorbar, ColorNorm, ColorScale, Grid};
+use crate::line::Chart2d;
+use crate::theme::theme;
+use crate::ticks::TickFormat;
+use crate::units::pt;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// About `n` round levels (steps of 1, 2, 2.5 or 5 × 10^k) strictly inside the
+/// grid's value range. Empty for a grid without finite values or with a flat one.
+pub fn auto_levels(grid: &Grid, n: usize) -> Vec<f64> {
+    let Some((min, max)) = grid.z_range() else {
+        return vec![];
+    };
+    if n == 0 || max <= min {
+        return vec![];
+    }
+    let raw = (max - min) / n as f64;
+    let exponent = raw.log10().floor() as i32;
+    let magnitude = 10f64.powi(exponent);
+    let mantissa = [1.0, 2.0, 2.5, 5.0, 10.0]
+        .into_iter()
+        .find(|m| m * magnitude >= raw)
+        .unwrap_or(10.0);
+    // Dividing by an exact power of ten gives 0.6 rather than 3 × 0.2 = 0.6000000000000001
+    let level = |k: i64| {
+        if exponent < 0 {
+            k as f64 * mantissa / 10f64.powi(-exponent)
+        } else {
+            k as f64 * mantissa * magnitude
+        }
+    };
+    let first = (min / (mantissa * magnitude)).floor() as i64 + 1;
+    (first..).map(level).take_while(|&v| v < max).filter(|&v| v > min).collect()
+}
+
+/// The contour of one level: polylines in data coordinates, closed ones
+/// ending where they start.
+#[derive(Clone, Debug)]
+pub struct ContourLine {
+    pub level: f64,
+    pub paths: Vec<Vec<(f64, f64)>>,
+}
+
+/// The contours of `grid` at each of `levels`. Cells with a non-finite corner
+/// are skipped, leaving gaps in the lines.
+pub fn contour_lines(grid: &Grid, levels: &[f64]) -> Vec<ContourLine> {
+    levels
+        .iter()
+        .map(|&level| ContourLine { level, paths: trace_level(grid, level) })
+        .collect()
+}
+
+// A grid edge: the horizontal one from (i, j) to (i + 1, j) or the vertical one
+// from (i, j) to (i, j + 1). Neighbouring cells share the key of their common edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Edge {
+    H(usize, usize),
+    V(usize, usize),
+}
+
+// Corners of a cell in counter-clockwise order from the bottom left
+const CORNERS: [(usize, usize); 4] = [(0, 0), (1, 0), (1, 1), (0, 1)];
+
+// The two cell edges (0 bottom, 1 right, 2 top, 3 left) meeting at each corner
+const CORNER_EDGES: [(usize, usize); 4] = [(3, 0), (0, 1), (1, 2), (2, 3)];
+
+fn cell_edge(i: usize, j: usize, side: usize) -> Edge {
+    match side {
+        0 => Edge::H(i, j),
+        1 => Edge::V(i + 1, j),
+        2 => Edge::H(i, j + 1),
+        _ => Edge::V(i, j),
+    }
+}
+
+// The four corner values of cell (i, j), if all are finite
+fn cell_values(grid: &Grid, i: usize, j: usize) -> Option<[f64; 4]> {
+    let z = CORNERS.map(|(di, dj)| grid.z[j + dj][i + di]);
+    z.iter().all(|v| v.is_finite()).then_some(z)
+}
+
+fn cell_count(grid: &Grid) -> (usize, usize) {
+    (grid.x.len().saturating_sub(1), grid.y.len().saturating_sub(1))
+}
+
+// Where `level` crosses `edge`, interpolating between its end values
+fn crossing(grid: &Grid, edge: Edge, level: f64) -> (f64, f64) {
+    let ((i0, j0), (i1, j1)) = match edge {
+        Edge::H(i, j) => ((i, j), (i + 1, j)),
+        Edge::V(i, j) => ((i, j), (i, j + 1)),
+    };
+    let (z0, z1) = (grid.z[j0][i0], grid.z[j1][i1]);
+    let t = if z1 == z0 { 0.5 } else { (level - z0) / (z1 - z0) };
+    (
+        grid.x[i0] + t * (grid.x[i1] - grid.x[i0]),
+        grid.y[j0] + t * (grid.y[j1] - grid.y[j0]),
+    )
+}
+
+// Marching squares for one level, then the segments joined into paths
+fn trace_level(grid: &Grid, level: f64) -> Vec<Vec<(f64, f64)>> {
+    let (nx, ny) = cell_count(grid);
+    let mut segments = Vec::new();
+    for j in 0..ny {
+        for i in 0..nx {
+            let Some(z) = cell_values(grid, i, j) else { continue };
+            let above = z.map(|v| v >= level);
+            let crossed = (0..4).filter(|&k| above[k] != above[(k + 1) % 4]).collect::<Vec<_>>();
+            match crossed.len() {
+                2 => segments.push((cell_edge(i, j, crossed[0]), cell_edge(i, j, crossed[1]))),
+                4 => {
+                    // Saddle: cut off the two corners on the other side from the center
+                    let center = z.iter().sum::<f64>() / 4.0 >= level;
+                    for k in (0..4).filter(|&k| above[k] != center) {
+                        let (a, b) = CORNER_EDGES[k];
+                        segments.push((cell_edge(i, j, a), cell_edge(i, j, b)));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    join_segments(&segments)
+        .into_iter()
+        .map(|path| path.into_iter().map(|e| crossing(grid, e, level)).collect())
+        .collect()
+}
+
+// Chains segments sharing an edge, starting from loose ends so that open
+// paths are walked whole; what remains are loops.
+fn join_segments(segments: &[(Edge, Edge)]) -> Vec<Vec<Edge>> {
+    let mut at_edge: HashMap<Edge, Vec<usize>> = HashMap::new();
+    for (s, &(a, b)) in segments.iter().enumerate() {
+        at_edge.entry(a).or_default().push(s);
+        at_edge.entry(b).or_default().push(s);
+    }
+    let loose = |e: &Edge| at_edge[e].len() == 1;
+    let starts = segments
+        .iter()
+        .enumerate()
+        .filter_map(|(s, (a, b))| {
+            if loose(a) {
+                Some((s, *a))
+            } else if loose(b) {
+                Some((s, *b))
+            } else {
+                None
+            }
+        })
+        .chain(segments.iter().enumerate().map(|(s, (a, _))| (s, *a)))
+        .collect::<Vec<_>>();
+
+    let mut used = vec![false; segments.len()];
+    let mut paths = Vec::new();
+    for (start, from) in starts {
+        if used[start] {
+            continue;
+        }
+        let mut path = vec![from];
+        let (mut s, mut edge) = (start, from);
+        loop {
+            used[s] = true;
+            let (a, b) = segments[s];
+            edge = if a == edge { b } else { a };
+            path.push(edge);
+            match at_edge[&edge].iter().find(|&&next| !used[next]) {
+                Some(&next) => s = next,
+                None => break,
+            }
+        }
+        paths.push(path);
+    }
+    paths
+}
+
+/// The region where `lo <= z <= hi`, as polygons in data coordinates.
+#[derive(Clone, Debug)]
+pub struct ContourBand {
+    pub lo: f64,
+    pub hi: f64,
+    pub polygons: Vec<Vec<(f64, f64)>>,
+}
+
+/// The bands between consecutive `levels`, plus one from the grid's minimum to
+/// the first level and one from the last level to its maximum.
+pub fn contour_bands(grid: &Grid, levels: &[f64]) -> Vec<ContourBand> {
+    let Some((min, max)) = grid.z_range() else {
+        return vec![];
+    };
+    let edges = band_edges(levels, min, max);
+    edges
+        .windows(2)
+        .map(|w| ContourBand { lo: w[0], hi: w[1], polygons: band_polygons(grid, w[0], w[1]) })
+        .collect()
+}
+
+// `levels` strictly inside (min, max), framed by min and max
+fn band_edges(levels: &[f64], min: f64, max: f64) -> Vec<f64> {
+    let mut edges = vec![min];
+    edges.extend(levels.iter().copied().filter(|&v| v > min && v < max));
+    edges.push(max);
+    edges
+}
+
+fn band_polygons(grid: &Grid, lo: f64, hi: f64) -> Vec<Vec<(f64, f64)>> {
+    let (nx, ny) = cell_count(grid);
+    let mut polygons = Vec::new();
+    for j in 0..ny {
+        for i in 0..nx {
+            let Some(z) = cell_values(grid, i, j) else { continue };
+            if z.iter().all(|&v| v < lo) || z.iter().all(|&v| v > hi) {
+                continue;
+            }
+            let corners = CORNERS.map(|(di, dj)| (grid.x[i + di], grid.y[j + dj]));
+            if z.iter().all(|&v| v >= lo && v <= hi) {
+                polygons.push(corners.to_vec());
+                continue;
+            }
+            let center = (
+                (corners[0].0 + corners[2].0) / 2.0,
+                (corners[0].1 + corners[2].1) / 2.0,
+                z.iter().sum::<f64>() / 4.0,
+            );
+            for k in 0..4 {
+                let (a, b) = (corners[k], corners[(k + 1) % 4]);
+                let triangle = vec![(a.0, a.1, z[k]), (b.0, b.1, z[(k + 1) % 4]), center];
+                let clipped = clip(&clip(&triangle, lo, true), hi, false);
+                if clipped.len() >= 3 {
+                    polygons.push(clipped.into_iter().map(|(x, y, _)| (x, y)).collect());
+                }
+            }
+        }
+    }
+    polygons
+}
+
+// One Sutherland–Hodgman pass: keeps the part of `polygon` with z >= limit
+// (`keep_above`) or z <= limit, z varying linearly along each side.
+fn clip(polygon: &[(f64, f64, f64)], limit: f64, keep_above: bool) -> Vec<(f64, f64, f64)> {
+    let inside = |p: &(f64, f64, f64)| if keep_above { p.2 >= limit } else { p.2 <= limit };
+    let mut out = Vec::with_capacity(polygon.len() + 2);
+    for (k, p) in polygon.iter().enumerate() {
+        let q = &polygon[(k + 1) % polygon.len()];
+        if inside(p) {
+            out.push(*p);
+        }
+        if inside(p) != inside(q) {
+            let t = (limit - p.2) / (q.2 - p.2);
+            out.push((p.0 + t * (q.0 - p.0), p.1 + t * (q.1 - p.1), limit));
+        }
+    }
+    out
+}
+
+/// Options for the contour drawing functions.
+#[derive(Clone, Debug)]
+pub struct ContourStyle {
+    /// Fills the bands between levels with colors from the colormap.
+    pub filled: bool,
+    /// Draws the contour lines; a filled plot can leave them out.
+    pub lines: bool,
+    /// Color of the lines; `None` colors each level from the colormap, or uses
+    /// the theme's foreground over filled bands.
+    pub color: Option<RGBColor>,
+    /// Line width in points; `None` uses the theme's line width.
+    pub width: Option<f64>,
+    /// Writes each level's value on its longer lines with this format.
+    pub labels: Option<TickFormat>,
+    /// `None` uses the theme's colormap.
+    pub colormap: Option<Colormap>,
+    /// Draws a colorbar of the bands next to filled plots.
+    pub colorbar: bool,
+    /// Caption of the colorbar.
+    pub label: String,
+}
+
+impl Default for ContourStyle {
+    fn default() -> Self {
+        Self {
+            filled: false,
+            lines: true,
+            color: None,
+            width: None,
+            labels: None,
+            colormap: None,
+            colorbar: true,
+            label: String::new(),
+        }
+    }
+}
+
+impl ContourStyle {
+    pub fn filled(mut self) -> Self {
+        self.filled = true;
+        self
+    }
+
+    pub fn hide_lines(mut self) -> Self {
+        self.lines = false;
+        self
+    }
+
+    pub fn color(mut self, color: RGBColor) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn width(mut self, points: f64) -> Self {
+        self.width = Some(points);
+        self
+    }
+
+    pub fn labels(mut self, format: TickFormat) -> Self {
+        self.labels = Some(format);
+        self
+    }
+
+    pub fn colormap(mut self, colormap: Colormap) -> Self {
+        self.colormap = Some(colormap);
+        self
+    }
+
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = label.to_string();
+        self
+    }
+
+    pub fn hide_colorbar(mut self) -> Self {
+        self.colorbar = false;
+        self
+    }
+
+    fn map(&self) -> Colormap {
+        self.colormap.unwrap_or_else(|| theme().colormap)
+    }
+}
+
+/// Draws contour `lines` on `chart`, labelling each level on its lines that
+/// are long enough on screen when the style has a label format. `norm` places
+/// the levels on the colormap when the style has no line color.
+pub fn draw_contour_lines<DB>(
+    chart: &mut Chart2d<'_, DB>,
+    lines: &[ContourLine],
+    norm: &ColorNorm,
+    style: &ContourStyle,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let theme = theme();
+    let width = pt(style.width.unwrap_or(theme.line_width));
+    let map = style.map();
+    for line in lines {
+        let color = match style.color {
+            Some(color) => color,
+            None if style.filled => theme.foreground,
+            None => norm.apply(line.level).map_or(theme.foreground, |t| map.at(t)),
+        };
+        chart.draw_series(
+            line.paths
+                .iter()
+                .map(|path| PathElement::new(path.clone(), color.stroke_width(width))),
+        )?;
+    }
+    if let Some(format) = &style.labels {
+        draw_level_labels(chart, lines, format)?;
+    }
+    Ok(())
+}
+
+// One label per path, at the middle of its length on screen, on a patch of
+// background that hides the line under it. Paths shorter than a few label
+// widths stay unlabelled.
+fn draw_level_labels<DB>(chart: &mut Chart2d<'_, DB>, lines: &[ContourLine], format: &TickFormat) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let theme = theme();
+    let font = theme.label_font().pos(Pos::new(HPos::Center, VPos::Center));
+    let pad = pt(2.0) as i32;
+    let mut labels = Vec::new();
+    for line in lines {
+        let text = format.format(line.level);
+        let (w, h) = chart.plotting_area().estimate_text_size(&text, &font)?;
+        for path in &line.paths {
+            let pixels = path.iter().map(|p| chart.backend_coord(p)).collect::<Vec<_>>();
+            let steps = pixels
+                .windows(2)
+                .map(|s| ((s[1].0 - s[0].0) as f64).hypot((s[1].1 - s[0].1) as f64))
+                .collect::<Vec<_>>();
+            let length = steps.iter().sum::<f64>();
+            if length < 3.0 * w as f64 {
+                continue;
+            }
+            let mut walked = 0.0;
+            let middle = steps
+                .iter()
+                .position(|step| {
+                    walked += step;
+                    walked >= length / 2.0
+                })
+                .unwrap_or(0);
+            labels.push((path[middle + 1], text.clone(), (w as i32 / 2 + pad, h as i32 / 2 + pad)));
+        }
+    }
+    chart.draw_series(labels.into_iter().map(|(at, text, (hw, hh))| {
+        EmptyElement::at(at)
+            + Rectangle::new([(-hw, -hh), (hw, hh)], theme.background.filled())
+            + Text::new(text, (0, 0), font.clone())
+    }))?;
+    Ok(())
+}
+
+/// Fills `bands` on `chart` with the colormap's color at the middle of each band.
+pub fn draw_filled_contours<DB>(
+    chart: &mut Chart2d<'_, DB>,
+    bands: &[ContourBand],
+    norm: &ColorNorm,
+    style: &ContourStyle,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let map = style.map();
+    for band in bands {
+        let Some(t) = norm.apply((band.lo + band.hi) / 2.0) else { continue };
+        let color = map.at(t);
+        chart.draw_series(band.polygons.iter().map(|p| Polygon::new(p.clone(), color.filled())))?;
+        // Touching polygons can rasterize with hairline gaps between them; an
+        // outline in the same color closes them.
+        chart.draw_series(band.polygons.iter().map(|p| {
+            let mut outline = p.clone();
+            outline.push(p[0]);
+            PathElement::new(outline, color.stroke_width(pt(0.5)))
+        }))?;
+    }
+    Ok(())
+}
+
+/// Draws the contours of `grid` at `levels` on axes spanning the grid, as lines
+/// or, with a filled style, as colored bands with a colorbar. Returns the chart
+/// for drawing lines or markers on top.
+pub fn draw_contour_plot<'a, DB>(
+    root: &'a DrawingArea<DB, Shift>,
+    title: &str,
+    x_label: &str,
+    y_label: &str,
+    grid: &Grid,
+    levels: &[f64],
+    style: &ContourStyle,
+) -> Result<Chart2d<'a, DB>, Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let (Some(&x0), Some(&x1), Some(&y0), Some(&y1)) = (grid.x.first(), grid.x.last(), grid.y.first(), grid.y.last()) else {
+        return Err("Cannot draw an empty grid".into());
+    };
+    let Some((min, max)) = grid.z_range() else {
+        return Err("Grid has no finite values to contour".into());
+    };
+    let norm = ColorNorm::from_values([min, max], ColorScale::Linear, (None, None))?;
+    let colorbar = style.filled && style.colorbar;
+
+    let theme = theme();
+    let margin = if colorbar { theme.margin + colorbar_width() } else { theme.margin };
+    let mut chart = theme
+        .chart(root)
+        .caption(title, theme.title_font())
+        .margin_right(pt(margin))
+        .build_cartesian_2d(x0..x1, y0..y1)?;
+    let mut mesh = chart.configure_mesh();
+    theme.mesh(&mut mesh).x_desc(x_label).y_desc(y_label);
+    if style.filled {
+        mesh.disable_mesh();
+    }
+    mesh.draw()?;
+
+    if style.filled {
+        draw_filled_contours(&mut chart, &contour_bands(grid, levels), &norm, style)?;
+    }
+    if style.lines {
+        draw_contour_lines(&mut chart, &contour_lines(grid, levels), &norm, style)?;
+    }
+    if colorbar {
+        let (_, rows) = chart.plotting_area().get_pixel_range();
+        let edges = band_edges(levels, min, max);
+        draw_colorbar(root, rows, &norm, style.map(), &style.label, Some(&edges))?;
+    }
+    Ok(chart)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::histogram::linspace;
+
+    fn close(a: (f64, f64), b: (f64, f64)) -> bool {
+        (a.0 - b.0).abs() < 1e-12 && (a.1 - b.1).abs() < 1e-12
+    }
+
+    fn area(polygon: &[(f64, f64)]) -> f64 {
+        let n = polygon.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum::<f64>()
+            .abs()
+            / 2.0
+    }
+
+    #[test]
+    fn single_peak_gives_a_closed_ring() {
+        let grid = Grid::from_fn(vec![0.0, 1.0, 2.0], vec![0.0, 1.0, 2.0], |x, y| {
+            if x == 1.0 && y == 1.0 {
+                1.0
+            } else {
+                0.0
+            }
+        });
+        let lines = contour_lines(&grid, &[0.5]);
+        let [path] = lines[0].paths.as_slice() else {
+            panic!("expected one path, got {:?}", lines[0].paths);
+        };
+        assert_eq!(path.len(), 5);
+        assert!(close(path[0], path[4]), "ring is not closed: {:?}", path);
+        for p in [(0.5, 1.0), (1.0, 0.5), (1.5, 1.0), (1.0, 1.5)] {
+            assert!(path.iter().any(|&q| close(p, q)), "{:?} missing from {:?}", p, path);
+        }
+    }
+
+    #[test]
+    fn ramp_gives_one_open_line() {
+        let grid = Grid::from_fn(linspace(0.0, 3.0, 4), linspace(0.0, 2.0, 3), |x, _| x);
+        let lines = contour_lines(&grid, &[1.5]);
+        let [path] = lines[0].paths.as_slice() else {
+            panic!("expected one path, got {:?}", lines[0].paths);
+        };
+        assert_eq!(path.len(), 3);
+        assert!(path.iter().all(|p| (p.0 - 1.5).abs() < 1e-12));
+        let mut ends = [path[0].1, path[2].1];
+        ends.sort_by(f64::total_cmp);
+        assert_eq!(ends, [0.0, 2.0]);
+    }
+
+    #[test]
+    fn saddle_cuts_off_the_corners_unlike_the_center() {
+        // Corners (0, 0) and (1, 1) are high, the others low; the center is 0.5
+        let grid = Grid::new(vec![0.0, 1.0], vec![0.0, 1.0], vec![vec![1.0, 0.0], vec![0.0, 1.0]]).unwrap();
+        let cut = |level: f64, corner: (f64, f64)| {
+            let lines = contour_lines(&grid, &[level]);
+            lines[0].paths.iter().any(|p| {
+                p.len() == 2 && p.iter().all(|q| (q.0 - corner.0).abs() + (q.1 - corner.1).abs() < 0.5 + 1e-9)
+            })
+        };
+        // At 0.4 the center is above, so the low corners are cut off
+        assert_eq!(contour_lines(&grid, &[0.4])[0].paths.len(), 2);
+        assert!(cut(0.4, (1.0, 0.0)) && cut(0.4, (0.0, 1.0)));
+        // At 0.6 the center is below, so the high corners are
+        assert!(cut(0.6, (0.0, 0.0)) && cut(0.6, (1.0, 1.0)));
+    }
+
+    #[test]
+    fn bands_tile_the_grid() {
+        let grid = Grid::from_fn(linspace(-3.0, 3.0, 13), linspace(-2.0, 2.0, 9), |x, y| x.sin() * y.cos() + 0.1 * x);
+        let levels = auto_levels(&grid, 8);
+        let bands = contour_bands(&grid, &levels);
+        assert_eq!(bands.len(), levels.len() + 1);
+        let total = bands.iter().flat_map(|b| &b.polygons).map(|p| area(p)).sum::<f64>();
+        assert!((total - 6.0 * 4.0).abs() < 1e-9, "bands cover {} instead of 24", total);
+    }
+
+    #[test]
+    fn auto_levels_are_round_and_inside_the_range() {
+        let grid = Grid::from_fn(linspace(0.0, 1.0, 11), vec![0.0, 1.0], |x, _| x);
+        assert_eq!(auto_levels(&grid, 5), vec![0.2, 0.4, 0.6, 0.8]);
+        let flat = Grid::from_fn(vec![0.0, 1.0], vec![0.0, 1.0], |_, _| 3.0);
+        assert!(auto_levels(&flat, 5).is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+// Loading numeric columns from CSV files or stdin for the command-line tools.
+
+use crate::time::parse_timestamp;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::str::FromStr;
+
+/// A CSV column, selected by header name or by 0-based index.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Column {
+    Name(String),
+    Index(usize),
+}
+
+impl FromStr for Column {
+    type Err = Box<dyn Error>;
+
+    /// All-digit strings are indices; anything else is a header name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("Empty column name".into());
+        }
+        Ok(match s.parse() {
+            Ok(i) => Column::Index(i),
+            Err(_) => Column::Name(s.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Column::Name(name) => write!(f, "{}", name),
+            Column::Index(i) => write!(f, "#{}", i),
+        }
+    }
+}
+
+/// Opens `path` for reading, with `-` meaning stdin.
+pub fn open_input(path: &str) -> Result<Box<dyn Read>, Box<dyn Error>> {
+    if path == "-" {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        let file = File::open(path).map_err(|e| format!("Cannot open {}: {}", path, e))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// A CSV file held in memory as strings.
+#[derive(Clone, Debug, Default)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Reads CSV from `reader`. Without a header row, columns can only be
+    /// selected by index and are named `column 0`, `column 1`, ...
+    pub fn read<R: Read>(reader: R, has_headers: bool) -> Result<Self, Box<dyn Error>> {
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(has_headers)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+
+        let mut rows = Vec::new();
+        for rec in rdr.records() {
+            rows.push(rec?.iter().map(str::to_string).collect::<Vec<_>>());
+        }
+        let headers = if has_headers {
+            rdr.headers()?.iter().map(str::to_string).collect()
+        } else {
+            let n = rows.iter().map(Vec::len).max().unwrap_or(0);
+            (0..n).map(|i| format!("column {}", i)).collect()
+        };
+        Ok(Self { headers, rows })
+    }
+
+    /// Reads a CSV file, or stdin when `path` is `-`.
+    pub fn from_path(path: &str, has_headers: bool) -> Result<Self, Box<dyn Error>> {
+        Self::read(open_input(path)?, has_headers)
+    }
+
+    pub fn index_of(&self, column: &Column) -> Result<usize, Box<dyn Error>> {
+        match column {
+            Column::Index(i) if *i < self.headers.len() => Ok(*i),
+            Column::Index(i) => Err(format!("Column index {} out of range ({} columns)", i, self.headers.len()).into()),
+            Column::Name(name) => self.headers.iter().position(|h| h == name).ok_or_else(|| {
+                format!("Missing column: {} (available: {})", name, self.headers.join(", ")).into()
+            }),
+        }
+    }
+
+    /// Header of `column`.
+    pub fn name_of(&self, column: &Column) -> Result<&str, Box<dyn Error>> {
+        Ok(&self.headers[self.index_of(column)?])
+    }
+
+    /// Every row's value of `column` parsed with `parse`; empty, `NA` and
+    /// `NaN` cells are `None`.
+    pub fn column_with<F>(&self, column: &Column, parse: F) -> Result<Vec<Option<f64>>, Box<dyn Error>>
+    where
+        F: Fn(&str) -> Result<f64, Box<dyn Error>>,
+    {
+        let idx = self.index_of(column)?;
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(row, rec)| {
+                let cell = rec.get(idx).map(String::as_str).unwrap_or("");
+                if is_missing(cell) {
+                    return Ok(None);
+                }
+                parse(cell)
+                    .map(Some)
+                    .map_err(|e| format!("Row {}, column {}: cannot parse '{}': {}", row + 1, column, cell, e).into())
+            })
+            .collect()
+    }
+
+    /// Numeric column with missing cells as `None`.
+    pub fn column(&self, column: &Column) -> Result<Vec<Option<f64>>, Box<dyn Error>> {
+        self.column_with(column, |s| Ok(s.parse::<f64>()?))
+    }
+
+    /// Column of timestamps as Unix seconds (see [`parse_timestamp`]).
+    pub fn timestamps(&self, column: &Column) -> Result<Vec<Option<f64>>, Box<dyn Error>> {
+        self.column_with(column, parse_timestamp)
+    }
+
+    /// Whether `column` holds timestamps rather than plain numbers.
+    pub fn has_timestamps(&self, column: &Column) -> bool {
+        self.column(column).is_err() && self.timestamps(column).is_ok()
+    }
+
+    /// Numeric column with missing and non-finite cells dropped.
+    pub fn numeric(&self, column: &Column) -> Result<Vec<f64>, Box<dyn Error>> {
+        Ok(self.column(column)?.into_iter().flatten().filter(|v| v.is_finite()).collect())
+    }
+}
+
+fn is_missing(cell: &str) -> bool {
+    cell.is_empty() || cell.eq_ignore_ascii_case("na") || cell.eq_ignore_ascii_case("nan")
+}
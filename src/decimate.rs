@@ -0,0 +1,172 @@
+// Pixel-aware decimation for very long line series.
+//
+// Both methods assume the input is ordered by x (as time series are) and keep
+// the first and last points so the decimated line spans the same x range.
+
+/// Decimation method used when a series has far more points than pixels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Decimation {
+    /// Largest-Triangle-Three-Buckets (Steinarsson, 2013).
+    Lttb,
+    /// First, min, max and last point of every pixel column (M4). Exact for line rendering.
+    #[default]
+    MinMax,
+}
+
+/// Series longer than `AUTO_FACTOR` points per pixel column are decimated automatically.
+pub const AUTO_FACTOR: usize = 4;
+
+/// Downsamples `points` to `threshold` points with LTTB.
+pub fn lttb(points: &[(f64, f64)], threshold: usize) -> Vec<(f64, f64)> {
+    let n = points.len();
+    if threshold >= n || threshold < 3 {
+        return points.to_vec();
+    }
+
+    let mut out = Vec::with_capacity(threshold);
+    out.push(points[0]);
+
+    // Buckets cover the points between the fixed first and last ones
+    let every = (n - 2) as f64 / (threshold - 2) as f64;
+    let mut a = 0usize;
+
+    for i in 0..(threshold - 2) {
+        // Average of the next bucket is the third triangle vertex
+        let next_lo = ((i + 1) as f64 * every).floor() as usize + 1;
+        let next_hi = (((i + 2) as f64 * every).floor() as usize + 1).min(n);
+        let (avg_x, avg_y) = if next_lo < next_hi {
+            let len = (next_hi - next_lo) as f64;
+            let (sx, sy) = points[next_lo..next_hi]
+                .iter()
+                .fold((0.0, 0.0), |(sx, sy), &(x, y)| (sx + x, sy + y));
+            (sx / len, sy / len)
+        } else {
+            points[n - 1]
+        };
+
+        let lo = (i as f64 * every).floor() as usize + 1;
+        let hi = next_lo.min(n - 1);
+        let (ax, ay) = points[a];
+
+        let mut best = lo;
+        let mut best_area = -1.0;
+        for (j, &(x, y)) in points.iter().enumerate().take(hi).skip(lo) {
+            let area = ((ax - avg_x) * (y - ay) - (ax - x) * (avg_y - ay)).abs();
+            if area > best_area {
+                best_area = area;
+                best = j;
+            }
+        }
+        out.push(points[best]);
+        a = best;
+    }
+
+    out.push(points[n - 1]);
+    out
+}
+
+/// Keeps the first, min, max and last point of every run of points that fall
+/// into the same pixel column, in their original order.
+pub fn min_max_by_column<F>(points: &[(f64, f64)], column: F) -> Vec<(f64, f64)>
+where
+    F: Fn(&(f64, f64)) -> i32,
+{
+    let mut out = Vec::new();
+    let mut start = 0usize;
+
+    while start < points.len() {
+        let col = column(&points[start]);
+        let mut end = start + 1;
+        while end < points.len() && column(&points[end]) == col {
+            end += 1;
+        }
+
+        let bucket = &points[start..end];
+        let (mut imin, mut imax) = (0usize, 0usize);
+        for (i, p) in bucket.iter().enumerate() {
+            if p.1 < bucket[imin].1 {
+                imin = i;
+            }
+            if p.1 > bucket[imax].1 {
+                imax = i;
+            }
+        }
+
+        let mut keep = vec![0, imin, imax, bucket.len() - 1];
+        keep.sort_unstable();
+        keep.dedup();
+        out.extend(keep.into_iter().map(|i| bucket[i]));
+
+        start = end;
+    }
+    out
+}
+
+/// Min/max decimation for a linear x axis spanning `x_range` over `width` pixel columns.
+pub fn min_max(points: &[(f64, f64)], x_range: (f64, f64), width: u32) -> Vec<(f64, f64)> {
+    let (x0, x1) = x_range;
+    let span = (x1 - x0).abs().max(1e-300);
+    let scale = width.max(1) as f64 / span;
+    min_max_by_column(points, |p| ((p.0 - x0) * scale).floor() as i32)
+}
+
+/// True when `n` points are far more than `width` pixel columns can show.
+pub fn needs_decimation(n: usize, width: u32) -> bool {
+    n > AUTO_FACTOR * (width.max(1) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A jagged series ordered by x
+    fn series(n: usize) -> Vec<(f64, f64)> {
+        (0..n).map(|i| (i as f64, (i as f64 * 0.37).sin() + ((i * 7919) % 13) as f64 / 13.0)).collect()
+    }
+
+    #[test]
+    fn lttb_respects_threshold_and_keeps_ends() {
+        let points = series(10_000);
+        for threshold in [3, 10, 500, 9_999] {
+            let out = lttb(&points, threshold);
+            assert!(out.len() <= threshold, "{} points for threshold {}", out.len(), threshold);
+            assert_eq!(out[0], points[0]);
+            assert_eq!(out[out.len() - 1], points[points.len() - 1]);
+            assert!(out.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+
+    #[test]
+    fn lttb_returns_short_series_unchanged() {
+        let points = series(50);
+        assert_eq!(lttb(&points, 100), points);
+        assert_eq!(lttb(&points, 2), points);
+    }
+
+    #[test]
+    fn min_max_keeps_ends_and_column_extremes() {
+        let points = series(10_000);
+        let width = 100;
+        let out = min_max(&points, (0.0, 9_999.0), width);
+        assert!(out.len() <= 4 * (width as usize + 1));
+        assert_eq!(out[0], points[0]);
+        assert_eq!(out[out.len() - 1], points[points.len() - 1]);
+
+        let column = |p: &(f64, f64)| (p.0 * width as f64 / 9_999.0).floor() as i32;
+        for col in 0..=width as i32 {
+            let extremes = |ps: &[(f64, f64)]| {
+                let ys = ps.iter().filter(|p| column(p) == col).map(|p| p.1).collect::<Vec<_>>();
+                let min = ys.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = ys.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                (min, max)
+            };
+            assert_eq!(extremes(&out), extremes(&points), "column {}", col);
+        }
+    }
+
+    #[test]
+    fn needs_decimation_above_auto_factor() {
+        assert!(!needs_decimation(AUTO_FACTOR * 100, 100));
+        assert!(needs_decimation(AUTO_FACTOR * 100 + 1, 100));
+    }
+}
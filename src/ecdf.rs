@@ -0,0 +1,272 @@
+// Distribution plots without binning: empirical CDFs and Q-Q / P-P plots.
+
+use crate::line::{draw_line_with, draw_mesh_f64};
+use crate::markers::{draw_marker_series, MarkerShape};
+use crate::stats::{quantile, sorted};
+use crate::theme::theme;
+use crate::units::pt;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::error::Error;
+
+/// Step points of the empirical CDF of `data`, from 0 before the smallest value to 1 after the largest.
+pub fn ecdf(data: &[f64]) -> Vec<(f64, f64)> {
+    let v = sorted(data);
+    let n = v.len() as f64;
+    let mut pts = Vec::with_capacity(2 * v.len());
+    for (i, &x) in v.iter().enumerate() {
+        pts.push((x, i as f64 / n));
+        pts.push((x, (i + 1) as f64 / n));
+    }
+    pts
+}
+
+/// Half-width of the Dvoretzky–Kiefer–Wolfowitz band: the true CDF lies within
+/// ±ε of the ECDF of `n` samples everywhere with probability 1 − `alpha`.
+pub fn dkw_epsilon(n: usize, alpha: f64) -> f64 {
+    ((2.0 / alpha).ln() / (2.0 * n.max(1) as f64)).sqrt()
+}
+
+/// A labelled sample for [`draw_ecdfs`].
+#[derive(Clone, Debug)]
+pub struct EcdfSeries {
+    pub label: String,
+    pub data: Vec<f64>,
+    pub style: ShapeStyle,
+}
+
+impl EcdfSeries {
+    pub fn new<S: Into<ShapeStyle>>(label: &str, data: Vec<f64>, style: S) -> Self {
+        Self {
+            label: label.to_string(),
+            data,
+            style: style.into(),
+        }
+    }
+}
+
+/// Draws the ECDF of every series as a step line. With `dkw_alpha = Some(a)`
+/// each line gets a shaded (1 − a) DKW confidence band.
+pub fn draw_ecdfs<DB>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    x_label: &str,
+    series: &[EcdfSeries],
+    dkw_alpha: Option<f64>,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let steps = series.iter().map(|s| ecdf(&s.data)).collect::<Vec<_>>();
+    let (mut xmin, mut xmax) = steps
+        .iter()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+    if xmin.partial_cmp(&xmax) != Some(std::cmp::Ordering::Less) {
+        // Empty or constant data
+        let c = if xmin.is_finite() { xmin } else { 0.0 };
+        (xmin, xmax) = (c - 1.0, c + 1.0);
+    }
+    let pad = (xmax - xmin) * 0.03;
+    let (xmin, xmax) = (xmin - pad, xmax + pad);
+
+    let theme = theme();
+    let mut chart = theme
+        .chart(root)
+        .caption(title, theme.title_font())
+        .build_cartesian_2d(xmin..xmax, 0.0f64..1.05)?;
+    draw_mesh_f64(&mut chart, x_label, "Cumulative probability")?;
+
+    for (s, pts) in series.iter().zip(&steps) {
+        // Extend the steps to the plot edges
+        let mut line = Vec::with_capacity(pts.len() + 2);
+        line.push((xmin, 0.0));
+        line.extend(pts.iter().copied());
+        line.push((xmax, 1.0));
+
+        if let Some(alpha) = dkw_alpha {
+            let eps = dkw_epsilon(s.data.len(), alpha);
+            let mut band = line.iter().map(|&(x, y)| (x, (y + eps).min(1.0))).collect::<Vec<_>>();
+            band.extend(line.iter().rev().map(|&(x, y)| (x, (y - eps).max(0.0))));
+            chart.draw_series(std::iter::once(Polygon::new(band, s.style.color.mix(0.18).filled())))?;
+        }
+
+        let style = s.style.stroke_width(s.style.stroke_width.max(pt(0.5)));
+        let label = match dkw_alpha {
+            Some(alpha) => format!("{} ({:.0}% DKW band)", s.label, 100.0 * (1.0 - alpha)),
+            None => s.label.clone(),
+        };
+        let len = pt(7.0) as i32;
+        draw_line_with(&mut chart, &line, style, None)?
+            .label(label)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + len, y)], style));
+    }
+
+    theme
+        .legend(&mut chart.configure_series_labels())
+        .position(SeriesLabelPosition::LowerRight)
+        .draw()?;
+    Ok(())
+}
+
+// Plotting positions (i − 0.5) / n
+fn plotting_positions(n: usize) -> impl Iterator<Item = f64> {
+    (0..n).map(move |i| (i as f64 + 0.5) / n as f64)
+}
+
+/// Q-Q points `(theoretical, sample)` of `data` against a quantile function `ppf`.
+pub fn qq_points<F>(data: &[f64], ppf: F) -> Vec<(f64, f64)>
+where
+    F: Fn(f64) -> f64,
+{
+    let v = sorted(data);
+    plotting_positions(v.len()).zip(&v).map(|(p, &x)| (ppf(p), x)).collect()
+}
+
+/// Q-Q points `(quantile of a, quantile of b)` of two samples, at as many
+/// probabilities as the smaller sample has values.
+pub fn qq_points_two_sample(a: &[f64], b: &[f64]) -> Vec<(f64, f64)> {
+    let (a, b) = (sorted(a), sorted(b));
+    plotting_positions(a.len().min(b.len()))
+        .map(|p| (quantile(&a, p), quantile(&b, p)))
+        .collect()
+}
+
+/// P-P points `(theoretical CDF, empirical CDF)` of `data` against `cdf`.
+pub fn pp_points<F>(data: &[f64], cdf: F) -> Vec<(f64, f64)>
+where
+    F: Fn(f64) -> f64,
+{
+    let v = sorted(data);
+    plotting_positions(v.len()).zip(&v).map(|(p, &x)| (cdf(x), p)).collect()
+}
+
+/// P-P points `(ECDF of a, ECDF of b)` evaluated at every value of the pooled samples.
+pub fn pp_points_two_sample(a: &[f64], b: &[f64]) -> Vec<(f64, f64)> {
+    let (a, b) = (sorted(a), sorted(b));
+    let mut pooled = a.iter().chain(&b).copied().collect::<Vec<_>>();
+    pooled = sorted(&pooled);
+    pooled.dedup();
+    let frac = |v: &[f64], x: f64| v.partition_point(|&y| y <= x) as f64 / v.len().max(1) as f64;
+    pooled.iter().map(|&x| (frac(&a, x), frac(&b, x))).collect()
+}
+
+/// Kind of probability plot, which decides the reference line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProbPlot {
+    /// Quantiles vs quantiles; reference line through the first and third quartiles.
+    QQ,
+    /// Probabilities vs probabilities on [0, 1]; reference line y = x.
+    PP,
+}
+
+/// Scatter of Q-Q or P-P `points` with the matching reference line.
+pub fn draw_prob_plot<DB>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    x_label: &str,
+    y_label: &str,
+    points: &[(f64, f64)],
+    kind: ProbPlot,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let finite = points
+        .iter()
+        .copied()
+        .filter(|p| p.0.is_finite() && p.1.is_finite())
+        .collect::<Vec<_>>();
+
+    let (xr, yr) = match kind {
+        ProbPlot::PP => ((0.0, 1.0), (0.0, 1.0)),
+        ProbPlot::QQ => {
+            let span = |f: fn(&(f64, f64)) -> f64| {
+                let (lo, hi) = finite
+                    .iter()
+                    .map(f)
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+                let pad = ((hi - lo) * 0.05).max(1e-9);
+                (lo - pad, hi + pad)
+            };
+            (span(|p| p.0), span(|p| p.1))
+        }
+    };
+
+    let theme = theme();
+    let mut chart = theme
+        .chart(root)
+        .caption(title, theme.title_font())
+        .build_cartesian_2d(xr.0..xr.1, yr.0..yr.1)?;
+    draw_mesh_f64(&mut chart, x_label, y_label)?;
+
+    let reference = match kind {
+        ProbPlot::PP => vec![(0.0, 0.0), (1.0, 1.0)],
+        ProbPlot::QQ => {
+            let xs = sorted(&finite.iter().map(|p| p.0).collect::<Vec<_>>());
+            let ys = sorted(&finite.iter().map(|p| p.1).collect::<Vec<_>>());
+            let (x1, x3) = (quantile(&xs, 0.25), quantile(&xs, 0.75));
+            let (y1, y3) = (quantile(&ys, 0.25), quantile(&ys, 0.75));
+            let slope = if x3 > x1 { (y3 - y1) / (x3 - x1) } else { 1.0 };
+            // Clip to the plotted y range so the line is not bent at the border
+            let at = |x: f64| (x, y1 + slope * (x - x1));
+            let x_at = |y: f64| x1 + (y - y1) / slope;
+            let (mut a, mut b) = (at(xr.0), at(xr.1));
+            if slope > 0.0 {
+                if a.1 < yr.0 {
+                    a = (x_at(yr.0), yr.0);
+                }
+                if b.1 > yr.1 {
+                    b = (x_at(yr.1), yr.1);
+                }
+            }
+            vec![a, b]
+        }
+    };
+    chart.draw_series(std::iter::once(PathElement::new(reference, theme.color(1).stroke_width(theme.stroke()))))?;
+
+    draw_marker_series(&mut chart, MarkerShape::Circle, &finite, pt(1.0) as i32, theme.color(0).mix(0.6).filled())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ecdf_steps_up_by_one_over_n_at_each_sorted_value() {
+        let pts = ecdf(&[3.0, 1.0, 2.0, 2.0]);
+        assert_eq!(
+            pts,
+            vec![
+                (1.0, 0.0),
+                (1.0, 0.25),
+                (2.0, 0.25),
+                (2.0, 0.5),
+                (2.0, 0.5),
+                (2.0, 0.75),
+                (3.0, 0.75),
+                (3.0, 1.0),
+            ]
+        );
+        assert!(ecdf(&[]).is_empty());
+    }
+
+    #[test]
+    fn dkw_epsilon_matches_formula() {
+        // ε = sqrt(ln(2/α) / (2n))
+        assert!((dkw_epsilon(100, 0.05) - 0.135_810_2).abs() < 1e-7);
+        assert!((dkw_epsilon(10, 0.1) - 0.387_022_8).abs() < 1e-7);
+        assert!(dkw_epsilon(400, 0.05) < dkw_epsilon(100, 0.05));
+    }
+
+    #[test]
+    fn pp_points_of_two_identical_samples_lie_on_the_diagonal() {
+        let a = [0.3, 1.2, -0.4, 2.2];
+        assert!(pp_points_two_sample(&a, &a).iter().all(|(x, y)| x == y));
+        let pp = pp_points(&a, |x| x);
+        assert_eq!(pp.iter().map(|p| p.1).collect::<Vec<_>>(), vec![0.125, 0.375, 0.625, 0.875]);
+    }
+}
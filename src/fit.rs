@@ -0,0 +1,512 @@
+// Maximum-likelihood fits of common distributions, with standard errors from
+// the Fisher information, and a helper to overlay fitted PDFs on a histogram.
+
+use crate::gof::draw_text_box;
+use crate::histogram::{counts_from_edges, draw_hist_bins, normalized_heights, HistStyle, Normalization};
+use crate::stats::{digamma, gamma_p, gamma_q, ln_gamma, mean, normal_cdf, normal_quantile, std_dev, trigamma};
+use crate::theme::theme;
+use crate::units::pt;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use rand::Rng;
+use rand_distr::{Distribution, Exp, Gamma, LogNormal, Normal, Poisson, Weibull};
+use std::error::Error;
+use std::str::FromStr;
+
+const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+
+/// Distribution families that can be fitted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Family {
+    /// Parameters `mu`, `sigma`.
+    Normal,
+    /// Parameters `mu`, `sigma` of ln(x).
+    LogNormal,
+    /// Parameter `lambda` (rate).
+    Exponential,
+    /// Parameters `shape`, `scale`.
+    Gamma,
+    /// Parameters `shape`, `scale`.
+    Weibull,
+    /// Parameter `lambda` (mean); data must be non-negative integers.
+    Poisson,
+}
+
+impl Family {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Family::Normal => "Normal",
+            Family::LogNormal => "Lognormal",
+            Family::Exponential => "Exponential",
+            Family::Gamma => "Gamma",
+            Family::Weibull => "Weibull",
+            Family::Poisson => "Poisson",
+        }
+    }
+
+    pub fn is_discrete(&self) -> bool {
+        matches!(self, Family::Poisson)
+    }
+}
+
+impl FromStr for Family {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "normal" | "gaussian" => Ok(Family::Normal),
+            "lognormal" | "log-normal" => Ok(Family::LogNormal),
+            "exponential" | "exp" => Ok(Family::Exponential),
+            "gamma" => Ok(Family::Gamma),
+            "weibull" => Ok(Family::Weibull),
+            "poisson" => Ok(Family::Poisson),
+            other => Err(format!(
+                "Unknown distribution '{}' (normal, lognormal, exponential, gamma, weibull, poisson)",
+                other
+            )
+            .into()),
+        }
+    }
+}
+
+/// A fitted parameter and its standard error.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Param {
+    pub name: &'static str,
+    pub value: f64,
+    pub std_err: f64,
+}
+
+/// Result of a maximum-likelihood fit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fit {
+    pub family: Family,
+    pub params: Vec<Param>,
+    pub log_likelihood: f64,
+    pub n: usize,
+}
+
+impl Fit {
+    /// Value of the named parameter (`NaN` if the family has no such parameter).
+    pub fn param(&self, name: &str) -> f64 {
+        self.params.iter().find(|p| p.name == name).map_or(f64::NAN, |p| p.value)
+    }
+
+    fn p(&self, i: usize) -> f64 {
+        self.params[i].value
+    }
+
+    /// Density at `x` (the probability mass at `x` for Poisson).
+    pub fn pdf(&self, x: f64) -> f64 {
+        match self.family {
+            Family::Normal => normal_pdf(x, self.p(0), self.p(1)),
+            Family::LogNormal if x > 0.0 => normal_pdf(x.ln(), self.p(0), self.p(1)) / x,
+            Family::Exponential if x >= 0.0 => self.p(0) * (-self.p(0) * x).exp(),
+            Family::Gamma if x > 0.0 => {
+                let (k, theta) = (self.p(0), self.p(1));
+                ((k - 1.0) * x.ln() - x / theta - ln_gamma(k) - k * theta.ln()).exp()
+            }
+            Family::Weibull if x >= 0.0 => {
+                let (k, lambda) = (self.p(0), self.p(1));
+                let z = x / lambda;
+                k / lambda * z.powf(k - 1.0) * (-z.powf(k)).exp()
+            }
+            Family::Poisson if x >= 0.0 && x.fract() == 0.0 => {
+                let lambda = self.p(0);
+                (x * lambda.ln() - lambda - ln_gamma(x + 1.0)).exp()
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Cumulative distribution function, e.g. for the tests in [`crate::gof`].
+    pub fn cdf(&self, x: f64) -> f64 {
+        match self.family {
+            Family::Normal => normal_cdf(x, self.p(0), self.p(1)),
+            Family::LogNormal if x > 0.0 => normal_cdf(x.ln(), self.p(0), self.p(1)),
+            Family::Exponential if x > 0.0 => 1.0 - (-self.p(0) * x).exp(),
+            Family::Gamma if x > 0.0 => gamma_p(self.p(0), x / self.p(1)),
+            Family::Weibull if x > 0.0 => 1.0 - (-(x / self.p(1)).powf(self.p(0))).exp(),
+            Family::Poisson if x >= 0.0 => gamma_q(x.floor() + 1.0, self.p(0)),
+            _ => 0.0,
+        }
+    }
+
+    /// Quantile function (inverse CDF), found by bisection on [`Fit::cdf`].
+    pub fn quantile(&self, p: f64) -> f64 {
+        if !(0.0..=1.0).contains(&p) {
+            return f64::NAN;
+        }
+        if self.family == Family::Normal {
+            return normal_quantile(p, self.p(0), self.p(1));
+        }
+        // The other families live on [0, ∞)
+        let (mut lo, mut hi) = (0.0, 1.0);
+        while self.cdf(hi) < p && hi < 1e300 {
+            hi *= 2.0;
+        }
+        for _ in 0..200 {
+            let mid = 0.5 * (lo + hi);
+            if self.cdf(mid) < p {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+            if hi - lo <= 1e-12 * hi.abs().max(1.0) {
+                break;
+            }
+        }
+        hi
+    }
+
+    /// Draws `n` samples from the fitted distribution with the `rand_distr` family.
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R, n: usize) -> Result<Vec<f64>, Box<dyn Error>> {
+        fn draw<D: Distribution<f64>, R: Rng + ?Sized>(d: D, rng: &mut R, n: usize) -> Vec<f64> {
+            d.sample_iter(rng).take(n).collect()
+        }
+        Ok(match self.family {
+            Family::Normal => draw(Normal::new(self.p(0), self.p(1))?, rng, n),
+            Family::LogNormal => draw(LogNormal::new(self.p(0), self.p(1))?, rng, n),
+            Family::Exponential => draw(Exp::new(self.p(0))?, rng, n),
+            Family::Gamma => draw(Gamma::new(self.p(0), self.p(1))?, rng, n),
+            Family::Weibull => draw(Weibull::new(self.p(1), self.p(0))?, rng, n),
+            Family::Poisson => draw(Poisson::new(self.p(0))?, rng, n),
+        })
+    }
+
+    /// Lines such as `Gamma fit (n = 2000)` and `shape = 2.013 ± 0.060`.
+    pub fn summary_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("{} fit (n = {})", self.family.name(), self.n)];
+        lines.extend(
+            self.params
+                .iter()
+                .map(|p| format!("{} = {:.4} ± {:.4}", p.name, p.value, p.std_err)),
+        );
+        lines
+    }
+}
+
+fn normal_pdf(x: f64, mu: f64, sigma: f64) -> f64 {
+    let z = (x - mu) / sigma;
+    (-0.5 * z * z).exp() / (sigma * (2.0 * std::f64::consts::PI).sqrt())
+}
+
+/// Maximum-likelihood fit of `family` to `data`.
+///
+/// Standard errors are the square roots of the diagonal of the inverse
+/// expected Fisher information at the estimate. Data with no spread (all
+/// equal, or all zero for Poisson) have no finite estimate and are rejected.
+pub fn fit(family: Family, data: &[f64]) -> Result<Fit, Box<dyn Error>> {
+    let n = data.len();
+    if n < 2 {
+        return Err("At least two data points are needed for a fit".into());
+    }
+    // Constant data would give a zero or infinite spread parameter
+    if family != Family::Poisson && data.iter().all(|&v| v == data[0]) {
+        return Err(format!("{} fit needs data that are not all equal", family.name()).into());
+    }
+    let nf = n as f64;
+    let positive = || -> Result<(), Box<dyn Error>> {
+        if data.iter().all(|&v| v > 0.0) {
+            Ok(())
+        } else {
+            Err(format!("{} fit needs strictly positive data", family.name()).into())
+        }
+    };
+
+    let params = match family {
+        Family::Normal | Family::LogNormal => {
+            let values = if family == Family::LogNormal {
+                positive()?;
+                data.iter().map(|v| v.ln()).collect::<Vec<_>>()
+            } else {
+                data.to_vec()
+            };
+            let mu = mean(&values);
+            let sigma = (values.iter().map(|v| (v - mu) * (v - mu)).sum::<f64>() / nf).sqrt();
+            vec![
+                param("mu", mu, sigma / nf.sqrt()),
+                param("sigma", sigma, sigma / (2.0 * nf).sqrt()),
+            ]
+        }
+        Family::Exponential => {
+            if data.iter().any(|&v| v < 0.0) {
+                return Err("Exponential fit needs non-negative data".into());
+            }
+            let lambda = 1.0 / mean(data);
+            vec![param("lambda", lambda, lambda / nf.sqrt())]
+        }
+        Family::Gamma => {
+            positive()?;
+            let m = mean(data);
+            let s = m.ln() - data.iter().map(|v| v.ln()).sum::<f64>() / nf;
+            // s > 0 unless the values are equal to within rounding
+            if s.is_nan() || s <= 0.0 {
+                return Err("Gamma fit needs data with more spread".into());
+            }
+            // Minka's starting point, then Newton on ln k − ψ(k) = s
+            let mut k = (3.0 - s + ((s - 3.0).powi(2) + 24.0 * s).sqrt()) / (12.0 * s);
+            for _ in 0..100 {
+                let step = (k.ln() - digamma(k) - s) / (1.0 / k - trigamma(k));
+                k = (k - step).max(k / 10.0);
+                if step.abs() < 1e-12 * k {
+                    break;
+                }
+            }
+            let theta = m / k;
+            // Fisher information per observation: [[ψ'(k), 1/θ], [1/θ, k/θ²]]
+            let det = trigamma(k) * k / (theta * theta) - 1.0 / (theta * theta);
+            vec![
+                param("shape", k, (k / (theta * theta) / det / nf).sqrt()),
+                param("scale", theta, (trigamma(k) / det / nf).sqrt()),
+            ]
+        }
+        Family::Weibull => {
+            positive()?;
+            let logs = data.iter().map(|v| v.ln()).collect::<Vec<_>>();
+            let mean_log = logs.iter().sum::<f64>() / nf;
+            let mut k = 1.2 / std_dev(&logs).max(1e-12);
+            for _ in 0..100 {
+                let (mut s0, mut s1, mut s2) = (0.0, 0.0, 0.0);
+                for (&v, &l) in data.iter().zip(&logs) {
+                    let p = v.powf(k);
+                    s0 += p;
+                    s1 += p * l;
+                    s2 += p * l * l;
+                }
+                let g = s1 / s0 - 1.0 / k - mean_log;
+                let dg = (s2 * s0 - s1 * s1) / (s0 * s0) + 1.0 / (k * k);
+                let step = g / dg;
+                k = (k - step).max(k / 10.0);
+                if step.abs() < 1e-12 * k {
+                    break;
+                }
+            }
+            let lambda = (data.iter().map(|v| v.powf(k)).sum::<f64>() / nf).powf(1.0 / k);
+            // Fisher information per observation for (k, λ)
+            let a = (std::f64::consts::PI.powi(2) / 6.0 + (1.0 - EULER_GAMMA).powi(2)) / (k * k);
+            let b = -(1.0 - EULER_GAMMA) / lambda;
+            let c = (k / lambda).powi(2);
+            let det = a * c - b * b;
+            vec![
+                param("shape", k, (c / det / nf).sqrt()),
+                param("scale", lambda, (a / det / nf).sqrt()),
+            ]
+        }
+        Family::Poisson => {
+            if data.iter().any(|&v| v < 0.0 || v.fract() != 0.0) {
+                return Err("Poisson fit needs non-negative integer data".into());
+            }
+            let lambda = mean(data);
+            if lambda == 0.0 {
+                return Err("Poisson fit needs some non-zero data".into());
+            }
+            vec![param("lambda", lambda, (lambda / nf).sqrt())]
+        }
+    };
+
+    let mut fit = Fit {
+        family,
+        params,
+        log_likelihood: 0.0,
+        n,
+    };
+    fit.log_likelihood = data.iter().map(|&v| fit.pdf(v).max(1e-300).ln()).sum();
+    Ok(fit)
+}
+
+fn param(name: &'static str, value: f64, std_err: f64) -> Param {
+    Param { name, value, std_err }
+}
+
+/// Draws `data` as a PDF-normalized histogram on `edges` with the PDF of every
+/// fit overlaid (probability masses as stems for discrete families), a legend,
+/// and the fitted parameters of the first fit in a box.
+pub fn draw_fits<DB>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    x_label: &str,
+    data: &[f64],
+    edges: &[f64],
+    fits: &[Fit],
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let counts = counts_from_edges(data, edges);
+    let heights = normalized_heights(&counts, edges, Normalization::Pdf);
+    let (xmin, xmax) = (*edges.first().unwrap(), *edges.last().unwrap());
+
+    let npts = 600usize;
+    let curves = fits
+        .iter()
+        .map(|f| {
+            if f.family.is_discrete() {
+                (xmin.ceil() as i64..=xmax.floor() as i64)
+                    .map(|k| (k as f64, f.pdf(k as f64)))
+                    .collect::<Vec<_>>()
+            } else {
+                (0..=npts)
+                    .map(|i| {
+                        let x = xmin + (xmax - xmin) * i as f64 / npts as f64;
+                        (x, f.pdf(x))
+                    })
+                    .filter(|(_, y)| y.is_finite())
+                    .collect()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let ymax = heights
+        .iter()
+        .cloned()
+        .chain(curves.iter().flatten().map(|p| p.1))
+        .fold(1e-12f64, f64::max);
+
+    let theme = theme();
+    let mut chart = theme
+        .chart(root)
+        .caption(title, theme.title_font())
+        .build_cartesian_2d(xmin..xmax, 0.0f64..(ymax * 1.15))?;
+
+    theme
+        .mesh(&mut chart.configure_mesh())
+        .x_desc(x_label)
+        .y_desc("Probability density")
+        .draw()?;
+
+    draw_hist_bins(&mut chart, edges, &heights, &HistStyle::bars(theme.color(0).mix(0.35)))?;
+
+    for (i, (f, pts)) in fits.iter().zip(&curves).enumerate() {
+        // The first palette color is taken by the histogram
+        let color = theme.color(i + 1);
+        let style = color.stroke_width(theme.stroke());
+        let anno = if f.family.is_discrete() {
+            chart.draw_series(pts.iter().map(|&(x, y)| PathElement::new(vec![(x, 0.0), (x, y)], style)))?;
+            chart.draw_series(pts.iter().map(|&p| Circle::new(p, pt(1.5), color.filled())))?
+        } else {
+            chart.draw_series(LineSeries::new(pts.iter().copied(), style))?
+        };
+        let len = pt(7.0) as i32;
+        anno.label(format!("{} (lnL = {:.1})", f.family.name(), f.log_likelihood))
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + len, y)], style));
+    }
+
+    theme
+        .legend(&mut chart.configure_series_labels())
+        .position(SeriesLabelPosition::UpperRight)
+        .draw()?;
+
+    if let Some(first) = fits.first() {
+        draw_text_box(
+            &chart.plotting_area().strip_coord_spec(),
+            &first.summary_lines(),
+            SeriesLabelPosition::UpperLeft,
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn close(a: f64, b: f64, tol: f64) {
+        assert!((a - b).abs() <= tol, "{} differs from {} by more than {}", a, b, tol);
+    }
+
+    fn se(fit: &Fit, name: &str) -> f64 {
+        fit.params.iter().find(|p| p.name == name).map_or(f64::NAN, |p| p.std_err)
+    }
+
+    fn ones_to_ten() -> Vec<f64> {
+        (1..=10).map(f64::from).collect()
+    }
+
+    #[test]
+    fn normal_fit_matches_fitdistr() {
+        // MASS::fitdistr(1:10, "normal"): mean 5.5 (0.9082951), sd 2.8722813 (0.6422616),
+        // log-likelihood −n/2·(ln 2πσ² + 1)
+        let f = fit(Family::Normal, &ones_to_ten()).unwrap();
+        close(f.param("mu"), 5.5, 1e-12);
+        close(f.param("sigma"), 2.872_281_3, 1e-7);
+        close(se(&f, "mu"), 0.908_295_1, 1e-7);
+        close(se(&f, "sigma"), 0.642_261_6, 1e-7);
+        close(f.log_likelihood, -24.740_451_3, 1e-6);
+    }
+
+    #[test]
+    fn exponential_and_poisson_fits_match_fitdistr() {
+        // fitdistr(1:10, "exponential"): rate 0.18181818 (0.05749596)
+        let f = fit(Family::Exponential, &ones_to_ten()).unwrap();
+        close(f.param("lambda"), 0.181_818_18, 1e-8);
+        close(se(&f, "lambda"), 0.057_495_96, 1e-8);
+
+        // fitdistr(c(0, 1, 1, 2, 3, 5), "poisson"): lambda 2 (0.5773503)
+        let f = fit(Family::Poisson, &[0.0, 1.0, 1.0, 2.0, 3.0, 5.0]).unwrap();
+        close(f.param("lambda"), 2.0, 1e-12);
+        close(se(&f, "lambda"), 0.577_350_3, 1e-7);
+    }
+
+    #[test]
+    fn gamma_fit_solves_likelihood_equation() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let data = Gamma::new(2.5, 1.5).unwrap().sample_iter(&mut rng).take(5_000).collect::<Vec<f64>>();
+        let f = fit(Family::Gamma, &data).unwrap();
+        let (k, theta) = (f.param("shape"), f.param("scale"));
+
+        // ln k − ψ(k) = ln x̄ − mean(ln x), and kθ = x̄
+        let s = mean(&data).ln() - data.iter().map(|v| v.ln()).sum::<f64>() / data.len() as f64;
+        close(k.ln() - digamma(k), s, 1e-10);
+        close(k * theta, mean(&data), 1e-9);
+        assert!((k - 2.5).abs() < 4.0 * se(&f, "shape"));
+        assert!((theta - 1.5).abs() < 4.0 * se(&f, "scale"));
+    }
+
+    #[test]
+    fn weibull_fit_solves_likelihood_equation() {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let data = Weibull::new(2.0, 1.7).unwrap().sample_iter(&mut rng).take(5_000).collect::<Vec<f64>>();
+        let f = fit(Family::Weibull, &data).unwrap();
+        let (lambda, k) = (f.param("scale"), f.param("shape"));
+
+        // Σ x^k ln x / Σ x^k − 1/k = mean(ln x)
+        let (s0, s1) = data.iter().fold((0.0, 0.0), |(s0, s1), &v| (s0 + v.powf(k), s1 + v.powf(k) * v.ln()));
+        let mean_log = data.iter().map(|v| v.ln()).sum::<f64>() / data.len() as f64;
+        close(s1 / s0 - 1.0 / k, mean_log, 1e-10);
+        assert!((k - 1.7).abs() < 4.0 * se(&f, "shape"));
+        assert!((lambda - 2.0).abs() < 4.0 * se(&f, "scale"));
+    }
+
+    #[test]
+    fn fit_rejects_unsupported_data() {
+        assert!(fit(Family::Normal, &[1.0]).is_err());
+        assert!(fit(Family::Gamma, &[1.0, 0.0, 2.0]).is_err());
+        assert!(fit(Family::Poisson, &[1.0, 2.5]).is_err());
+    }
+
+    #[test]
+    fn fit_rejects_degenerate_data() {
+        for family in [Family::Normal, Family::LogNormal, Family::Gamma, Family::Weibull, Family::Exponential] {
+            assert!(fit(family, &[2.0, 2.0, 2.0]).is_err(), "{family:?}");
+        }
+        // All data on the support boundary
+        assert!(fit(Family::Exponential, &[0.0, 0.0, 0.0]).is_err());
+        assert!(fit(Family::Poisson, &[0.0, 0.0]).is_err());
+        // Equal up to rounding: ln x̄ − mean(ln x) is not positive
+        assert!(fit(Family::Gamma, &[1.0, 1.0 + f64::EPSILON, 1.0]).is_err());
+        // Constant counts are a valid Poisson sample
+        close(fit(Family::Poisson, &[3.0, 3.0]).unwrap().param("lambda"), 3.0, 0.0);
+    }
+
+    #[test]
+    fn quantile_inverts_cdf() {
+        let f = fit(Family::Gamma, &[0.5, 1.2, 2.0, 3.1, 0.8, 1.7]).unwrap();
+        for p in [0.05, 0.5, 0.95] {
+            close(f.cdf(f.quantile(p)), p, 1e-9);
+        }
+    }
+}
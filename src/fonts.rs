@@ -0,0 +1,47 @@
+// Fonts bundled with the crate.
+//
+// Text is laid out by plotters' ab_glyph backend from the fonts registered
+// here, not from whatever the system maps "sans-serif" to, so a figure (and a
+// golden reference) renders the same on every machine. The files are DejaVu
+// Sans and DejaVu Serif; their license is in `fonts/LICENSE`.
+
+use plotters::style::{register_font, FontStyle};
+use std::sync::Once;
+
+static SANS: &[u8] = include_bytes!("../fonts/DejaVuSans.ttf");
+static SERIF: &[u8] = include_bytes!("../fonts/DejaVuSerif.ttf");
+
+/// Font families available to [`crate::theme::Theme::font_family`].
+pub const FAMILIES: [&str; 2] = ["sans-serif", "serif"];
+
+/// Registers the bundled fonts under [`FAMILIES`]; later calls do nothing.
+/// Other styles (bold, italic) fall back to the regular face.
+pub fn register_fonts() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        for (name, bytes) in FAMILIES.into_iter().zip([SANS, SERIF]) {
+            if register_font(name, FontStyle::Normal, bytes).is_err() {
+                panic!("bundled font {name} is not a valid TrueType file");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::style::{FontDesc, FontFamily};
+
+    #[test]
+    fn registered_families_lay_out_text() {
+        register_fonts();
+        for name in FAMILIES {
+            let font = FontDesc::new(FontFamily::Name(name), 12.0, FontStyle::Normal);
+            let (w, h) = font.box_size("Histogram").unwrap();
+            assert!(w > 0 && h > 0, "{name}: {w}x{h}");
+        }
+        // Bold falls back to the regular face rather than failing
+        let bold = FontDesc::new(FontFamily::Name("serif"), 12.0, FontStyle::Bold);
+        assert!(bold.box_size("x").is_ok());
+    }
+}
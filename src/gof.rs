@@ -0,0 +1,306 @@
+// Goodness-of-fit tests of binned or raw data against a model.
+//
+// The model is given as a CDF (`Fn(f64) -> f64`) for the unbinned tests and as
+// expected bin counts (see `histogram::expected_counts`) for chi-square.
+
+use crate::stats::{chi_square_sf, sorted};
+use crate::theme::theme;
+use crate::units::pt;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::error::Error;
+
+/// Bins are merged with their neighbours until each expects at least this many counts.
+pub const MIN_EXPECTED: f64 = 5.0;
+
+/// Outcome of a goodness-of-fit test.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GofResult {
+    /// Short test name, e.g. `"χ²"`, `"KS"`, `"AD"`.
+    pub test: String,
+    pub statistic: f64,
+    pub p_value: f64,
+    /// Degrees of freedom (chi-square only).
+    pub dof: Option<usize>,
+}
+
+impl GofResult {
+    /// One-line summary such as `χ²/ndf = 41.2/38, p = 0.332`.
+    pub fn summary(&self) -> String {
+        match self.dof {
+            Some(dof) => format!("{}/ndf = {:.1}/{}, p = {:.3}", self.test, self.statistic, dof, self.p_value),
+            None => format!("{} = {:.4}, p = {:.3}", self.test, self.statistic, self.p_value),
+        }
+    }
+}
+
+/// Pearson chi-square test of observed bin counts against expected counts.
+///
+/// Adjacent bins are merged (left to right, any remainder into the last group)
+/// until every group expects at least [`MIN_EXPECTED`] counts. `fitted_params`
+/// is the number of model parameters estimated from the same data; it is
+/// subtracted from the degrees of freedom.
+pub fn chi_square(observed: &[usize], expected: &[f64], fitted_params: usize) -> GofResult {
+    let mut groups: Vec<(f64, f64)> = Vec::new();
+    let (mut o, mut e) = (0.0, 0.0);
+    for (&ob, &ex) in observed.iter().zip(expected) {
+        o += ob as f64;
+        e += ex;
+        if e >= MIN_EXPECTED {
+            groups.push((o, e));
+            o = 0.0;
+            e = 0.0;
+        }
+    }
+    if e > 0.0 || o > 0.0 {
+        match groups.last_mut() {
+            Some(last) => {
+                last.0 += o;
+                last.1 += e;
+            }
+            None => groups.push((o, e)),
+        }
+    }
+
+    let statistic = groups
+        .iter()
+        .filter(|(_, e)| *e > 0.0)
+        .map(|(o, e)| (o - e) * (o - e) / e)
+        .sum::<f64>();
+    let dof = groups.len().saturating_sub(1 + fitted_params);
+    let p_value = if dof > 0 { chi_square_sf(statistic, dof as f64) } else { f64::NAN };
+
+    GofResult {
+        test: "χ²".to_string(),
+        statistic,
+        p_value,
+        dof: Some(dof),
+    }
+}
+
+/// One-sample Kolmogorov–Smirnov test of `data` against `cdf`.
+///
+/// The p-value uses the asymptotic Kolmogorov distribution with Stephens'
+/// small-sample correction.
+pub fn ks_test<F>(data: &[f64], cdf: F) -> GofResult
+where
+    F: Fn(f64) -> f64,
+{
+    let sorted = sorted(data);
+    let n = sorted.len() as f64;
+    let d = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| {
+            let f = cdf(x);
+            (f - i as f64 / n).max((i + 1) as f64 / n - f)
+        })
+        .fold(0.0f64, f64::max);
+
+    let sqrt_n = n.sqrt();
+    let lambda = (sqrt_n + 0.12 + 0.11 / sqrt_n) * d;
+    GofResult {
+        test: "KS".to_string(),
+        statistic: d,
+        p_value: kolmogorov_sf(lambda),
+        dof: None,
+    }
+}
+
+/// Anderson–Darling test of `data` against a fully specified `cdf`.
+///
+/// The p-value uses Marsaglia & Marsaglia's (2004) approximation of the
+/// limiting distribution, which is accurate for n ≳ 5. Parameters estimated
+/// from the same data make it conservative.
+pub fn anderson_darling<F>(data: &[f64], cdf: F) -> GofResult
+where
+    F: Fn(f64) -> f64,
+{
+    let sorted = sorted(data);
+    let n = sorted.len();
+    let nf = n as f64;
+    let eps = 1e-300;
+    let s = (0..n)
+        .map(|i| {
+            let lo = cdf(sorted[i]).clamp(eps, 1.0 - 1e-16);
+            let hi = cdf(sorted[n - 1 - i]).clamp(eps, 1.0 - 1e-16);
+            (2 * i + 1) as f64 * (lo.ln() + (1.0 - hi).ln())
+        })
+        .sum::<f64>();
+    let a2 = -nf - s / nf.max(1.0);
+
+    GofResult {
+        test: "AD".to_string(),
+        statistic: a2,
+        p_value: (1.0 - ad_limit_cdf(a2)).clamp(0.0, 1.0),
+        dof: None,
+    }
+}
+
+// P(K > lambda) for the Kolmogorov distribution
+fn kolmogorov_sf(lambda: f64) -> f64 {
+    if lambda < 0.2 {
+        return 1.0;
+    }
+    let mut sum = 0.0;
+    let mut sign = 1.0;
+    for k in 1..=100 {
+        let term = sign * (-2.0 * (k * k) as f64 * lambda * lambda).exp();
+        sum += term;
+        if term.abs() < 1e-12 {
+            break;
+        }
+        sign = -sign;
+    }
+    (2.0 * sum).clamp(0.0, 1.0)
+}
+
+// Limiting CDF of the Anderson–Darling statistic (Marsaglia & Marsaglia, 2004)
+fn ad_limit_cdf(z: f64) -> f64 {
+    if z <= 0.0 {
+        return 0.0;
+    }
+    if z < 2.0 {
+        (-1.2337141 / z).exp() / z.sqrt()
+            * (2.00012 + (0.247105 - (0.0649821 - (0.0347962 - (0.011672 - 0.00168691 * z) * z) * z) * z) * z)
+    } else {
+        (-(1.0776 - (2.30695 - (0.43424 - (0.082433 - (0.008056 - 0.0003146 * z) * z) * z) * z) * z).exp()).exp()
+    }
+}
+
+/// Draws test summaries as a boxed text block in a corner of `area`.
+///
+/// Pass `chart.plotting_area().strip_coord_spec()` to place it inside a chart.
+pub fn draw_gof_box<DB>(
+    area: &DrawingArea<DB, Shift>,
+    results: &[GofResult],
+    position: SeriesLabelPosition,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let lines = results.iter().map(GofResult::summary).collect::<Vec<_>>();
+    draw_text_box(area, &lines, position)
+}
+
+/// Draws `lines` of text in a bordered, semi-transparent box at `position`.
+pub fn draw_text_box<DB>(
+    area: &DrawingArea<DB, Shift>,
+    lines: &[String],
+    position: SeriesLabelPosition,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    if lines.is_empty() {
+        return Ok(());
+    }
+    let theme = theme();
+    let style = theme.label_font();
+    let (pad, gap, margin) = (pt(3.0) as i32, pt(2.0) as i32, pt(3.0) as i32);
+
+    let mut w = 0i32;
+    let mut line_h = 0i32;
+    for line in lines {
+        let (tw, th) = area.estimate_text_size(line, &style)?;
+        w = w.max(tw as i32);
+        line_h = line_h.max(th as i32);
+    }
+    let n = lines.len() as i32;
+    let (bw, bh) = (w + 2 * pad, n * line_h + (n - 1) * gap + 2 * pad);
+
+    let (aw, ah) = area.dim_in_pixel();
+    let (aw, ah) = (aw as i32, ah as i32);
+    let (x0, y0) = match position {
+        SeriesLabelPosition::UpperLeft => (margin, margin),
+        SeriesLabelPosition::MiddleLeft => (margin, (ah - bh) / 2),
+        SeriesLabelPosition::LowerLeft => (margin, ah - bh - margin),
+        SeriesLabelPosition::UpperMiddle => ((aw - bw) / 2, margin),
+        SeriesLabelPosition::MiddleMiddle => ((aw - bw) / 2, (ah - bh) / 2),
+        SeriesLabelPosition::LowerMiddle => ((aw - bw) / 2, ah - bh - margin),
+        SeriesLabelPosition::UpperRight => (aw - bw - margin, margin),
+        SeriesLabelPosition::MiddleRight => (aw - bw - margin, (ah - bh) / 2),
+        SeriesLabelPosition::LowerRight => (aw - bw - margin, ah - bh - margin),
+        SeriesLabelPosition::Coordinate(x, y) => (x, y),
+    };
+
+    area.draw(&Rectangle::new([(x0, y0), (x0 + bw, y0 + bh)], theme.background.mix(0.85).filled()))?;
+    area.draw(&Rectangle::new([(x0, y0), (x0 + bw, y0 + bh)], theme.axis))?;
+    for (i, line) in lines.iter().enumerate() {
+        let y = y0 + pad + i as i32 * (line_h + gap);
+        area.draw(&Text::new(line.as_str(), (x0 + pad, y), style.clone()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, tol: f64) {
+        assert!((a - b).abs() <= tol, "{} differs from {} by more than {}", a, b, tol);
+    }
+
+    // Sorted sample tested against the uniform CDF on [0, 1], so the statistics
+    // can be worked out by hand: D = 0.2 (at 0.4 and 0.8).
+    const UNIFORM_SAMPLE: [f64; 5] = [0.9, 0.1, 0.45, 0.8, 0.4];
+
+    fn uniform_cdf(x: f64) -> f64 {
+        x.clamp(0.0, 1.0)
+    }
+
+    #[test]
+    fn chi_square_matches_reference() {
+        // scipy.stats.chisquare([10, 20, 30]): statistic 10, p = e^-5 with 2 dof
+        let r = chi_square(&[10, 20, 30], &[20.0, 20.0, 20.0], 0);
+        close(r.statistic, 10.0, 1e-12);
+        assert_eq!(r.dof, Some(2));
+        close(r.p_value, 0.006_737_947, 1e-8);
+
+        // One fitted parameter takes a degree of freedom: p = pchisq(10, 1, lower = FALSE)
+        let r = chi_square(&[10, 20, 30], &[20.0, 20.0, 20.0], 1);
+        assert_eq!(r.dof, Some(1));
+        close(r.p_value, 0.001_565_402, 1e-8);
+    }
+
+    #[test]
+    fn chi_square_merges_sparse_bins() {
+        // The first three bins expect 6 together: (9 - 6)²/6 + (18 - 20)²/20
+        let r = chi_square(&[3, 4, 2, 18], &[2.0, 2.0, 2.0, 20.0], 0);
+        assert_eq!(r.dof, Some(1));
+        close(r.statistic, 1.5 + 0.2, 1e-12);
+    }
+
+    #[test]
+    fn ks_statistic_and_p_value() {
+        let r = ks_test(&UNIFORM_SAMPLE, uniform_cdf);
+        close(r.statistic, 0.2, 1e-12);
+        // Kolmogorov survival at λ = (√5 + 0.12 + 0.11/√5)·0.2 = 0.48105
+        close(r.p_value, 0.974_789_2, 1e-6);
+    }
+
+    #[test]
+    fn kolmogorov_tail_matches_table() {
+        close(kolmogorov_sf(1.0), 0.269_999_7, 1e-6);
+        close(kolmogorov_sf(1.3581), 0.05, 1e-5);
+        close(kolmogorov_sf(1.6276), 0.01, 1e-5);
+    }
+
+    #[test]
+    fn anderson_darling_statistic() {
+        // A² = -n - Σ(2i - 1)(ln u_i + ln(1 - u_{n+1-i}))/n
+        let r = anderson_darling(&UNIFORM_SAMPLE, uniform_cdf);
+        close(r.statistic, 0.239_670_6, 1e-6);
+        assert!(r.p_value > 0.9);
+    }
+
+    #[test]
+    fn anderson_darling_tail_matches_critical_values() {
+        // Upper 10%, 5% and 1% points of A² for a fully specified distribution
+        close(1.0 - ad_limit_cdf(1.933), 0.10, 1e-3);
+        close(1.0 - ad_limit_cdf(2.492), 0.05, 1e-3);
+        close(1.0 - ad_limit_cdf(3.857), 0.01, 1e-3);
+    }
+}
@@ -0,0 +1,488 @@
+// Heatmaps: values on a grid drawn as colored cells, with a colorbar.
+//
+// Values are mapped onto [0, 1] by a `ColorNorm` (linear or log, between
+// limits that default to the data range) and then to colors by a `Colormap`,
+// the theme's unless the style names one. `draw_heatmap` draws a `Grid`
+// sampled at numeric x and y coordinates and returns its chart so lines can be
+// drawn on top; `draw_matrix` draws a matrix with named rows and columns, such
+// as a correlation matrix, with the first row at the top. Both keep a strip at
+// the right of the figure for the colorbar.
+
+use crate::category::CategoryAxis;
+use crate::color::Colormap;
+use crate::line::Chart2d;
+use crate::theme::theme;
+use crate::ticks::TickFormat;
+use crate::units::pt;
+use plotters::coord::ranged1d::{AsRangedCoord, ValueFormatter};
+use plotters::coord::{CoordTranslate, Shift};
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use std::error::Error;
+use std::ops::Range;
+use std::str::FromStr;
+
+/// Values sampled on a rectangular grid: `z[j][i]` is the value at `(x[i], y[j])`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid {
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub z: Vec<Vec<f64>>,
+}
+
+impl Grid {
+    /// A grid from increasing `x` and `y` coordinates and one row of `z` per `y`.
+    pub fn new(x: Vec<f64>, y: Vec<f64>, z: Vec<Vec<f64>>) -> Result<Self, Box<dyn Error>> {
+        if x.is_empty() || y.is_empty() {
+            return Err("A grid needs at least one x and one y coordinate".into());
+        }
+        for (name, c) in [("x", &x), ("y", &y)] {
+            if c.windows(2).any(|w| w[0] >= w[1] || w[0].is_nan()) {
+                return Err(format!("Grid {} coordinates must be increasing", name).into());
+            }
+        }
+        if z.len() != y.len() || z.iter().any(|row| row.len() != x.len()) {
+            return Err(format!("Grid values must be {} rows of {} (one row per y)", y.len(), x.len()).into());
+        }
+        Ok(Self { x, y, z })
+    }
+
+    /// `f(x, y)` at every point of `x` × `y`, e.g. over two `linspace` ranges.
+    pub fn from_fn<F: Fn(f64, f64) -> f64>(x: Vec<f64>, y: Vec<f64>, f: F) -> Self {
+        let z = y.iter().map(|&yv| x.iter().map(|&xv| f(xv, yv)).collect()).collect();
+        Self { x, y, z }
+    }
+
+    /// The smallest and largest finite value, if there is one.
+    pub fn z_range(&self) -> Option<(f64, f64)> {
+        finite_range(self.z.iter().flatten().copied())
+    }
+
+    /// Cell boundaries along x: halfway between neighboring points, and half a step beyond the ends.
+    pub fn x_edges(&self) -> Vec<f64> {
+        cell_edges(&self.x)
+    }
+
+    /// Cell boundaries along y, as for [`Grid::x_edges`].
+    pub fn y_edges(&self) -> Vec<f64> {
+        cell_edges(&self.y)
+    }
+}
+
+fn cell_edges(centers: &[f64]) -> Vec<f64> {
+    match centers {
+        [] => vec![],
+        [c] => vec![c - 0.5, c + 0.5],
+        _ => {
+            let n = centers.len();
+            let mut edges = Vec::with_capacity(n + 1);
+            edges.push(centers[0] - (centers[1] - centers[0]) / 2.0);
+            edges.extend(centers.windows(2).map(|w| (w[0] + w[1]) / 2.0));
+            edges.push(centers[n - 1] + (centers[n - 1] - centers[n - 2]) / 2.0);
+            edges
+        }
+    }
+}
+
+fn finite_range<I: IntoIterator<Item = f64>>(values: I) -> Option<(f64, f64)> {
+    values
+        .into_iter()
+        .filter(|v| v.is_finite())
+        .fold(None, |acc, v| match acc {
+            None => Some((v, v)),
+            Some((lo, hi)) => Some((v.min(lo), v.max(hi))),
+        })
+}
+
+/// How values are spread over the colormap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ColorScale {
+    #[default]
+    Linear,
+    /// Base-10 logarithmic; non-positive values are left blank.
+    Log,
+}
+
+impl FromStr for ColorScale {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "linear" | "lin" => Ok(ColorScale::Linear),
+            "log" => Ok(ColorScale::Log),
+            _ => Err(format!("Unknown color scale '{}' (expected linear or log)", s).into()),
+        }
+    }
+}
+
+/// Maps values between `min` and `max` onto [0, 1] for a colormap.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ColorNorm {
+    pub scale: ColorScale,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ColorNorm {
+    /// Limits covering the finite `values` (the positive ones on a log scale);
+    /// `limits` override either end.
+    pub fn from_values<I>(values: I, scale: ColorScale, limits: (Option<f64>, Option<f64>)) -> Result<Self, Box<dyn Error>>
+    where
+        I: IntoIterator<Item = f64>,
+    {
+        let log = scale == ColorScale::Log;
+        let (min, max) = match finite_range(values.into_iter().filter(|v| !log || *v > 0.0)) {
+            Some((lo, hi)) if lo < hi => (lo, hi),
+            Some((v, _)) if log => (v / 10.0, v * 10.0),
+            Some((v, _)) => (v - 0.5, v + 0.5),
+            None if log => (1.0, 10.0),
+            None => (0.0, 1.0),
+        };
+        let (min, max) = (limits.0.unwrap_or(min), limits.1.unwrap_or(max));
+        if min >= max {
+            return Err(format!("Empty color range {}..{}", min, max).into());
+        }
+        if log && min <= 0.0 {
+            return Err(format!("A log color scale needs a positive range, got {}..{}", min, max).into());
+        }
+        Ok(Self { scale, min, max })
+    }
+
+    /// Where `v` falls between the limits, clamped to [0, 1]; `None` for values
+    /// without a color (NaN, or non-positive on a log scale).
+    pub fn apply(&self, v: f64) -> Option<f64> {
+        let t = match self.scale {
+            _ if v.is_nan() => return None,
+            ColorScale::Linear => (v - self.min) / (self.max - self.min),
+            ColorScale::Log if v <= 0.0 => return None,
+            ColorScale::Log => (v / self.min).ln() / (self.max / self.min).ln(),
+        };
+        Some(t.clamp(0.0, 1.0))
+    }
+
+    /// The value at `t` in [0, 1], the inverse of [`ColorNorm::apply`].
+    pub fn value_at(&self, t: f64) -> f64 {
+        match self.scale {
+            ColorScale::Linear => self.min + t * (self.max - self.min),
+            ColorScale::Log => self.min * (self.max / self.min).powf(t),
+        }
+    }
+}
+
+/// Options for [`draw_heatmap`] and [`draw_matrix`].
+#[derive(Clone, Debug)]
+pub struct HeatmapStyle {
+    /// `None` uses the theme's colormap.
+    pub colormap: Option<Colormap>,
+    pub scale: ColorScale,
+    /// Color limits; a missing end comes from the data.
+    pub limits: (Option<f64>, Option<f64>),
+    /// Writes each cell's value in it with this format.
+    pub annotate: Option<TickFormat>,
+    pub colorbar: bool,
+    /// Caption of the colorbar.
+    pub label: String,
+}
+
+impl Default for HeatmapStyle {
+    fn default() -> Self {
+        Self {
+            colormap: None,
+            scale: ColorScale::Linear,
+            limits: (None, None),
+            annotate: None,
+            colorbar: true,
+            label: String::new(),
+        }
+    }
+}
+
+impl HeatmapStyle {
+    pub fn colormap(mut self, colormap: Colormap) -> Self {
+        self.colormap = Some(colormap);
+        self
+    }
+
+    pub fn log(mut self) -> Self {
+        self.scale = ColorScale::Log;
+        self
+    }
+
+    /// Fixes both color limits, e.g. `-1..1` for correlations.
+    pub fn range(mut self, min: f64, max: f64) -> Self {
+        self.limits = (Some(min), Some(max));
+        self
+    }
+
+    pub fn annotate(mut self, format: TickFormat) -> Self {
+        self.annotate = Some(format);
+        self
+    }
+
+    pub fn label(mut self, label: &str) -> Self {
+        self.label = label.to_string();
+        self
+    }
+
+    pub fn hide_colorbar(mut self) -> Self {
+        self.colorbar = false;
+        self
+    }
+
+    /// The normalization of `values` with this style's scale and limits.
+    pub fn norm<I: IntoIterator<Item = f64>>(&self, values: I) -> Result<ColorNorm, Box<dyn Error>> {
+        ColorNorm::from_values(values, self.scale, self.limits)
+    }
+
+    fn map(&self) -> Colormap {
+        self.colormap.unwrap_or_else(|| theme().colormap)
+    }
+
+    fn colorbar_width(&self) -> f64 {
+        if self.colorbar {
+            colorbar_width()
+        } else {
+            0.0
+        }
+    }
+}
+
+// Colorbar layout in points: gap after the plot, width of the bar itself
+const COLORBAR_GAP: f64 = 6.0;
+const COLORBAR_BAR: f64 = 8.0;
+
+fn colorbar_label_area() -> f64 {
+    theme().y_label_area + 11.0
+}
+
+/// Points a colorbar takes from the right of a figure: the gap after the plot,
+/// the bar, its tick labels and caption. Leave this much margin for [`draw_colorbar`].
+pub fn colorbar_width() -> f64 {
+    COLORBAR_GAP + COLORBAR_BAR + colorbar_label_area()
+}
+
+/// Draws `grid` as cells spanning the grid's cell edges, with a colorbar unless
+/// the style hides it. Returns the chart, on the grid's x and y range, for
+/// drawing lines or markers on top.
+pub fn draw_heatmap<'a, DB>(
+    root: &'a DrawingArea<DB, Shift>,
+    title: &str,
+    x_label: &str,
+    y_label: &str,
+    grid: &Grid,
+    style: &HeatmapStyle,
+) -> Result<Chart2d<'a, DB>, Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    if grid.x.is_empty() || grid.y.is_empty() {
+        return Err("Cannot draw an empty grid".into());
+    }
+    let norm = style.norm(grid.z.iter().flatten().copied())?;
+    let (xe, ye) = (grid.x_edges(), grid.y_edges());
+
+    let theme = theme();
+    let mut chart = theme
+        .chart(root)
+        .caption(title, theme.title_font())
+        .margin_right(pt(theme.margin + style.colorbar_width()))
+        .build_cartesian_2d(xe[0]..xe[xe.len() - 1], ye[0]..ye[ye.len() - 1])?;
+    theme
+        .mesh(&mut chart.configure_mesh())
+        .disable_mesh()
+        .x_desc(x_label)
+        .y_desc(y_label)
+        .draw()?;
+
+    let cells = grid.z.iter().enumerate().flat_map(|(j, row)| {
+        let (xe, ye) = (&xe, &ye);
+        row.iter()
+            .enumerate()
+            .map(move |(i, &v)| ([(xe[i], ye[j]), (xe[i + 1], ye[j + 1])], v))
+    });
+    draw_cells(&mut chart, cells, &norm, style)?;
+
+    if style.colorbar {
+        let (_, rows) = chart.plotting_area().get_pixel_range();
+        draw_colorbar(root, rows, &norm, style.map(), &style.label, None)?;
+    }
+    Ok(chart)
+}
+
+/// Draws `values`, one row per entry of `rows` and one column per entry of
+/// `columns`, as labelled cells with the first row at the top.
+pub fn draw_matrix<DB, S>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    rows: &[S],
+    columns: &[S],
+    values: &[Vec<f64>],
+    style: &HeatmapStyle,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    S: AsRef<str>,
+{
+    if rows.is_empty() || columns.is_empty() {
+        return Err("Cannot draw an empty matrix".into());
+    }
+    if values.len() != rows.len() || values.iter().any(|r| r.len() != columns.len()) {
+        return Err(format!("Matrix values must be {} rows of {}", rows.len(), columns.len()).into());
+    }
+    let norm = style.norm(values.iter().flatten().copied())?;
+    let n = rows.len();
+
+    // Category 0 is at the bottom, so the y axis lists the rows bottom-up
+    let theme = theme();
+    let bottom_up = rows.iter().rev().map(AsRef::as_ref).collect::<Vec<_>>();
+    let label_width = bottom_up
+        .iter()
+        .map(|name| root.estimate_text_size(name, &theme.label_font()).map(|(w, _)| w))
+        .try_fold(0, |max, w| w.map(|w| max.max(w)))?;
+    let mut chart = theme
+        .chart(root)
+        .caption(title, theme.title_font())
+        .margin_right(pt(theme.margin + style.colorbar_width()))
+        .y_label_area_size(label_width + pt(theme.tick_size + 4.0))
+        .build_cartesian_2d(CategoryAxis::new(columns), CategoryAxis::new(&bottom_up))?;
+    theme
+        .mesh(&mut chart.configure_mesh())
+        .disable_mesh()
+        .x_labels(columns.len())
+        .y_labels(n)
+        .draw()?;
+
+    let cells = values.iter().enumerate().flat_map(|(r, row)| {
+        let y = (n - 1 - r) as f64;
+        row.iter()
+            .enumerate()
+            .map(move |(c, &v)| ([(c as f64 - 0.5, y - 0.5), (c as f64 + 0.5, y + 0.5)], v))
+    });
+    draw_cells(&mut chart, cells, &norm, style)?;
+
+    if style.colorbar {
+        let (_, plot_rows) = chart.plotting_area().get_pixel_range();
+        draw_colorbar(root, plot_rows, &norm, style.map(), &style.label, None)?;
+    }
+    Ok(())
+}
+
+// Fills each cell with its value's color and writes the value in it if the style annotates
+fn draw_cells<DB, CT, I>(chart: &mut ChartContext<'_, DB, CT>, cells: I, norm: &ColorNorm, style: &HeatmapStyle) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    CT: CoordTranslate<From = (f64, f64)>,
+    I: IntoIterator<Item = ([(f64, f64); 2], f64)>,
+{
+    let map = style.map();
+    let colored = cells
+        .into_iter()
+        .filter_map(|(rect, v)| Some((rect, v, map.at(norm.apply(v)?))))
+        .collect::<Vec<_>>();
+    chart.draw_series(colored.iter().map(|&(rect, _, color)| Rectangle::new(rect, color.filled())))?;
+
+    if let Some(format) = &style.annotate {
+        let font = theme().label_font().pos(Pos::new(HPos::Center, VPos::Center));
+        let (on_light, on_dark) = (font.color(&BLACK), font.color(&WHITE));
+        chart.draw_series(colored.iter().map(|&([(x0, y0), (x1, y1)], v, color)| {
+            let center = ((x0 + x1) / 2.0, (y0 + y1) / 2.0);
+            let font = if is_light(color) { on_light.clone() } else { on_dark.clone() };
+            Text::new(format.format(v), center, font)
+        }))?;
+    }
+    Ok(())
+}
+
+// Whether black text reads better than white on `cell`
+fn is_light(cell: RGBColor) -> bool {
+    0.299 * cell.0 as f64 + 0.587 * cell.1 as f64 + 0.114 * cell.2 as f64 > 128.0
+}
+
+/// Draws a colorbar for `norm` in a strip at the right of `root`, level with
+/// the plot's pixel `rows` (see [`colorbar_width`]). With `bands`, each band
+/// between consecutive values gets the flat color of its middle, as for filled contours.
+pub fn draw_colorbar<DB>(
+    root: &DrawingArea<DB, Shift>,
+    rows: Range<i32>,
+    norm: &ColorNorm,
+    map: Colormap,
+    label: &str,
+    bands: Option<&[f64]>,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let theme = theme();
+    let (w, h) = root.dim_in_pixel();
+    let strip_w = pt(theme.margin + colorbar_width()).min(w);
+    let (_, strip) = root.split_horizontally(w - strip_w);
+    let top = rows.start - root.get_base_pixel().1;
+    let bottom = h as i32 - (rows.end - root.get_base_pixel().1);
+
+    let mut builder = ChartBuilder::on(&strip);
+    builder
+        .margin_top(top.max(0) as u32)
+        .margin_bottom(bottom.max(0) as u32)
+        .margin_left(pt(COLORBAR_GAP))
+        .margin_right(pt(theme.margin))
+        .right_y_label_area_size(pt(colorbar_label_area()));
+    let bar = Colorbar { norm, map, label, bands };
+    match norm.scale {
+        ColorScale::Linear => bar.draw(builder, norm.min..norm.max),
+        ColorScale::Log => bar.draw(builder, (norm.min..norm.max).log_scale()),
+    }
+}
+
+// Arguments of `draw_colorbar`, carried through the scale dispatch
+struct Colorbar<'a> {
+    norm: &'a ColorNorm,
+    map: Colormap,
+    label: &'a str,
+    bands: Option<&'a [f64]>,
+}
+
+impl Colorbar<'_> {
+    fn draw<DB, Y>(&self, mut builder: ChartBuilder<'_, '_, DB>, yr: Y) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+        Y: AsRangedCoord<Value = f64>,
+        Y::CoordDescType: ValueFormatter<f64>,
+    {
+        let (norm, map) = (self.norm, self.map);
+        let theme = theme();
+        let mut chart = builder.build_cartesian_2d(0.0f64..1.0f64, yr)?;
+        theme
+            .mesh(&mut chart.configure_mesh())
+            .disable_mesh()
+            .disable_x_axis()
+            .y_desc(self.label)
+            .draw()?;
+
+        match self.bands {
+            Some(edges) => {
+                chart.draw_series(edges.windows(2).filter_map(|w| {
+                    let t = norm.apply((w[0] + w[1]) / 2.0)?;
+                    Some(Rectangle::new([(0.0, w[0]), (1.0, w[1])], map.at(t).filled()))
+                }))?;
+            }
+            None => {
+                let steps = 256;
+                chart.draw_series((0..steps).map(|k| {
+                    let (t0, t1) = (k as f64 / steps as f64, (k + 1) as f64 / steps as f64);
+                    let (v0, v1) = (norm.value_at(t0), norm.value_at(t1));
+                    Rectangle::new([(0.0, v0), (1.0, v1)], map.at((t0 + t1) / 2.0).filled())
+                }))?;
+            }
+        }
+        chart.draw_series(std::iter::once(Rectangle::new(
+            [(0.0, norm.min), (1.0, norm.max)],
+            theme.axis.stroke_width(pt(theme.axis_width)),
+        )))?;
+        Ok(())
+    }
+}
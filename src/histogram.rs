@@ -0,0 +1,941 @@
+use crate::stats::{data_min_max, iqr, std_dev, QuantileMethod};
+use crate::theme::theme;
+use crate::units::pt;
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::{CoordTranslate, Shift};
+use plotters::prelude::*;
+use std::error::Error;
+use std::str::FromStr;
+
+pub fn linspace(start: f64, end: f64, n: usize) -> Vec<f64> {
+    if n == 0 {
+        return vec![];
+    }
+    if n == 1 {
+        return vec![start];
+    }
+    let step = (end - start) / ((n - 1) as f64);
+    (0..n).map(|i| start + (i as f64) * step).collect()
+}
+
+pub fn edges_from_bins(minv: f64, maxv: f64, bins: usize) -> Vec<f64> {
+    linspace(minv, maxv, bins + 1)
+}
+
+pub fn counts_from_edges(data: &[f64], edges: &[f64]) -> Vec<usize> {
+    let nb = edges.len().saturating_sub(1);
+    let mut counts = vec![0usize; nb];
+
+    for &v in data {
+        for i in 0..nb {
+            let lo = edges[i];
+            let hi = edges[i + 1];
+            let in_bin = if i == nb - 1 {
+                v >= lo && v <= hi
+            } else {
+                v >= lo && v < hi
+            };
+            if in_bin {
+                counts[i] += 1;
+                break;
+            }
+        }
+    }
+    counts
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    Count,
+    CountDensity,
+    Probability,
+    Pdf,
+}
+
+impl Normalization {
+    pub const ALL: [Normalization; 4] = [
+        Normalization::Count,
+        Normalization::CountDensity,
+        Normalization::Probability,
+        Normalization::Pdf,
+    ];
+
+    /// Default y-axis description for heights under this normalization.
+    pub fn axis_label(self) -> &'static str {
+        match self {
+            Normalization::Count => "Count",
+            Normalization::CountDensity => "Count density",
+            Normalization::Probability => "Probability",
+            Normalization::Pdf => "Probability density",
+        }
+    }
+}
+
+impl FromStr for Normalization {
+    type Err = Box<dyn Error>;
+
+    /// `count`, `count-density` (or `density`), `probability` or `pdf`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "count" => Ok(Normalization::Count),
+            "count-density" | "countdensity" | "density" => Ok(Normalization::CountDensity),
+            "probability" | "prob" => Ok(Normalization::Probability),
+            "pdf" => Ok(Normalization::Pdf),
+            other => Err(format!("Unknown normalization '{}' (count, count-density, probability, pdf)", other).into()),
+        }
+    }
+}
+
+pub fn normalized_heights(counts: &[usize], edges: &[f64], norm: Normalization) -> Vec<f64> {
+    let n = counts.iter().sum::<usize>().max(1) as f64;
+    counts
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| c as f64 * norm_scale(norm, n, edges[i + 1] - edges[i]))
+        .collect()
+}
+
+// Factor turning a raw bin count into a `norm` height, given the total count and bin width
+fn norm_scale(norm: Normalization, total: f64, width: f64) -> f64 {
+    let w = width.abs().max(1e-12);
+    match norm {
+        Normalization::Count => 1.0,
+        Normalization::CountDensity => 1.0 / w,
+        Normalization::Probability => 1.0 / total,
+        Normalization::Pdf => 1.0 / (total * w),
+    }
+}
+
+/// Height of a curve with density `pdf` on a histogram of `total` values with
+/// bins of `width`, so fitted PDFs can be overlaid under any normalization.
+pub fn pdf_to_height(pdf: f64, norm: Normalization, total: f64, width: f64) -> f64 {
+    pdf * total * width.abs() * norm_scale(norm, total.max(1.0), width)
+}
+
+/// Statistical uncertainty of each bin, scaled like [`normalized_heights`].
+///
+/// `Count` and `CountDensity` use the Poisson √N. `Probability` and `Pdf` divide
+/// by the total N, so their bins are multinomial fractions with error
+/// √(N(1 − N/total)) / total (and 1/width more for `Pdf`); empty bins get 0.
+pub fn poisson_errors(counts: &[usize], edges: &[f64], norm: Normalization) -> Vec<f64> {
+    let n = counts.iter().sum::<usize>().max(1) as f64;
+    counts
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            let c = c as f64;
+            let spread = match norm {
+                Normalization::Count | Normalization::CountDensity => c,
+                Normalization::Probability | Normalization::Pdf => c * (1.0 - c / n),
+            };
+            spread.sqrt() * norm_scale(norm, n, edges[i + 1] - edges[i])
+        })
+        .collect()
+}
+
+/// Per-bin sum of weights and sum of squared weights, with the same bin rules as [`counts_from_edges`].
+pub fn weighted_counts_from_edges(data: &[f64], weights: &[f64], edges: &[f64]) -> (Vec<f64>, Vec<f64>) {
+    let nb = edges.len().saturating_sub(1);
+    let mut sum_w = vec![0.0f64; nb];
+    let mut sum_w2 = vec![0.0f64; nb];
+
+    for (&v, &w) in data.iter().zip(weights) {
+        if let Some(i) = bin_index(v, edges) {
+            sum_w[i] += w;
+            sum_w2[i] += w * w;
+        }
+    }
+    (sum_w, sum_w2)
+}
+
+/// Heights and uncertainties of a weighted histogram under `norm`.
+///
+/// `Count` and `CountDensity` use √Σw². `Probability` and `Pdf` divide by the
+/// total weight W, so like [`poisson_errors`] they account for the bin's share
+/// of it: the error of wᵢ/W is √(Σw²ᵢ(1 − 2pᵢ) + pᵢ²Σw²) / W with pᵢ = wᵢ/W,
+/// which reduces to the multinomial error for unit weights.
+pub fn weighted_heights_and_errors(
+    sum_w: &[f64],
+    sum_w2: &[f64],
+    edges: &[f64],
+    norm: Normalization,
+) -> (Vec<f64>, Vec<f64>) {
+    let total = sum_w.iter().sum::<f64>();
+    let total = if total.abs() > 0.0 { total } else { 1.0 };
+    let total_w2 = sum_w2.iter().sum::<f64>();
+    sum_w
+        .iter()
+        .zip(sum_w2)
+        .enumerate()
+        .map(|(i, (&w, &w2))| {
+            let spread = match norm {
+                Normalization::Count | Normalization::CountDensity => w2,
+                Normalization::Probability | Normalization::Pdf => {
+                    let p = w / total;
+                    (w2 * (1.0 - 2.0 * p) + p * p * total_w2).max(0.0)
+                }
+            };
+            let k = norm_scale(norm, total, edges[i + 1] - edges[i]);
+            (w * k, spread.sqrt() * k)
+        })
+        .unzip()
+}
+
+// Bin containing `v`; the last bin includes its upper edge
+fn bin_index(v: f64, edges: &[f64]) -> Option<usize> {
+    let nb = edges.len().checked_sub(1)?;
+    if nb == 0 || !(v >= edges[0] && v <= edges[nb]) {
+        return None;
+    }
+    Some(edges.partition_point(|&e| e <= v).saturating_sub(1).min(nb - 1))
+}
+
+/// MATLAB-style `DisplayStyle` of a histogram.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisplayStyle {
+    /// Filled bars.
+    Bar,
+    /// Unfilled step outline ("stairs").
+    Stairs,
+}
+
+/// Appearance of a single histogram.
+#[derive(Clone, Copy, Debug)]
+pub struct HistStyle {
+    pub display: DisplayStyle,
+    /// Bar fill color, or the outline color for stairs.
+    pub color: ShapeStyle,
+    /// Outline drawn around each bar (`Bar` only).
+    pub edge: Option<ShapeStyle>,
+    /// Bar width as a fraction of the bin width; `1.0` leaves no gap (`Bar` only).
+    pub bar_width: f64,
+    /// Opacity of a faint fill under the outline (`Stairs` only).
+    pub fill_alpha: Option<f64>,
+}
+
+impl HistStyle {
+    pub fn bars<S: Into<ShapeStyle>>(color: S) -> Self {
+        Self {
+            display: DisplayStyle::Bar,
+            color: color.into(),
+            edge: None,
+            bar_width: 1.0,
+            fill_alpha: None,
+        }
+    }
+
+    pub fn stairs<S: Into<ShapeStyle>>(color: S) -> Self {
+        Self {
+            display: DisplayStyle::Stairs,
+            ..Self::bars(color)
+        }
+    }
+
+    pub fn edge<S: Into<ShapeStyle>>(mut self, edge: S) -> Self {
+        self.edge = Some(edge.into());
+        self
+    }
+
+    pub fn bar_width(mut self, fraction: f64) -> Self {
+        self.bar_width = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn fill_alpha(mut self, alpha: f64) -> Self {
+        self.fill_alpha = Some(alpha);
+        self
+    }
+}
+
+impl From<ShapeStyle> for HistStyle {
+    fn from(style: ShapeStyle) -> Self {
+        HistStyle::bars(style)
+    }
+}
+
+pub fn draw_histogram<DB, S>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    x_label: &str,
+    y_label: &str,
+    edges: &[f64],
+    heights: &[f64],
+    style: S,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    S: Into<HistStyle>,
+{
+    let ymax = heights.iter().cloned().fold(0.0f64, f64::max);
+    let mut chart = histogram_chart(root, title, x_label, y_label, edges, ymax)?;
+    draw_hist_bins(&mut chart, edges, heights, &style.into())
+}
+
+/// [`draw_histogram`] with a ±`errors` uncertainty on every bin: capped
+/// whiskers on bars, a shaded band on stairs.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_histogram_with_errors<DB, S>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    x_label: &str,
+    y_label: &str,
+    edges: &[f64],
+    heights: &[f64],
+    errors: &[f64],
+    style: S,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    S: Into<HistStyle>,
+{
+    let ymax = heights
+        .iter()
+        .zip(errors)
+        .map(|(h, e)| h + e)
+        .fold(0.0f64, f64::max);
+    let style = style.into();
+    let mut chart = histogram_chart(root, title, x_label, y_label, edges, ymax)?;
+    draw_hist_bins(&mut chart, edges, heights, &style)?;
+    draw_hist_errors(&mut chart, edges, heights, errors, &style)
+}
+
+/// Titled chart spanning `edges` and `0..ymax` (plus headroom) with a labelled mesh,
+/// ready for [`draw_hist_bins`] and further series such as fitted curves.
+pub fn histogram_chart<'a, DB>(
+    root: &'a DrawingArea<DB, Shift>,
+    title: &str,
+    x_label: &str,
+    y_label: &str,
+    edges: &[f64],
+    ymax: f64,
+) -> Result<ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>, Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let (xmin, xmax) = (*edges.first().unwrap(), *edges.last().unwrap());
+    let ymax = ymax.max(1e-12);
+
+    let theme = theme();
+    let mut chart = theme
+        .chart(root)
+        .caption(title, theme.title_font())
+        .build_cartesian_2d(xmin..xmax, 0.0f64..(ymax * 1.1))?;
+
+    theme
+        .mesh(&mut chart.configure_mesh())
+        .x_desc(x_label)
+        .y_desc(y_label)
+        .draw()?;
+
+    Ok(chart)
+}
+
+/// Draws ±`errors` around each bin height: whiskers with caps at the bin centers
+/// for `Bar` (in the edge color, black by default), a translucent band following
+/// the step outline for `Stairs`.
+pub fn draw_hist_errors<DB, CT>(
+    chart: &mut ChartContext<'_, DB, CT>,
+    edges: &[f64],
+    heights: &[f64],
+    errors: &[f64],
+    style: &HistStyle,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    CT: CoordTranslate<From = (f64, f64)>,
+{
+    let n = heights.len().min(errors.len()).min(edges.len().saturating_sub(1));
+    match style.display {
+        DisplayStyle::Bar => {
+            let whisker = style.edge.unwrap_or_else(|| theme().foreground.into());
+            let whisker = whisker.stroke_width(whisker.stroke_width.max(pt(0.5)));
+            chart.draw_series((0..n).filter(|&i| errors[i] > 0.0).map(|i| {
+                let x = (edges[i] + edges[i + 1]) / 2.0;
+                let (h, e) = (heights[i], errors[i]);
+                ErrorBar::new_vertical(x, (h - e).max(0.0), h, h + e, whisker, pt(3.0))
+            }))?;
+        }
+        DisplayStyle::Stairs => {
+            let band = style.color.color.mix(0.25).filled();
+            chart.draw_series((0..n).filter(|&i| errors[i] > 0.0).map(|i| {
+                let (h, e) = (heights[i], errors[i]);
+                Rectangle::new([(edges[i], (h - e).max(0.0)), (edges[i + 1], h + e)], band)
+            }))?;
+        }
+    }
+    Ok(())
+}
+
+/// Draws histogram bins on an existing chart in the given style.
+pub fn draw_hist_bins<DB, CT>(
+    chart: &mut ChartContext<'_, DB, CT>,
+    edges: &[f64],
+    heights: &[f64],
+    style: &HistStyle,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    CT: CoordTranslate<From = (f64, f64)>,
+{
+    match style.display {
+        DisplayStyle::Bar => {
+            let bars = heights
+                .iter()
+                .enumerate()
+                .map(|(i, &h)| {
+                    let pad = (edges[i + 1] - edges[i]) * (1.0 - style.bar_width) / 2.0;
+                    [(edges[i] + pad, 0.0), (edges[i + 1] - pad, h)]
+                })
+                .collect::<Vec<_>>();
+            chart.draw_series(bars.iter().map(|&r| Rectangle::new(r, style.color.filled())))?;
+            if let Some(edge) = style.edge {
+                chart.draw_series(bars.iter().map(|&r| Rectangle::new(r, edge.stroke_width(edge.stroke_width.max(pt(0.25))))))?;
+            }
+        }
+        DisplayStyle::Stairs => {
+            let outline = step_outline(edges, heights);
+            if let Some(alpha) = style.fill_alpha {
+                chart.draw_series(std::iter::once(Polygon::new(outline.clone(), style.color.color.mix(alpha).filled())))?;
+            }
+            chart.draw_series(std::iter::once(PathElement::new(
+                outline,
+                style.color.color.stroke_width(style.color.stroke_width.max(pt(0.5))),
+            )))?;
+        }
+    }
+    Ok(())
+}
+
+/// Most bins the automatic rules choose, and the most [`BinRule::edges`] accepts.
+pub const MAX_BINS: usize = 5_000;
+
+pub fn bins_sturges(n: usize) -> usize {
+    let n = n.max(1) as f64;
+    (n.log2() + 1.0).ceil().max(1.0) as usize
+}
+
+pub fn bins_sqrt(n: usize) -> usize {
+    ((n.max(1) as f64).sqrt().ceil().clamp(1.0, MAX_BINS as f64)) as usize
+}
+
+/// Scott's bin count, at most [`MAX_BINS`]; Sturges when the data have no spread.
+pub fn bins_scott(data: &[f64]) -> usize {
+    let n = data.len().max(2) as f64;
+    let (mn, mx) = data_min_max(data);
+    let sd = std_dev(data);
+    if sd.is_nan() || sd <= 0.0 {
+        return bins_sturges(data.len());
+    }
+    let bw = 3.5 * sd / n.powf(1.0 / 3.0);
+    ((mx - mn) / bw).ceil().clamp(1.0, MAX_BINS as f64) as usize
+}
+
+/// Freedman–Diaconis bin count, at most [`MAX_BINS`]; `method` selects the quantile
+/// definition used for the IQR. Sturges when the IQR is zero (mostly repeated values).
+pub fn bins_fd(data: &[f64], method: QuantileMethod) -> usize {
+    let n = data.len().max(2) as f64;
+    let (mn, mx) = data_min_max(data);
+    let i = iqr(data, method);
+    if i.is_nan() || i <= 0.0 {
+        return bins_sturges(data.len());
+    }
+    let bw = 2.0 * i / n.powf(1.0 / 3.0);
+    ((mx - mn) / bw).ceil().clamp(1.0, MAX_BINS as f64) as usize
+}
+
+pub fn bins_auto(data: &[f64]) -> usize {
+    let k1 = bins_sturges(data.len());
+    let k2 = bins_fd(data, QuantileMethod::default());
+    k1.max(k2).max(1)
+}
+
+/// A rule for choosing bin edges from the data.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BinRule {
+    Auto,
+    Sturges,
+    Sqrt,
+    Scott,
+    Fd,
+    /// A fixed number of bins.
+    Count(usize),
+    /// Bins of a fixed width, aligned to multiples of it.
+    Width(f64),
+}
+
+impl BinRule {
+    /// Evenly spaced edges spanning the data (a unit range around constant data).
+    /// Fails when a fixed count or width asks for more than [`MAX_BINS`] bins.
+    pub fn edges(self, data: &[f64]) -> Result<Vec<f64>, Box<dyn Error>> {
+        let (mut mn, mut mx) = data_min_max(data);
+        if !mn.is_finite() || !mx.is_finite() {
+            (mn, mx) = (0.0, 1.0);
+        }
+        if mx <= mn {
+            (mn, mx) = (mn - 0.5, mx + 0.5);
+        }
+        let bins = match self {
+            BinRule::Auto => bins_auto(data),
+            BinRule::Sturges => bins_sturges(data.len()),
+            BinRule::Sqrt => bins_sqrt(data.len()),
+            BinRule::Scott => bins_scott(data),
+            BinRule::Fd => bins_fd(data, QuantileMethod::default()),
+            BinRule::Count(n) if n > MAX_BINS => return Err(format!("{n} bins is more than the {MAX_BINS} allowed").into()),
+            BinRule::Count(n) => n.max(1),
+            BinRule::Width(w) => {
+                let w = w.abs().max(1e-12);
+                let (lo, hi) = ((mn / w).floor(), (mx / w).floor() + 1.0);
+                if hi - lo > MAX_BINS as f64 {
+                    return Err(format!(
+                        "Bin width {w:?} gives {} bins over {mn}..{mx}, more than the {MAX_BINS} allowed",
+                        hi - lo
+                    )
+                    .into());
+                }
+                return Ok((0..=(hi - lo) as usize).map(|i| (lo + i as f64) * w).collect());
+            }
+        };
+        Ok(edges_from_bins(mn, mx, bins))
+    }
+}
+
+impl FromStr for BinRule {
+    type Err = Box<dyn Error>;
+
+    /// `auto`, `sturges`, `sqrt`, `scott`, `fd`, a bin count (`30`) or `width=0.5`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_lowercase();
+        if let Some(w) = s.strip_prefix("width=") {
+            let w: f64 = w.parse()?;
+            if !(w > 0.0 && w.is_finite()) {
+                return Err("Bin width must be positive".into());
+            }
+            return Ok(BinRule::Width(w));
+        }
+        if let Ok(n) = s.parse::<usize>() {
+            return Ok(BinRule::Count(n.max(1)));
+        }
+        match s.as_str() {
+            "auto" => Ok(BinRule::Auto),
+            "sturges" => Ok(BinRule::Sturges),
+            "sqrt" => Ok(BinRule::Sqrt),
+            "scott" => Ok(BinRule::Scott),
+            "fd" | "freedman-diaconis" => Ok(BinRule::Fd),
+            other => Err(format!("Unknown bin rule '{}' (auto, sturges, sqrt, scott, fd, N or width=W)", other).into()),
+        }
+    }
+}
+
+/// How several datasets sharing the same bin edges are combined in one chart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistLayout {
+    /// Bars drawn on top of each other in dataset order, in the datasets' own
+    /// styles: give them an alpha (`color.mix(0.5)`) so later bars don't hide earlier ones.
+    Overlay,
+    /// Bars stacked in dataset order.
+    Stacked,
+    /// Bars placed side by side within each bin.
+    Dodged,
+    /// Unfilled step outlines.
+    Step,
+}
+
+impl FromStr for HistLayout {
+    type Err = Box<dyn Error>;
+
+    /// `overlay`, `stacked`, `dodged` or `step`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "overlay" => Ok(HistLayout::Overlay),
+            "stacked" | "stack" => Ok(HistLayout::Stacked),
+            "dodged" | "dodge" => Ok(HistLayout::Dodged),
+            "step" => Ok(HistLayout::Step),
+            other => Err(format!("Unknown histogram layout '{}' (overlay, stacked, dodged, step)", other).into()),
+        }
+    }
+}
+
+/// One dataset of a multi-dataset histogram: bar heights on the shared edges.
+#[derive(Clone, Debug)]
+pub struct HistDataset {
+    pub label: String,
+    pub heights: Vec<f64>,
+    pub style: ShapeStyle,
+}
+
+impl HistDataset {
+    pub fn new<S: Into<ShapeStyle>>(label: &str, heights: Vec<f64>, style: S) -> Self {
+        Self {
+            label: label.to_string(),
+            heights,
+            style: style.into(),
+        }
+    }
+
+    /// Bins `data` on `edges` and normalizes the counts.
+    pub fn from_data<S: Into<ShapeStyle>>(label: &str, data: &[f64], edges: &[f64], norm: Normalization, style: S) -> Self {
+        let counts = counts_from_edges(data, edges);
+        Self::new(label, normalized_heights(&counts, edges, norm), style)
+    }
+}
+
+/// Largest bar top of the combined histogram, for a common y range.
+pub fn layout_ymax(datasets: &[HistDataset], layout: HistLayout) -> f64 {
+    let nb = datasets.iter().map(|d| d.heights.len()).max().unwrap_or(0);
+    let ymax = match layout {
+        HistLayout::Stacked => (0..nb)
+            .map(|i| datasets.iter().filter_map(|d| d.heights.get(i)).sum::<f64>())
+            .fold(0.0f64, f64::max),
+        _ => datasets
+            .iter()
+            .flat_map(|d| d.heights.iter().cloned())
+            .fold(0.0f64, f64::max),
+    };
+    ymax.max(1e-12)
+}
+
+/// Draws several datasets on shared `edges` in a single chart with a legend.
+/// Bars are filled with each dataset's style as given; no transparency is added.
+pub fn draw_histograms<DB>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    x_label: &str,
+    y_label: &str,
+    edges: &[f64],
+    datasets: &[HistDataset],
+    layout: HistLayout,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let (xmin, xmax) = (*edges.first().unwrap(), *edges.last().unwrap());
+    let ymax = layout_ymax(datasets, layout);
+
+    let theme = theme();
+    let mut chart = theme
+        .chart(root)
+        .caption(title, theme.title_font())
+        .build_cartesian_2d(xmin..xmax, 0.0f64..(ymax * 1.1))?;
+
+    theme
+        .mesh(&mut chart.configure_mesh())
+        .x_desc(x_label)
+        .y_desc(y_label)
+        .draw()?;
+
+    let nb = edges.len().saturating_sub(1);
+    let nd = datasets.len().max(1);
+    let mut base = vec![0.0f64; nb];
+
+    for (k, d) in datasets.iter().enumerate() {
+        let heights = &d.heights[..d.heights.len().min(nb)];
+        let anno = match layout {
+            HistLayout::Overlay => chart.draw_series(
+                heights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &h)| Rectangle::new([(edges[i], 0.0), (edges[i + 1], h)], d.style.filled())),
+            )?,
+            HistLayout::Stacked => {
+                let bars = heights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &h)| Rectangle::new([(edges[i], base[i]), (edges[i + 1], base[i] + h)], d.style.filled()))
+                    .collect::<Vec<_>>();
+                for (b, h) in base.iter_mut().zip(heights) {
+                    *b += h;
+                }
+                chart.draw_series(bars)?
+            }
+            HistLayout::Dodged => chart.draw_series(heights.iter().enumerate().map(|(i, &h)| {
+                let w = (edges[i + 1] - edges[i]) / nd as f64;
+                let x0 = edges[i] + w * k as f64;
+                Rectangle::new([(x0, 0.0), (x0 + w, h)], d.style.filled())
+            }))?,
+            HistLayout::Step => chart.draw_series(std::iter::once(PathElement::new(
+                step_outline(edges, heights),
+                d.style.stroke_width(d.style.stroke_width.max(pt(0.5))),
+            )))?,
+        };
+
+        let style = d.style;
+        let (w, h, thin) = (pt(6.0) as i32, pt(2.0) as i32, pt(0.25) as i32);
+        anno.label(d.label.as_str()).legend(move |(x, y)| match layout {
+            HistLayout::Step => Rectangle::new([(x, y - thin), (x + w, y + thin)], style.filled()),
+            _ => Rectangle::new([(x, y - h), (x + w, y + h)], style.filled()),
+        });
+    }
+
+    theme
+        .legend(&mut chart.configure_series_labels())
+        .position(SeriesLabelPosition::UpperRight)
+        .draw()?;
+
+    Ok(())
+}
+
+/// Closed step outline (stairs) of a histogram, starting and ending on the baseline.
+pub fn step_outline(edges: &[f64], heights: &[f64]) -> Vec<(f64, f64)> {
+    let mut pts = Vec::with_capacity(2 * heights.len() + 2);
+    if heights.is_empty() {
+        return pts;
+    }
+    pts.push((edges[0], 0.0));
+    for (i, &h) in heights.iter().enumerate() {
+        pts.push((edges[i], h));
+        pts.push((edges[i + 1], h));
+    }
+    pts.push((edges[heights.len()], 0.0));
+    pts
+}
+
+/// Expected count in each bin when `total` samples follow `pdf`, by integrating
+/// the PDF over every edge interval (composite Simpson rule).
+pub fn expected_counts<F>(pdf: F, edges: &[f64], total: f64) -> Vec<f64>
+where
+    F: Fn(f64) -> f64,
+{
+    const STEPS: usize = 32; // even
+    edges
+        .windows(2)
+        .map(|w| {
+            let h = (w[1] - w[0]) / STEPS as f64;
+            let inner = (1..STEPS)
+                .map(|k| {
+                    let c = if k % 2 == 1 { 4.0 } else { 2.0 };
+                    c * pdf(w[0] + k as f64 * h)
+                })
+                .sum::<f64>();
+            total * h / 3.0 * (pdf(w[0]) + inner + pdf(w[1]))
+        })
+        .collect()
+}
+
+/// Quantity shown in the lower panel of a data-vs-model histogram.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Residual {
+    /// Observed / expected, with error √N / expected.
+    Ratio,
+    /// (observed − expected) / √expected.
+    Pull,
+}
+
+/// Per-bin residual value and its uncertainty (the uncertainty is 1 for pulls).
+/// Bins with no expected count get `NaN`.
+pub fn residuals(observed: &[usize], expected: &[f64], kind: Residual) -> Vec<(f64, f64)> {
+    observed
+        .iter()
+        .zip(expected)
+        .map(|(&o, &e)| {
+            if e <= 0.0 {
+                return (f64::NAN, f64::NAN);
+            }
+            let o = o as f64;
+            match kind {
+                Residual::Ratio => (o / e, o.sqrt() / e),
+                Residual::Pull => ((o - e) / e.sqrt(), 1.0),
+            }
+        })
+        .collect()
+}
+
+/// Histogram of `counts` against a model `pdf` in a main panel, with a data/model
+/// ratio or pull panel underneath sharing the x axis. Expected counts come from
+/// [`expected_counts`] with the observed total; both are scaled by `norm` in the
+/// main panel, where the data carry Poisson error bars and the model is a step line.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_histogram_with_residuals<DB>(
+    root: &DrawingArea<DB, Shift>,
+    title: &str,
+    x_label: &str,
+    edges: &[f64],
+    counts: &[usize],
+    pdf: &dyn Fn(f64) -> f64,
+    norm: Normalization,
+    kind: Residual,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let total = counts.iter().sum::<usize>() as f64;
+    let expected = expected_counts(pdf, edges, total);
+    let scale = |i: usize| norm_scale(norm, total.max(1.0), edges[i + 1] - edges[i]);
+    let heights = normalized_heights(counts, edges, norm);
+    let errors = poisson_errors(counts, edges, norm);
+    let model = expected.iter().enumerate().map(|(i, &e)| e * scale(i)).collect::<Vec<_>>();
+
+    let (xmin, xmax) = (*edges.first().unwrap(), *edges.last().unwrap());
+    let ymax = heights
+        .iter()
+        .zip(&errors)
+        .map(|(h, e)| h + e)
+        .chain(model.iter().cloned())
+        .fold(1e-12f64, f64::max);
+
+    let h = root.dim_in_pixel().1;
+    let (upper, lower) = root.split_vertically(h * 7 / 10);
+    let y_label = norm.axis_label();
+
+    let theme = theme();
+    let mut chart = theme
+        .chart(&upper)
+        .caption(title, theme.title_font())
+        .margin_bottom(0)
+        .x_label_area_size(pt(2.5))
+        .build_cartesian_2d(xmin..xmax, 0.0f64..(ymax * 1.1))?;
+    theme
+        .mesh(&mut chart.configure_mesh())
+        .y_desc(y_label)
+        .x_label_formatter(&|_| String::new())
+        .draw()?;
+
+    let (data_color, model_color) = (theme.color(0), theme.color(1));
+    let data_style = HistStyle::bars(data_color.mix(0.45));
+    draw_hist_bins(&mut chart, edges, &heights, &data_style)?;
+    draw_hist_errors(&mut chart, edges, &heights, &errors, &data_style)?;
+    let (w, h) = (pt(6.0) as i32, pt(2.0) as i32);
+    chart
+        .draw_series(std::iter::empty::<Rectangle<(f64, f64)>>())?
+        .label("Data (±√N)")
+        .legend(move |(x, y)| Rectangle::new([(x, y - h), (x + w, y + h)], data_color.mix(0.45).filled()));
+    chart
+        .draw_series(std::iter::once(PathElement::new(step_outline(edges, &model), model_color.stroke_width(theme.stroke()))))?
+        .label("Model")
+        .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + w, y)], model_color.stroke_width(theme.stroke())));
+    theme
+        .legend(&mut chart.configure_series_labels())
+        .position(SeriesLabelPosition::UpperRight)
+        .draw()?;
+
+    // Lower panel: same x range and label area widths so the axes line up
+    let res = residuals(counts, &expected, kind);
+    let finite = res.iter().filter(|(v, e)| v.is_finite() && e.is_finite());
+    let (center, y_desc) = match kind {
+        Residual::Ratio => (1.0, "Data / model"),
+        Residual::Pull => (0.0, "Pull"),
+    };
+    let span = finite.map(|(v, _)| (v - center).abs()).fold(0.0f64, f64::max) * 1.1;
+    // Ratios of nearly empty tail bins are clipped rather than stretching the panel
+    let (ylo, yhi) = match kind {
+        Residual::Ratio => {
+            let span = span.clamp(0.5, 1.5);
+            ((center - span).max(0.0), center + span)
+        }
+        Residual::Pull => (-span.max(3.0), span.max(3.0)),
+    };
+
+    let mut res_chart = theme
+        .chart(&lower)
+        .margin_top(0)
+        .build_cartesian_2d(xmin..xmax, ylo..yhi)?;
+    theme
+        .mesh(&mut res_chart.configure_mesh())
+        .x_desc(x_label)
+        .y_desc(y_desc)
+        .y_labels(5)
+        .draw()?;
+    res_chart.draw_series(std::iter::once(PathElement::new(
+        vec![(xmin, center), (xmax, center)],
+        theme.foreground.stroke_width(pt(0.5)),
+    )))?;
+
+    let ok = |i: &usize| res[*i].0.is_finite();
+    match kind {
+        Residual::Ratio => {
+            res_chart.draw_series((0..res.len()).filter(ok).map(|i| {
+                let (v, e) = res[i];
+                ErrorBar::new_vertical((edges[i] + edges[i + 1]) / 2.0, v - e, v, v + e, theme.foreground.stroke_width(pt(0.5)), pt(2.5))
+            }))?;
+        }
+        Residual::Pull => {
+            res_chart.draw_series((0..res.len()).filter(ok).map(|i| {
+                Rectangle::new([(edges[i], 0.0), (edges[i + 1], res[i].0)], data_color.mix(0.55).filled())
+            }))?;
+            for band in [-2.0, 2.0] {
+                res_chart.draw_series(DashedLineSeries::new(
+                    vec![(xmin, band), (xmax, band)],
+                    pt(2.0),
+                    pt(1.5),
+                    theme.foreground.mix(0.6).stroke_width(pt(0.5)),
+                ))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NORMS: [Normalization; 4] =
+        [Normalization::Count, Normalization::CountDensity, Normalization::Probability, Normalization::Pdf];
+
+    #[test]
+    fn unit_weight_errors_match_poisson_errors() {
+        let data = [0.1, 0.2, 0.25, 0.7, 1.4, 1.5, 1.6, 1.9, 2.5, 2.9];
+        let edges = [0.0, 0.5, 1.0, 2.0, 3.0];
+        let counts = counts_from_edges(&data, &edges);
+        let (sum_w, sum_w2) = weighted_counts_from_edges(&data, &[1.0; 10], &edges);
+        for norm in NORMS {
+            let (heights, errors) = weighted_heights_and_errors(&sum_w, &sum_w2, &edges, norm);
+            assert_eq!(heights, normalized_heights(&counts, &edges, norm), "{norm:?}");
+            for (a, b) in errors.iter().zip(poisson_errors(&counts, &edges, norm)) {
+                assert!((a - b).abs() < 1e-12, "{norm:?}: {a} vs {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn repeated_values_fall_back_to_sturges() {
+        // IQR 0: Freedman–Diaconis has no bin width
+        let data = [1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 2.0, 100.0];
+        assert_eq!(bins_fd(&data, QuantileMethod::default()), bins_sturges(9));
+        assert_eq!(bins_auto(&data), 5);
+        assert_eq!(BinRule::Auto.edges(&data).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn single_value_gets_one_bin() {
+        for rule in [BinRule::Auto, BinRule::Fd, BinRule::Scott, BinRule::Sturges, BinRule::Sqrt] {
+            let edges = rule.edges(&[42.0]).unwrap();
+            assert_eq!(edges, [41.0, 43.0], "{rule:?}");
+        }
+    }
+
+    #[test]
+    fn automatic_rules_are_capped() {
+        // A tiny IQR against a huge range
+        let mut data = (0..1000).map(|i| i as f64 * 1e-9).collect::<Vec<_>>();
+        data.push(1e9);
+        assert_eq!(bins_fd(&data, QuantileMethod::default()), MAX_BINS);
+        assert_eq!(BinRule::Auto.edges(&data).unwrap().len(), MAX_BINS + 1);
+    }
+
+    #[test]
+    fn oversized_width_or_count_is_an_error() {
+        let data = [0.0, 100.0];
+        let rule = "width=1e-9".parse::<BinRule>().unwrap();
+        assert!(rule.edges(&data).is_err());
+        assert_eq!(BinRule::Width(0.5).edges(&[0.0, 10.0]).unwrap().len(), 22);
+        assert!("100000".parse::<BinRule>().unwrap().edges(&data).is_err());
+        assert_eq!(BinRule::Count(MAX_BINS).edges(&data).unwrap().len(), MAX_BINS + 1);
+    }
+
+    #[test]
+    fn weighted_fraction_error_vanishes_for_a_single_bin() {
+        // All the weight in one bin: its fraction is exactly 1
+        let (sum_w, sum_w2) = ([0.0, 3.5, 0.0], [0.0, 6.25, 0.0]);
+        let edges = [0.0, 1.0, 2.0, 3.0];
+        let (heights, errors) = weighted_heights_and_errors(&sum_w, &sum_w2, &edges, Normalization::Probability);
+        assert_eq!(heights, [0.0, 1.0, 0.0]);
+        assert_eq!(errors, [0.0, 0.0, 0.0]);
+    }
+}
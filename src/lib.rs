@@ -0,0 +1,25 @@
+pub mod boxplot;
+pub mod category;
+pub mod cli;
+pub mod color;
+pub mod contour;
+pub mod data;
+pub mod decimate;
+pub mod ecdf;
+pub mod fit;
+pub mod fonts;
+pub mod gof;
+pub mod heatmap;
+pub mod histogram;
+pub mod line;
+pub mod markers;
+pub mod render;
+pub mod rng;
+pub mod spec;
+pub mod stats;
+pub mod stream;
+pub mod ticks;
+pub mod theme;
+pub mod time;
+pub mod units;
+pub mod value;
@@ -0,0 +1,632 @@
+use crate::decimate::{self, Decimation};
+use crate::markers::{MarkerPlacement, MarkerShape, Markers};
+use crate::ticks::TickFormat;
+use crate::time::TimeAxis;
+use crate::theme::theme;
+use crate::units::pt;
+use plotters::coord::ranged1d::{AsRangedCoord, Ranged, ValueFormatter};
+use plotters::chart::{DualCoordChartContext, SeriesAnno};
+use plotters::coord::types::RangedCoordf64;
+use plotters::coord::{CoordTranslate, Shift};
+use plotters::prelude::*;
+use std::error::Error;
+use std::ops::Range;
+use std::str::FromStr;
+
+pub type Chart2d<'a, DB> = ChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>>;
+
+/// A [`Chart2d`] with a secondary y axis sharing its x axis.
+pub type DualChart2d<'a, DB> =
+    DualCoordChartContext<'a, DB, Cartesian2d<RangedCoordf64, RangedCoordf64>, Cartesian2d<RangedCoordf64, RangedCoordf64>>;
+
+/// Applies the current theme's mesh styling (grid, axes, labels, axis captions) to a floating-point chart.
+pub fn draw_mesh_f64<DB>(chart: &mut Chart2d<'_, DB>, x_desc: &str, y_desc: &str) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    theme().mesh(&mut chart.configure_mesh()).x_desc(x_desc).y_desc(y_desc).draw()?;
+    Ok(())
+}
+
+/// [`draw_mesh_f64`] with tick label formats for the x and y axes (`None` keeps the default labels).
+pub fn draw_mesh_f64_with<DB>(
+    chart: &mut Chart2d<'_, DB>,
+    x_desc: &str,
+    y_desc: &str,
+    x_fmt: Option<&TickFormat>,
+    y_fmt: Option<&TickFormat>,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let fx = |v: &f64| x_fmt.map_or_else(|| format!("{v}"), |f| f.format(*v));
+    let fy = |v: &f64| y_fmt.map_or_else(|| format!("{v}"), |f| f.format(*v));
+
+    let mut mesh = chart.configure_mesh();
+    theme().mesh(&mut mesh).x_desc(x_desc).y_desc(y_desc);
+    if x_fmt.is_some() {
+        mesh.x_label_formatter(&fx);
+    }
+    if y_fmt.is_some() {
+        mesh.y_label_formatter(&fy);
+    }
+    mesh.draw()?;
+    Ok(())
+}
+
+/// [`draw_mesh_f64`] for a chart with a secondary y axis: the primary mesh from `x`
+/// and `y`, and the right-hand axis captioned and labelled from `y2`.
+pub fn draw_mesh_f64_dual<DB>(
+    chart: &mut DualChart2d<'_, DB>,
+    x: &AxisSpec<'_>,
+    y: &AxisSpec<'_>,
+    y2: &AxisSpec<'_>,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    draw_axes_mesh(chart, x, y)?;
+    let fy2 = |v: &f64| y2.label(v);
+    let mut axes = chart.configure_secondary_axes();
+    theme().secondary_axes(&mut axes).y_desc(y2.desc.as_str());
+    if y2.has_labels() {
+        axes.y_label_formatter(&fy2);
+    }
+    axes.draw()?;
+    Ok(())
+}
+
+// The themed mesh with captions and tick labels from the axis specs, on any scale
+fn draw_axes_mesh<DB, X, Y>(
+    chart: &mut ChartContext<'_, DB, Cartesian2d<X, Y>>,
+    x: &AxisSpec<'_>,
+    y: &AxisSpec<'_>,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    X: Ranged<ValueType = f64> + ValueFormatter<f64>,
+    Y: Ranged<ValueType = f64> + ValueFormatter<f64>,
+{
+    let (fx, fy) = (|v: &f64| x.label(v), |v: &f64| y.label(v));
+    let mut mesh = chart.configure_mesh();
+    theme().mesh(&mut mesh).x_desc(x.desc.as_str()).y_desc(y.desc.as_str());
+    if x.has_labels() {
+        mesh.x_label_formatter(&fx);
+    }
+    if y.has_labels() {
+        mesh.y_label_formatter(&fy);
+    }
+    mesh.draw()?;
+    Ok(())
+}
+
+/// Draws `points` as a line, decimating first when the series has far more
+/// points than the plotting area has pixel columns.
+pub fn draw_line<'a, 'b, DB, CT, S>(
+    chart: &'b mut ChartContext<'a, DB, CT>,
+    points: &[(f64, f64)],
+    style: S,
+) -> Result<&'b mut SeriesAnno<'a, DB>, Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    CT: CoordTranslate<From = (f64, f64)>,
+    S: Into<ShapeStyle>,
+{
+    draw_line_with(chart, points, style, Some(Decimation::default()))
+}
+
+/// Like [`draw_line`], with an explicit decimation method (`None` draws every point).
+pub fn draw_line_with<'a, 'b, DB, CT, S>(
+    chart: &'b mut ChartContext<'a, DB, CT>,
+    points: &[(f64, f64)],
+    style: S,
+    decimation: Option<Decimation>,
+) -> Result<&'b mut SeriesAnno<'a, DB>, Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    CT: CoordTranslate<From = (f64, f64)>,
+    S: Into<ShapeStyle>,
+{
+    let reduced = {
+        let area = chart.plotting_area();
+        let width = area.dim_in_pixel().0;
+        match decimation {
+            Some(_) if !decimate::needs_decimation(points.len(), width) => None,
+            Some(Decimation::Lttb) => Some(decimate::lttb(points, 2 * width as usize)),
+            Some(Decimation::MinMax) => Some(decimate::min_max_by_column(points, |p| area.map_coordinate(p).0)),
+            None => None,
+        }
+    };
+
+    let points = reduced.as_deref().unwrap_or(points);
+    Ok(chart.draw_series(LineSeries::new(points.iter().copied(), style))?)
+}
+
+/// Which y axis a series is plotted against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum YAxis {
+    #[default]
+    Primary,
+    Secondary,
+}
+
+/// A labelled line series for the multi-series helpers.
+#[derive(Clone, Debug)]
+pub struct LineSpec {
+    pub label: String,
+    pub points: Vec<(f64, f64)>,
+    pub style: ShapeStyle,
+    pub axis: YAxis,
+    pub markers: Option<Markers>,
+    pub dash: LineDash,
+}
+
+impl LineSpec {
+    pub fn new<S: Into<ShapeStyle>>(label: &str, points: Vec<(f64, f64)>, style: S) -> Self {
+        Self {
+            label: label.to_string(),
+            points,
+            style: style.into(),
+            axis: YAxis::Primary,
+            markers: None,
+            dash: LineDash::Solid,
+        }
+    }
+
+    pub fn axis(mut self, axis: YAxis) -> Self {
+        self.axis = axis;
+        self
+    }
+
+    pub fn markers(mut self, markers: Markers) -> Self {
+        self.markers = Some(markers);
+        self
+    }
+
+    pub fn dash(mut self, dash: LineDash) -> Self {
+        self.dash = dash;
+        self
+    }
+}
+
+/// How the line of a [`LineSpec`] is stroked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum LineDash {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+    /// Markers only.
+    NoLine,
+}
+
+/// A matplotlib-style format string such as `r--o`: an optional color letter
+/// (`b g r c m y k w`), line style (`-`, `--`, `:`) and marker
+/// (`o ^ x s d *`), in any order. A marker without a line style means markers only.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct LineFormat {
+    pub color: Option<RGBColor>,
+    pub dash: Option<LineDash>,
+    pub marker: Option<MarkerShape>,
+}
+
+impl FromStr for LineFormat {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fmt = LineFormat::default();
+        let mut rest = s.trim();
+        while !rest.is_empty() {
+            let (dash, len) = if rest.starts_with("--") {
+                (Some(LineDash::Dashed), 2)
+            } else if rest.starts_with("-.") {
+                return Err("Dash-dot lines ('-.') are not supported; use '-', '--' or ':'".into());
+            } else if rest.starts_with('-') {
+                (Some(LineDash::Solid), 1)
+            } else if rest.starts_with(':') {
+                (Some(LineDash::Dotted), 1)
+            } else {
+                (None, 1)
+            };
+            if dash.is_some() {
+                fmt.dash = dash;
+                rest = &rest[len..];
+                continue;
+            }
+            let c = rest.chars().next().unwrap();
+            let color = match c {
+                'b' => Some(BLUE),
+                'g' => Some(GREEN),
+                'r' => Some(RED),
+                'c' => Some(CYAN),
+                'm' => Some(MAGENTA),
+                'y' => Some(YELLOW),
+                'k' => Some(BLACK),
+                'w' => Some(WHITE),
+                _ => None,
+            };
+            let marker = match c {
+                'o' => Some(MarkerShape::Circle),
+                '^' => Some(MarkerShape::Triangle),
+                'x' => Some(MarkerShape::Cross),
+                's' => Some(MarkerShape::Square),
+                'd' | 'D' => Some(MarkerShape::Diamond),
+                '*' => Some(MarkerShape::Star),
+                _ => None,
+            };
+            match (color, marker) {
+                (Some(color), _) => fmt.color = Some(color),
+                (_, Some(marker)) => fmt.marker = Some(marker),
+                _ => return Err(format!("Unknown character '{}' in line format '{}'", c, s).into()),
+            }
+            rest = &rest[c.len_utf8()..];
+        }
+        Ok(fmt)
+    }
+}
+
+impl LineFormat {
+    /// The line style implied by the format: markers alone mean no line.
+    pub fn line_dash(&self) -> LineDash {
+        match (self.dash, self.marker) {
+            (Some(dash), _) => dash,
+            (None, Some(_)) => LineDash::NoLine,
+            (None, None) => LineDash::Solid,
+        }
+    }
+
+    /// A series drawn in this format with lines `width` points wide, in
+    /// `fallback` when the format names no color. Long series get markers on
+    /// every n-th point only (at most [`MAX_MARKERS`]).
+    pub fn series(&self, label: &str, points: Vec<(f64, f64)>, fallback: RGBColor, width: f64) -> LineSpec {
+        let color = self.color.unwrap_or(fallback);
+        let n = points.len();
+        let spec = LineSpec::new(label, points, color.stroke_width(pt(width))).dash(self.line_dash());
+        match self.marker {
+            Some(shape) => {
+                let placement = MarkerPlacement::at_most(n, MAX_MARKERS);
+                spec.markers(Markers::new(shape, placement, color.filled()).size(width + 1.0))
+            }
+            None => spec,
+        }
+    }
+}
+
+/// Most markers [`LineFormat::series`] puts on one series.
+pub const MAX_MARKERS: usize = 200;
+
+/// Axis range covering `values` with 5% padding (multiplicative on log axes,
+/// where non-positive values are ignored). `limits` override either end.
+pub fn padded_range<I>(values: I, scale: AxisScale, limits: (Option<f64>, Option<f64>)) -> Result<Range<f64>, Box<dyn Error>>
+where
+    I: IntoIterator<Item = f64>,
+{
+    let log = scale == AxisScale::Log;
+    let (min, max) = values
+        .into_iter()
+        .filter(|v| v.is_finite() && (!log || *v > 0.0))
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(a, b), v| (a.min(v), b.max(v)));
+    let (min, max) = if min > max {
+        if log { (1.0, 10.0) } else { (0.0, 1.0) }
+    } else if log {
+        let pad = if max > min { (max / min).powf(0.05) } else { 2.0 };
+        (min / pad, max * pad)
+    } else {
+        let pad = if max > min { 0.05 * (max - min) } else { 0.5f64.max(0.05 * min.abs()) };
+        (min - pad, max + pad)
+    };
+    let (lo, hi) = (limits.0.unwrap_or(min), limits.1.unwrap_or(max));
+    if lo >= hi {
+        return Err(format!("Empty axis range {}..{}", lo, hi).into());
+    }
+    Ok(lo..hi)
+}
+
+/// Range, caption and optional tick label formatting for one axis.
+pub struct AxisSpec<'f> {
+    pub range: Range<f64>,
+    pub desc: String,
+    pub formatter: Option<&'f dyn Fn(&f64) -> String>,
+    pub tick_format: Option<TickFormat>,
+    pub scale: AxisScale,
+}
+
+/// How values are mapped along an axis of [`draw_lines`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum AxisScale {
+    #[default]
+    Linear,
+    /// Base-10 logarithmic; the range and plotted values must be positive.
+    Log,
+    /// Unix timestamps, labelled with dates through [`TimeAxis`].
+    Time,
+}
+
+impl<'f> AxisSpec<'f> {
+    pub fn new(range: Range<f64>, desc: &str) -> Self {
+        Self {
+            range,
+            desc: desc.to_string(),
+            formatter: None,
+            tick_format: None,
+            scale: AxisScale::Linear,
+        }
+    }
+
+    pub fn formatter(mut self, f: &'f dyn Fn(&f64) -> String) -> Self {
+        self.formatter = Some(f);
+        self
+    }
+
+    pub fn tick_format(mut self, fmt: TickFormat) -> Self {
+        self.tick_format = Some(fmt);
+        self
+    }
+
+    pub fn scale(mut self, scale: AxisScale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    fn has_labels(&self) -> bool {
+        self.formatter.is_some() || self.tick_format.is_some()
+    }
+
+    fn label(&self, v: &f64) -> String {
+        match (self.formatter, &self.tick_format) {
+            (Some(f), _) => f(v),
+            (None, Some(fmt)) => fmt.format(*v),
+            (None, None) => format!("{v}"),
+        }
+    }
+}
+
+/// Draws line series against a left (primary) and right (secondary) y axis that
+/// share the x axis. Legend entries are suffixed with the axis they belong to.
+/// All three axes must be linear, and the secondary range must not be empty.
+/// Points with a non-finite coordinate are dropped.
+pub fn draw_dual_axis_lines<DB>(
+    root: &DrawingArea<DB, Shift>,
+    caption: &str,
+    x: &AxisSpec<'_>,
+    y: &AxisSpec<'_>,
+    y2: &AxisSpec<'_>,
+    series: &[LineSpec],
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    for axis in [x, y, y2] {
+        if axis.scale != AxisScale::Linear {
+            return Err(format!("Dual-axis plots need linear axes, '{}' is {:?}", axis.desc, axis.scale).into());
+        }
+    }
+    if !(y2.range.end - y2.range.start).is_normal() {
+        return Err(format!("Secondary axis '{}' needs a non-empty range, got {:?}", y2.desc, y2.range).into());
+    }
+    let theme = theme();
+    // Wider y label areas leave room for formatted tick labels with units
+    let y_area = pt(theme.y_label_area + 2.5);
+    let mut chart = theme
+        .chart(root)
+        .caption(caption, theme.title_font())
+        .y_label_area_size(y_area)
+        .right_y_label_area_size(y_area)
+        .build_cartesian_2d(x.range.clone(), y.range.clone())?
+        .set_secondary_coord(x.range.clone(), y2.range.clone());
+
+    draw_mesh_f64_dual(&mut chart, x, y, y2)?;
+
+    // Secondary series are mapped onto the primary y range so lines, markers and
+    // decimation all go through the same (primary) coordinate system.
+    let to_primary = |v: f64| {
+        let t = (v - y2.range.start) / (y2.range.end - y2.range.start);
+        y.range.start + t * (y.range.end - y.range.start)
+    };
+
+    for s in series {
+        let (points, side) = match s.axis {
+            YAxis::Primary => (s.points.clone(), "left"),
+            YAxis::Secondary => (s.points.iter().map(|&(a, b)| (a, to_primary(b))).collect(), "right"),
+        };
+        let points = points.into_iter().filter(|&(a, b)| a.is_finite() && b.is_finite()).collect::<Vec<_>>();
+        draw_series_spec(&mut chart, s, &points, format!("{} ({side} axis)", s.label))?;
+    }
+
+    theme
+        .legend(&mut chart.configure_series_labels())
+        .position(SeriesLabelPosition::UpperRight)
+        .draw()?;
+
+    Ok(())
+}
+
+/// Draws labelled series on one set of axes whose scales come from `x` and `y`
+/// (linear, log or time). Points that cannot be shown on a log axis are dropped.
+/// `legend = None` hides the legend.
+pub fn draw_lines<DB>(
+    root: &DrawingArea<DB, Shift>,
+    caption: &str,
+    x: &AxisSpec<'_>,
+    y: &AxisSpec<'_>,
+    series: &[LineSpec],
+    legend: Option<SeriesLabelPosition>,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    for axis in [x, y] {
+        if axis.scale == AxisScale::Log && axis.range.start <= 0.0 {
+            return Err(format!("Log axis '{}' needs a positive range, got {:?}", axis.desc, axis.range).into());
+        }
+    }
+    let lines = Lines { root, caption, x, y, series, legend };
+    match x.scale {
+        AxisScale::Linear => lines.with_x(x.range.clone()),
+        AxisScale::Log => lines.with_x(x.range.clone().log_scale()),
+        AxisScale::Time => lines.with_x(TimeAxis::timestamps(x.range.clone())),
+    }
+}
+
+// Arguments of `draw_lines`, carried through the per-axis scale dispatch
+struct Lines<'r, 'a, DB: DrawingBackend> {
+    root: &'r DrawingArea<DB, Shift>,
+    caption: &'a str,
+    x: &'a AxisSpec<'a>,
+    y: &'a AxisSpec<'a>,
+    series: &'a [LineSpec],
+    legend: Option<SeriesLabelPosition>,
+}
+
+impl<DB> Lines<'_, '_, DB>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    fn with_x<X>(&self, xr: X) -> Result<(), Box<dyn Error>>
+    where
+        X: AsRangedCoord<Value = f64>,
+        X::CoordDescType: ValueFormatter<f64>,
+    {
+        match self.y.scale {
+            AxisScale::Linear => self.draw(xr, self.y.range.clone()),
+            AxisScale::Log => self.draw(xr, self.y.range.clone().log_scale()),
+            AxisScale::Time => self.draw(xr, TimeAxis::timestamps(self.y.range.clone())),
+        }
+    }
+
+    fn draw<X, Y>(&self, xr: X, yr: Y) -> Result<(), Box<dyn Error>>
+    where
+        X: AsRangedCoord<Value = f64>,
+        X::CoordDescType: ValueFormatter<f64>,
+        Y: AsRangedCoord<Value = f64>,
+        Y::CoordDescType: ValueFormatter<f64>,
+    {
+        let (x, y) = (self.x, self.y);
+        let theme = theme();
+        // A wider y label area leaves room for log and time tick labels
+        let mut chart = theme
+            .chart(self.root)
+            .caption(self.caption, theme.title_font())
+            .y_label_area_size(pt(theme.y_label_area + 2.5))
+            .build_cartesian_2d(xr, yr)?;
+
+        draw_axes_mesh(&mut chart, x, y)?;
+
+        let visible = |v: f64, axis: &AxisSpec<'_>| v.is_finite() && (axis.scale != AxisScale::Log || v > 0.0);
+        for s in self.series {
+            let points = s
+                .points
+                .iter()
+                .copied()
+                .filter(|&(a, b)| visible(a, x) && visible(b, y))
+                .collect::<Vec<_>>();
+            draw_series_spec(&mut chart, s, &points, s.label.clone())?;
+        }
+
+        if let Some(position) = &self.legend {
+            theme.legend(&mut chart.configure_series_labels()).position(position.clone()).draw()?;
+        }
+        Ok(())
+    }
+}
+
+// Draws one series of `draw_lines` or `draw_dual_axis_lines` in its dash style,
+// with its legend entry and markers.
+fn draw_series_spec<'a, DB, CT>(
+    chart: &mut ChartContext<'a, DB, CT>,
+    s: &LineSpec,
+    points: &[(f64, f64)],
+    label: String,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend + 'a,
+    DB::ErrorType: 'static,
+    CT: CoordTranslate<From = (f64, f64)>,
+{
+    let style = s.style;
+    let anno = match s.dash {
+        LineDash::Solid => draw_line(chart, points, style)?,
+        LineDash::Dashed => chart.draw_series(DashedLineSeries::new(points.iter().copied(), pt(4.0), pt(2.5), style))?,
+        LineDash::Dotted => chart.draw_series(DashedLineSeries::new(
+            points.iter().copied(),
+            style.stroke_width.max(pt(0.5)),
+            3 * style.stroke_width.max(pt(0.5)),
+            style,
+        ))?,
+        LineDash::NoLine => chart.draw_series(std::iter::empty::<Circle<(f64, f64), i32>>())?,
+    };
+    // Legend: the line (if any) with a dot standing in for the marker glyph
+    // (a transparent dot when there are no markers keeps one element type)
+    let dot = s.markers.as_ref().map_or(TRANSPARENT.into(), |m| m.style);
+    let line = match s.dash {
+        LineDash::NoLine => vec![],
+        _ => vec![(0, 0), (pt(7.0) as i32, 0)],
+    };
+    let (dot_x, dot_r) = (pt(3.5) as i32, pt(1.5));
+    anno.label(label).legend(move |c| {
+        EmptyElement::at(c) + PathElement::new(line.clone(), style) + Circle::new((dot_x, 0), dot_r, dot)
+    });
+
+    if let Some(m) = &s.markers {
+        m.draw(chart, points.iter().copied())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::backend::BitMapBackend;
+
+    fn render_dual(y2: &AxisSpec<'_>, series: &[LineSpec]) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut buf = vec![0; 320 * 240 * 3];
+        {
+            let root = BitMapBackend::with_buffer(&mut buf, (320, 240)).into_drawing_area();
+            let (x, y) = (AxisSpec::new(0.0..10.0, "x"), AxisSpec::new(0.0..1.0, "y"));
+            draw_dual_axis_lines(&root, "", &x, &y, y2, series)?;
+            root.present()?;
+        }
+        Ok(buf)
+    }
+
+    fn ramp(dash: LineDash) -> Vec<LineSpec> {
+        let points = (0..=10).map(|i| (i as f64, 10.0 * i as f64)).collect();
+        vec![LineSpec::new("ramp", points, RED.stroke_width(2)).axis(YAxis::Secondary).dash(dash)]
+    }
+
+    #[test]
+    fn dual_axis_honors_dash() {
+        let y2 = AxisSpec::new(0.0..100.0, "y2");
+        let solid = render_dual(&y2, &ramp(LineDash::Solid)).unwrap();
+        let dashed = render_dual(&y2, &ramp(LineDash::Dashed)).unwrap();
+        let none = render_dual(&y2, &ramp(LineDash::NoLine)).unwrap();
+        assert_ne!(solid, dashed);
+        assert_ne!(dashed, none);
+    }
+
+    #[test]
+    fn dual_axis_drops_non_finite_points() {
+        let y2 = AxisSpec::new(0.0..100.0, "y2");
+        let clean = render_dual(&y2, &ramp(LineDash::Dashed)).unwrap();
+        let mut gappy = ramp(LineDash::Dashed);
+        gappy[0].points.insert(3, (2.5, f64::NAN));
+        gappy[0].points.insert(7, (f64::INFINITY, 50.0));
+        assert_eq!(render_dual(&y2, &gappy).unwrap(), clean);
+    }
+
+    #[test]
+    fn dual_axis_rejects_bad_secondary_axis() {
+        let series = ramp(LineDash::Solid);
+        assert!(render_dual(&AxisSpec::new(1.0..100.0, "y2").scale(AxisScale::Log), &series).is_err());
+        assert!(render_dual(&AxisSpec::new(5.0..5.0, "y2"), &series).is_err());
+    }
+}
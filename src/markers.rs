@@ -0,0 +1,253 @@
+use plotters::coord::CoordTranslate;
+use plotters::prelude::*;
+use crate::units::pt;
+use std::error::Error;
+
+/// Marker glyphs that can be attached to a line series.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarkerShape {
+    Circle,
+    Triangle,
+    Cross,
+    Square,
+    Diamond,
+    Star,
+}
+
+/// Where along a series the markers are placed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MarkerPlacement {
+    /// Every n-th data point, starting with the first one.
+    EveryN(usize),
+    /// Explicit data point indices (out-of-range indices are ignored).
+    Indices(Vec<usize>),
+    /// A target number of markers spaced evenly along the on-screen arc length.
+    Count(usize),
+    /// One marker on every data point.
+    DataPoints,
+}
+
+impl MarkerPlacement {
+    /// A marker on every one of `points` data points, or on every n-th one when
+    /// that would exceed `max` markers.
+    pub fn at_most(points: usize, max: usize) -> Self {
+        if points <= max {
+            MarkerPlacement::DataPoints
+        } else {
+            MarkerPlacement::EveryN(points.div_ceil(max.max(1)))
+        }
+    }
+}
+
+/// Marker option for a line series: shape, placement, size (radius in points) and style.
+#[derive(Clone, Debug)]
+pub struct Markers {
+    pub shape: MarkerShape,
+    pub placement: MarkerPlacement,
+    pub size: f64,
+    pub style: ShapeStyle,
+}
+
+impl Markers {
+    pub fn new<S: Into<ShapeStyle>>(shape: MarkerShape, placement: MarkerPlacement, style: S) -> Self {
+        Self {
+            shape,
+            placement,
+            size: 1.25,
+            style: style.into(),
+        }
+    }
+
+    pub fn size(mut self, size: f64) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Marker positions in data coordinates. `project` maps a data point to the
+    /// space in which arc length is measured (pixels when drawing).
+    pub fn positions<P>(&self, points: &[(f64, f64)], project: P) -> Vec<(f64, f64)>
+    where
+        P: Fn(&(f64, f64)) -> (f64, f64),
+    {
+        match &self.placement {
+            MarkerPlacement::EveryN(n) => points.iter().step_by((*n).max(1)).copied().collect(),
+            MarkerPlacement::Indices(idx) => idx.iter().filter_map(|&i| points.get(i).copied()).collect(),
+            MarkerPlacement::DataPoints => points.to_vec(),
+            MarkerPlacement::Count(n) => positions_along_arc(points, *n, project),
+        }
+    }
+
+    /// Draws the markers for `points` on `chart`.
+    pub fn draw<DB, CT, I>(&self, chart: &mut ChartContext<'_, DB, CT>, points: I) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+        CT: CoordTranslate<From = (f64, f64)>,
+        I: IntoIterator<Item = (f64, f64)>,
+    {
+        let points = points.into_iter().collect::<Vec<_>>();
+        let positions = {
+            let area = chart.plotting_area();
+            self.positions(&points, |p| {
+                let (px, py) = area.map_coordinate(p);
+                (px as f64, py as f64)
+            })
+        };
+        draw_marker_series(chart, self.shape, &positions, pt(self.size) as i32, self.style)
+    }
+}
+
+fn positions_along_arc<P>(points: &[(f64, f64)], n: usize, project: P) -> Vec<(f64, f64)>
+where
+    P: Fn(&(f64, f64)) -> (f64, f64),
+{
+    if n == 0 || points.is_empty() {
+        return vec![];
+    }
+    if n == 1 || points.len() == 1 {
+        return vec![points[0]];
+    }
+
+    // Cumulative arc length in projected space
+    let projected = points.iter().map(&project).collect::<Vec<_>>();
+    let mut cum = Vec::with_capacity(points.len());
+    cum.push(0.0);
+    for w in projected.windows(2) {
+        let (dx, dy) = (w[1].0 - w[0].0, w[1].1 - w[0].1);
+        cum.push(cum.last().unwrap() + (dx * dx + dy * dy).sqrt());
+    }
+    let total = *cum.last().unwrap();
+    if total <= 0.0 {
+        return vec![points[0]];
+    }
+
+    let mut out = Vec::with_capacity(n);
+    let mut seg = 0usize;
+    for k in 0..n {
+        let s = total * (k as f64) / ((n - 1) as f64);
+        while seg + 2 < cum.len() && cum[seg + 1] < s {
+            seg += 1;
+        }
+        let len = cum[seg + 1] - cum[seg];
+        let t = if len > 0.0 { ((s - cum[seg]) / len).clamp(0.0, 1.0) } else { 0.0 };
+        let (a, b) = (points[seg], points[seg + 1]);
+        out.push((a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1)));
+    }
+    out
+}
+
+/// Draws one glyph of `shape` at each position, `size` pixels in radius.
+pub fn draw_marker_series<DB, CT>(
+    chart: &mut ChartContext<'_, DB, CT>,
+    shape: MarkerShape,
+    positions: &[(f64, f64)],
+    size: i32,
+    style: ShapeStyle,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+    CT: CoordTranslate<From = (f64, f64)>,
+{
+    let s = size;
+    let at = positions.iter().copied();
+    match shape {
+        MarkerShape::Circle => {
+            chart.draw_series(at.map(|p| Circle::new(p, s, style)))?;
+        }
+        MarkerShape::Triangle => {
+            chart.draw_series(at.map(|p| TriangleMarker::new(p, s, style)))?;
+        }
+        MarkerShape::Cross => {
+            chart.draw_series(at.map(|p| Cross::new(p, s, style)))?;
+        }
+        MarkerShape::Square => {
+            chart.draw_series(at.map(|p| EmptyElement::at(p) + Rectangle::new([(-s, -s), (s, s)], style)))?;
+        }
+        MarkerShape::Diamond | MarkerShape::Star => {
+            let mut outline = glyph_outline(shape, s);
+            if style.filled {
+                chart.draw_series(at.map(|p| EmptyElement::at(p) + Polygon::new(outline.clone(), style)))?;
+            } else {
+                outline.push(outline[0]);
+                chart.draw_series(at.map(|p| EmptyElement::at(p) + PathElement::new(outline.clone(), style)))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Pixel-offset outline for the polygon glyphs
+fn glyph_outline(shape: MarkerShape, s: i32) -> Vec<(i32, i32)> {
+    match shape {
+        MarkerShape::Star => {
+            let outer = s as f64;
+            let inner = outer * 0.45;
+            (0..10)
+                .map(|i| {
+                    let r = if i % 2 == 0 { outer } else { inner };
+                    let a = std::f64::consts::PI * (i as f64) / 5.0 - std::f64::consts::FRAC_PI_2;
+                    ((r * a.cos()).round() as i32, (r * a.sin()).round() as i32)
+                })
+                .collect()
+        }
+        _ => vec![(0, -s), (s, 0), (0, s), (-s, 0)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity(p: &(f64, f64)) -> (f64, f64) {
+        *p
+    }
+
+    fn close(a: (f64, f64), b: (f64, f64)) -> bool {
+        (a.0 - b.0).abs() < 1e-12 && (a.1 - b.1).abs() < 1e-12
+    }
+
+    #[test]
+    fn arc_positions_are_evenly_spaced_along_the_path() {
+        // An L of length 4: 3 along x, then 1 up
+        let points = [(0.0, 0.0), (1.0, 0.0), (3.0, 0.0), (3.0, 1.0)];
+        let out = positions_along_arc(&points, 5, identity);
+        let expected = [(0.0, 0.0), (1.0, 0.0), (2.0, 0.0), (3.0, 0.0), (3.0, 1.0)];
+        assert_eq!(out.len(), 5);
+        for (o, e) in out.iter().zip(expected) {
+            assert!(close(*o, e), "{:?} instead of {:?}", out, expected);
+        }
+    }
+
+    #[test]
+    fn arc_length_is_measured_after_projection() {
+        // Stretching y by 3 makes the vertical leg as long as the horizontal one
+        let points = [(0.0, 0.0), (3.0, 0.0), (3.0, 1.0)];
+        let out = positions_along_arc(&points, 3, |&(x, y)| (x, 3.0 * y));
+        assert!(close(out[1], (3.0, 0.0)), "{:?}", out);
+        assert!(close(out[2], (3.0, 1.0)), "{:?}", out);
+    }
+
+    #[test]
+    fn arc_positions_of_degenerate_inputs() {
+        assert!(positions_along_arc(&[], 4, identity).is_empty());
+        assert!(positions_along_arc(&[(1.0, 2.0), (3.0, 4.0)], 0, identity).is_empty());
+        assert_eq!(positions_along_arc(&[(1.0, 2.0), (3.0, 4.0)], 1, identity), vec![(1.0, 2.0)]);
+        // A path of zero length collapses to its first point
+        assert_eq!(positions_along_arc(&[(1.0, 1.0), (1.0, 1.0)], 3, identity), vec![(1.0, 1.0)]);
+        // Repeated points inside a path do not divide by zero
+        let out = positions_along_arc(&[(0.0, 0.0), (0.0, 0.0), (2.0, 0.0)], 3, identity);
+        assert!(close(out[1], (1.0, 0.0)), "{:?}", out);
+    }
+
+    #[test]
+    fn placements_pick_data_points() {
+        let points = (0..10).map(|i| (i as f64, 0.0)).collect::<Vec<_>>();
+        let style = BLACK.filled();
+        let at = |placement| Markers::new(MarkerShape::Circle, placement, style).positions(&points, identity);
+        assert_eq!(at(MarkerPlacement::EveryN(4)), vec![(0.0, 0.0), (4.0, 0.0), (8.0, 0.0)]);
+        assert_eq!(at(MarkerPlacement::Indices(vec![9, 2, 42])).len(), 2);
+        assert_eq!(at(MarkerPlacement::DataPoints).len(), 10);
+        assert_eq!(MarkerPlacement::at_most(10, 4), MarkerPlacement::EveryN(3));
+    }
+}
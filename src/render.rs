@@ -0,0 +1,187 @@
+// Render targets for the example binaries.
+//
+// Examples draw through a `Canvas`, which either writes PNG files to a
+// directory (what the binaries do) or keeps the pixels in memory (what the
+// golden-image tests do). Both hand the example the same bitmap root type.
+// PNG files record the figure's DPI in their pHYs chunk.
+
+use crate::theme::theme;
+use crate::units::{with_dpi, FigureSize};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// An image drawn into memory: packed 8-bit RGB, row-major.
+#[derive(Clone, Debug)]
+pub struct RenderedImage {
+    pub name: String,
+    pub size: (u32, u32),
+    pub dpi: f64,
+    pub rgb: Vec<u8>,
+}
+
+/// Where examples draw their images.
+#[derive(Debug, Default)]
+pub struct Canvas {
+    out_dir: Option<PathBuf>,
+    images: Vec<RenderedImage>,
+}
+
+impl Canvas {
+    /// Writes every image to `dir/<name>.png`, creating `dir` if needed.
+    pub fn to_dir<P: Into<PathBuf>>(dir: P) -> Result<Self, Box<dyn Error>> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            out_dir: Some(dir),
+            images: Vec::new(),
+        })
+    }
+
+    /// Keeps every image in memory; see [`Canvas::images`].
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Draws one image of `size` on the theme's background, with [`crate::units::dpi`]
+    /// set to its DPI.
+    pub fn draw<F>(&mut self, name: &str, size: FigureSize, f: F) -> Result<(), Box<dyn Error>>
+    where
+        F: FnOnce(&DrawingArea<BitMapBackend<'_>, Shift>) -> Result<(), Box<dyn Error>>,
+    {
+        let pixels = size.pixels();
+        let rgb = render_rgb(pixels, size.dpi, f)?;
+        match &self.out_dir {
+            Some(dir) => write_png(dir.join(format!("{}.png", name)), pixels, size.dpi, &rgb)?,
+            None => self.images.push(RenderedImage {
+                name: name.to_string(),
+                size: pixels,
+                dpi: size.dpi,
+                rgb,
+            }),
+        }
+        Ok(())
+    }
+
+    /// Sleeps for `delay` between frames written to disk; an in-memory canvas
+    /// (the golden tests) returns at once.
+    pub fn pause(&self, delay: Duration) {
+        if self.out_dir.is_some() {
+            thread::sleep(delay);
+        }
+    }
+
+    /// Images drawn so far by an in-memory canvas.
+    pub fn images(&self) -> &[RenderedImage] {
+        &self.images
+    }
+}
+
+/// One entry of an example gallery.
+#[derive(Clone, Copy)]
+pub struct Example {
+    pub name: &'static str,
+    pub run: fn(&mut Canvas) -> Result<(), Box<dyn Error>>,
+    /// Downloads its data, so it is skipped where there is no network.
+    pub needs_network: bool,
+}
+
+impl Example {
+    pub const fn new(name: &'static str, run: fn(&mut Canvas) -> Result<(), Box<dyn Error>>) -> Self {
+        Self {
+            name,
+            run,
+            needs_network: false,
+        }
+    }
+
+    pub const fn online(name: &'static str, run: fn(&mut Canvas) -> Result<(), Box<dyn Error>>) -> Self {
+        Self {
+            name,
+            run,
+            needs_network: true,
+        }
+    }
+}
+
+/// The examples selected by `--example N`: the one named `N` or whose name
+/// starts with `N_`, or all of them for `None`.
+pub fn select_examples<'a>(examples: &'a [Example], only: Option<&str>) -> Result<Vec<&'a Example>, Box<dyn Error>> {
+    let selected = examples
+        .iter()
+        .filter(|ex| only.is_none_or(|n| ex.name == n || ex.name.split('_').next() == Some(n)))
+        .collect::<Vec<_>>();
+    if selected.is_empty() {
+        let names = examples.iter().map(|ex| ex.name).collect::<Vec<_>>().join(", ");
+        return Err(format!("No example {} (available: {})", only.unwrap_or_default(), names).into());
+    }
+    Ok(selected)
+}
+
+/// Something that can draw itself on any backend, so it can be saved as PNG or SVG.
+pub trait Figure {
+    fn draw<DB>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static;
+}
+
+/// Draws `figure` on the theme's background into `path`, choosing the backend
+/// from the extension (`.png` or `.svg`).
+pub fn save_figure<F: Figure>(figure: &F, path: &str, size: FigureSize) -> Result<(), Box<dyn Error>> {
+    let ext = Path::new(path).extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase);
+    match ext.as_deref() {
+        Some("svg") => with_dpi(size.dpi, || -> Result<(), Box<dyn Error>> {
+            let root = SVGBackend::new(path, size.pixels()).into_drawing_area();
+            root.fill(&theme().background)?;
+            figure.draw(&root)?;
+            root.present()?;
+            Ok(())
+        }),
+        Some("png") => {
+            let rgb = render_rgb(size.pixels(), size.dpi, |root| figure.draw(root))?;
+            write_png(path, size.pixels(), size.dpi, &rgb)
+        }
+        _ => Err(format!("Unsupported output '{}': use .png or .svg", path).into()),
+    }
+}
+
+// Draws on a bitmap of `pixels` filled with the theme's background, with the DPI set, returning packed RGB
+fn render_rgb<F>(pixels: (u32, u32), dpi: f64, f: F) -> Result<Vec<u8>, Box<dyn Error>>
+where
+    F: FnOnce(&DrawingArea<BitMapBackend<'_>, Shift>) -> Result<(), Box<dyn Error>>,
+{
+    let mut rgb = vec![0u8; pixels.0 as usize * pixels.1 as usize * 3];
+    with_dpi(dpi, || -> Result<(), Box<dyn Error>> {
+        let root = BitMapBackend::with_buffer(&mut rgb, pixels).into_drawing_area();
+        root.fill(&theme().background)?;
+        f(&root)?;
+        root.present()?;
+        Ok(())
+    })?;
+    Ok(rgb)
+}
+
+/// Writes packed RGB as a PNG whose pHYs chunk records `dpi`.
+pub fn write_png<P: AsRef<Path>>(path: P, pixels: (u32, u32), dpi: f64, rgb: &[u8]) -> Result<(), Box<dyn Error>> {
+    let path = path.as_ref();
+    let file = File::create(path).map_err(|e| format!("Cannot create {}: {}", path.display(), e))?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), pixels.0, pixels.1);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let per_metre = (dpi * 100.0 / crate::units::CM_PER_INCH).round() as u32;
+    encoder.set_pixel_dims(Some(png::PixelDimensions {
+        xppu: per_metre,
+        yppu: per_metre,
+        unit: png::Unit::Meter,
+    }));
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(rgb)?;
+    writer.finish()?;
+    Ok(())
+}
@@ -0,0 +1,69 @@
+// Seeded random number generation for reproducible example data.
+//
+// Every generator asks for a named stream, so each example draws the same
+// numbers no matter which other examples ran first. The seed comes from
+// `set_seed` (e.g. a `--seed` flag), then the `RUST_PLOT_SEED` environment
+// variable, then `DEFAULT_SEED`.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::error::Error;
+use std::sync::RwLock;
+
+pub const DEFAULT_SEED: u64 = 42;
+
+/// Environment variable read when no seed was set explicitly.
+pub const SEED_ENV: &str = "RUST_PLOT_SEED";
+
+static SEED: RwLock<Option<u64>> = RwLock::new(None);
+
+/// Overrides the seed for every stream created afterwards.
+pub fn set_seed(seed: u64) {
+    *SEED.write().unwrap_or_else(|e| e.into_inner()) = Some(seed);
+}
+
+/// The active seed: [`set_seed`], then `RUST_PLOT_SEED`, then [`DEFAULT_SEED`].
+pub fn seed() -> u64 {
+    if let Some(seed) = *SEED.read().unwrap_or_else(|e| e.into_inner()) {
+        return seed;
+    }
+    std::env::var(SEED_ENV)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .unwrap_or(DEFAULT_SEED)
+}
+
+/// Applies a `--seed N` or `--seed=N` flag from `args`, if present, and
+/// returns the remaining arguments.
+pub fn seed_from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let value = if arg == "--seed" {
+            args.next().ok_or("--seed needs a value")?
+        } else if let Some(v) = arg.strip_prefix("--seed=") {
+            v.to_string()
+        } else {
+            rest.push(arg);
+            continue;
+        };
+        set_seed(value.parse().map_err(|_| format!("Invalid seed '{}'", value))?);
+    }
+    Ok(rest)
+}
+
+/// A generator for the stream `name`, derived from the active seed.
+///
+/// ChaCha8 is used rather than `StdRng` because its output is guaranteed not
+/// to change between `rand` releases.
+pub fn seeded_rng(name: &str) -> ChaCha8Rng {
+    // FNV-1a over the name, mixed with the seed by SplitMix64
+    let hash = name
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3));
+    let mut z = seed() ^ hash;
+    z = z.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    ChaCha8Rng::seed_from_u64(z ^ (z >> 31))
+}
@@ -0,0 +1,693 @@
+// Declarative plot specifications.
+//
+// A spec file (TOML or JSON, read by `value`) describes one figure: its size,
+// a grid of panels, named CSV data sources and the series drawn in each panel.
+// It is drawn with the same helpers as the examples: `draw_lines` for line and
+// scatter panels and `draw_histograms` for histogram panels.
+
+use crate::cli::{parse_legend, parse_limits};
+use crate::color::palette;
+use crate::data::{Column, Table};
+use crate::histogram::{draw_histograms, BinRule, HistDataset, HistLayout, Normalization};
+use crate::line::{draw_lines, padded_range, AxisScale, AxisSpec, LineFormat, LineSpec};
+use crate::markers::{MarkerPlacement, MarkerShape};
+use crate::render::Figure;
+use crate::theme::{theme, with_theme, Theme};
+use crate::units::{check_dpi, pt, with_dpi, FigureSize, DEFAULT_DPI};
+use crate::value::{parse_json, parse_toml, Value};
+use plotters::chart::SeriesLabelPosition;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// A parsed plot specification; [`PlotSpec::load`] reads its data.
+#[derive(Clone)]
+pub struct PlotSpec {
+    pub title: Option<String>,
+    pub size: FigureSize,
+    pub theme: Theme,
+    /// Rows and columns of panels, filled row by row.
+    pub grid: (usize, usize),
+    pub output: PathBuf,
+    pub panels: Vec<PanelSpec>,
+}
+
+/// A CSV file, optionally without a header row.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DataSource {
+    pub path: PathBuf,
+    pub header: bool,
+}
+
+/// One chart of the grid. Its series are either all histograms or all lines
+/// and scatters.
+#[derive(Clone)]
+pub struct PanelSpec {
+    pub title: String,
+    pub x_label: Option<String>,
+    pub y_label: Option<String>,
+    pub logx: bool,
+    pub logy: bool,
+    pub xlim: (Option<f64>, Option<f64>),
+    pub ylim: (Option<f64>, Option<f64>),
+    /// Legend position of a line panel (histograms always show theirs upper
+    /// right); `None` hides it.
+    pub legend: Option<SeriesLabelPosition>,
+    /// How the histograms of a histogram panel are combined.
+    pub layout: HistLayout,
+    pub series: Vec<SeriesSpec>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeriesKind {
+    Line,
+    Scatter,
+    Histogram,
+}
+
+impl FromStr for SeriesKind {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "line" => Ok(SeriesKind::Line),
+            "scatter" => Ok(SeriesKind::Scatter),
+            "histogram" | "hist" => Ok(SeriesKind::Histogram),
+            other => Err(format!("Unknown series type '{}' (line, scatter, histogram)", other).into()),
+        }
+    }
+}
+
+/// One series of a panel. Lines and scatters plot `y` against `x` (or the row
+/// number); histograms bin `column`.
+#[derive(Clone, Debug)]
+pub struct SeriesSpec {
+    pub kind: SeriesKind,
+    pub data: DataSource,
+    pub x: Option<Column>,
+    /// The y column, or the binned column of a histogram.
+    pub y: Column,
+    /// Parse `x` as timestamps even if it looks numeric.
+    pub time: bool,
+    pub label: Option<String>,
+    pub format: LineFormat,
+    /// Line width in points (the theme's by default).
+    pub width: Option<f64>,
+    pub bins: BinRule,
+    pub norm: Normalization,
+}
+
+impl PlotSpec {
+    /// Reads a `.json` spec, or TOML for any other extension. Relative data
+    /// and output paths are resolved against the spec's directory.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let src = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let is_json = path.extension().is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        let value = if is_json { parse_json(&src) } else { parse_toml(&src) }
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        let default_output = base.join(path.file_stem().unwrap_or_default()).with_extension("png");
+        Self::from_value(&value, base, default_output).map_err(|e| format!("{}: {}", path.display(), e).into())
+    }
+
+    /// Builds a spec from a parsed document; see the README for its keys.
+    pub fn from_value(value: &Value, base: &Path, default_output: PathBuf) -> Result<Self, Box<dyn Error>> {
+        let mut top = Fields::new(value, "spec")?;
+        let title = top.string("title")?;
+        let output = top.string("output")?.map(|p| base.join(p)).unwrap_or(default_output);
+
+        let mut sources = HashMap::new();
+        if let Some(data) = top.get("data") {
+            let Value::Table(entries) = data else {
+                return Err(format!("'data' must be a table of named sources, not {}", data.type_name()).into());
+            };
+            for (name, source) in entries {
+                let mut f = Fields::new(source, format!("data.{}", name))?;
+                let path = f.string("path")?.ok_or_else(|| f.missing("path"))?;
+                let header = f.bool("header")?.unwrap_or(true);
+                f.finish()?;
+                sources.insert(name.clone(), DataSource { path: base.join(path), header });
+            }
+        }
+
+        let mut panels = Vec::new();
+        for (i, panel) in top.tables("panel")?.into_iter().enumerate() {
+            panels.push(PanelSpec::from_value(panel, i + 1, base, &sources)?);
+        }
+        if panels.is_empty() {
+            return Err("the spec has no [[panel]]".into());
+        }
+
+        let grid = match top.pair("grid")? {
+            Some((rows, cols)) if rows >= 1.0 && cols >= 1.0 => (rows as usize, cols as usize),
+            Some(_) => return Err("'grid' needs at least one row and column".into()),
+            None => (panels.len(), 1),
+        };
+        if panels.len() > grid.0 * grid.1 {
+            return Err(format!("{} panels do not fit a {}x{} grid", panels.len(), grid.0, grid.1).into());
+        }
+
+        let dpi = check_dpi(top.number("dpi")?.unwrap_or(DEFAULT_DPI))?;
+        let size = match top.get("size") {
+            None => FigureSize::inches((6.0 * grid.1 as f64).max(8.0), (4.0 * grid.0 as f64).max(16.0 / 3.0)).with_dpi(dpi),
+            Some(Value::String(s)) => FigureSize::parse(s, dpi)?,
+            Some(Value::Array(wh)) => match wh.as_slice() {
+                [Value::Number(w), Value::Number(h)] if *w >= 1.0 && *h >= 1.0 => FigureSize::from_pixels((*w as u32, *h as u32), dpi),
+                _ => return Err("'size' must be [width, height] in pixels or a string such as \"8x5in\"".into()),
+            },
+            Some(v) => return Err(format!("'size' must be an array or a string, not {}", v.type_name()).into()),
+        };
+        let mut theme: Theme = top.parsed("theme")?.unwrap_or_default();
+        if let Some(name) = top.string("palette")? {
+            theme = theme.with_palette(palette(&name).map_err(|e| top.invalid("palette", &format!("is invalid: {}", e)))?);
+        }
+        if let Some(colormap) = top.parsed("colormap")? {
+            theme = theme.with_colormap(colormap);
+        }
+        top.finish()?;
+
+        Ok(Self {
+            title,
+            size,
+            theme,
+            grid,
+            output,
+            panels,
+        })
+    }
+
+    /// Reads every data source and prepares the panels for drawing at the
+    /// spec's DPI and in its theme.
+    pub fn load(&self) -> Result<Plot, Box<dyn Error>> {
+        let mut tables = HashMap::new();
+        let panels = with_theme(self.theme, || {
+            with_dpi(self.size.dpi, || {
+                self.panels
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| p.load(&mut tables).map_err(|e| format!("panel {}: {}", i + 1, e).into()))
+                    .collect::<Result<Vec<_>, Box<dyn Error>>>()
+            })
+        })?;
+        Ok(Plot {
+            title: self.title.clone(),
+            size: self.size,
+            theme: self.theme,
+            grid: self.grid,
+            output: self.output.clone(),
+            panels,
+        })
+    }
+}
+
+impl PanelSpec {
+    fn from_value(
+        value: &Value,
+        index: usize,
+        base: &Path,
+        sources: &HashMap<String, DataSource>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut f = Fields::new(value, format!("panel {}", index))?;
+        let mut panel = PanelSpec {
+            title: f.string("title")?.unwrap_or_default(),
+            x_label: f.string("x_label")?,
+            y_label: f.string("y_label")?,
+            logx: f.bool("logx")?.unwrap_or(false),
+            logy: f.bool("logy")?.unwrap_or(false),
+            xlim: f.limits("xlim")?,
+            ylim: f.limits("ylim")?,
+            legend: match f.string("legend")? {
+                Some(s) => parse_legend(&s)?,
+                None => Some(SeriesLabelPosition::UpperRight),
+            },
+            layout: f.parsed("layout")?.unwrap_or(HistLayout::Overlay),
+            series: Vec::new(),
+        };
+        for (i, series) in f.tables("series")?.into_iter().enumerate() {
+            let what = format!("panel {}, series {}", index, i + 1);
+            panel.series.push(SeriesSpec::from_value(series, what, base, sources)?);
+        }
+        f.finish()?;
+
+        let histograms = panel.series.iter().filter(|s| s.kind == SeriesKind::Histogram).count();
+        if panel.series.is_empty() {
+            return Err(format!("panel {} has no [[panel.series]]", index).into());
+        }
+        if histograms != 0 && histograms != panel.series.len() {
+            return Err(format!("panel {} mixes histograms with lines or scatters", index).into());
+        }
+        if histograms != 0 && (panel.logx || panel.logy || panel.xlim != (None, None) || panel.ylim != (None, None)) {
+            return Err(format!("panel {}: logx, logy, xlim and ylim only apply to lines and scatters", index).into());
+        }
+        if panel.series.iter().any(|s| s.bins != panel.series[0].bins) {
+            return Err(format!("histograms in panel {} share their bins, so they need the same 'bins'", index).into());
+        }
+        if panel.series.iter().any(|s| s.norm != panel.series[0].norm) {
+            return Err(format!("histograms in panel {} share their y axis, so they need the same 'norm'", index).into());
+        }
+        Ok(panel)
+    }
+
+    fn load(&self, tables: &mut HashMap<DataSource, Table>) -> Result<Panel, Box<dyn Error>> {
+        if self.series[0].kind == SeriesKind::Histogram {
+            self.load_histograms(tables)
+        } else {
+            self.load_lines(tables)
+        }
+    }
+
+    fn load_lines(&self, tables: &mut HashMap<DataSource, Table>) -> Result<Panel, Box<dyn Error>> {
+        let mut series = Vec::new();
+        let (mut x_name, mut y_names, mut any_time) = (None, Vec::new(), false);
+        for (i, s) in self.series.iter().enumerate() {
+            let table = table(tables, &s.data)?;
+            let xs = match &s.x {
+                Some(col) => {
+                    let is_time = s.time || table.has_timestamps(col);
+                    any_time |= is_time;
+                    x_name.get_or_insert_with(|| table.name_of(col).map(str::to_string));
+                    if is_time { table.timestamps(col)? } else { table.column(col)? }
+                }
+                None => (0..table.rows.len()).map(|r| Some(r as f64)).collect(),
+            };
+            let y_name = table.name_of(&s.y)?.to_string();
+            let points = xs
+                .into_iter()
+                .zip(table.column(&s.y)?)
+                .filter_map(|(x, y)| Some((x?, y?)))
+                .collect::<Vec<_>>();
+
+            let mut format = s.format;
+            if s.kind == SeriesKind::Scatter {
+                format.dash = None;
+                format.marker.get_or_insert(MarkerShape::Circle);
+            }
+            let label = s.label.as_deref().unwrap_or(&y_name);
+            let mut line = format.series(label, points, theme().color(i), s.width.unwrap_or(theme().line_width));
+            if s.kind == SeriesKind::Scatter {
+                if let Some(markers) = &mut line.markers {
+                    markers.placement = MarkerPlacement::DataPoints;
+                }
+            }
+            series.push(line);
+            y_names.push(y_name);
+        }
+
+        let x_scale = match (self.logx, any_time) {
+            (true, _) => AxisScale::Log,
+            (false, true) => AxisScale::Time,
+            (false, false) => AxisScale::Linear,
+        };
+        let y_scale = if self.logy { AxisScale::Log } else { AxisScale::Linear };
+        let x_range = padded_range(series.iter().flat_map(|s| s.points.iter().map(|p| p.0)), x_scale, self.xlim)?;
+        let y_range = padded_range(series.iter().flat_map(|s| s.points.iter().map(|p| p.1)), y_scale, self.ylim)?;
+        let default_y = match y_names.as_slice() {
+            [one] => one.clone(),
+            _ => "Value".to_string(),
+        };
+        Ok(Panel {
+            title: self.title.clone(),
+            x_label: self.x_label.clone().unwrap_or(x_name.transpose()?.unwrap_or_else(|| "Row".to_string())),
+            y_label: self.y_label.clone().unwrap_or(default_y),
+            legend: self.legend.clone(),
+            kind: PanelKind::Lines {
+                x: (x_range, x_scale),
+                y: (y_range, y_scale),
+                series,
+            },
+        })
+    }
+
+    fn load_histograms(&self, tables: &mut HashMap<DataSource, Table>) -> Result<Panel, Box<dyn Error>> {
+        let mut columns = Vec::new();
+        for s in &self.series {
+            let table = table(tables, &s.data)?;
+            columns.push((table.name_of(&s.y)?.to_string(), table.numeric(&s.y)?));
+        }
+        let all = columns.iter().flat_map(|(_, v)| v.iter().copied()).collect::<Vec<_>>();
+        if all.is_empty() {
+            return Err("no numeric values to bin".into());
+        }
+        let first = &self.series[0];
+        let edges = first.bins.edges(&all)?;
+        let datasets = self
+            .series
+            .iter()
+            .zip(&columns)
+            .enumerate()
+            .map(|(i, (s, (name, values)))| {
+                let color = s.format.color.unwrap_or(theme().color(i));
+                let style = match self.layout {
+                    HistLayout::Overlay => color.mix(0.6).filled(),
+                    _ => color.stroke_width(s.width.map_or(theme().stroke(), pt)),
+                };
+                HistDataset::from_data(s.label.as_deref().unwrap_or(name), values, &edges, s.norm, style)
+            })
+            .collect();
+        Ok(Panel {
+            title: self.title.clone(),
+            x_label: self.x_label.clone().unwrap_or_else(|| columns[0].0.clone()),
+            y_label: self.y_label.clone().unwrap_or_else(|| first.norm.axis_label().to_string()),
+            legend: self.legend.clone(),
+            kind: PanelKind::Histogram {
+                edges,
+                datasets,
+                layout: self.layout,
+            },
+        })
+    }
+}
+
+impl SeriesSpec {
+    fn from_value(
+        value: &Value,
+        what: String,
+        base: &Path,
+        sources: &HashMap<String, DataSource>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut f = Fields::new(value, what)?;
+        let kind = f.parsed("type")?.unwrap_or(SeriesKind::Line);
+        let data = match (f.string("data")?, f.string("path")?) {
+            (Some(name), None) => sources
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| f.invalid("data", &format!("names no [data.{}] source", name)))?,
+            (None, Some(path)) => DataSource {
+                path: base.join(path),
+                header: f.bool("header")?.unwrap_or(true),
+            },
+            (Some(_), Some(_)) => return Err(f.invalid("data", "and 'path' cannot both be given")),
+            (None, None) => return Err(f.missing("data")),
+        };
+
+        let (x, y) = if kind == SeriesKind::Histogram {
+            (None, f.parsed("column")?.ok_or_else(|| f.missing("column"))?)
+        } else {
+            (f.parsed("x")?, f.parsed("y")?.ok_or_else(|| f.missing("y"))?)
+        };
+        let mut format: LineFormat = f.parsed("style")?.unwrap_or_default();
+        if let Some(color) = f.string("color")? {
+            format.color = Some(parse_color(&color).map_err(|e| f.invalid("color", &format!("is invalid: {}", e)))?);
+        }
+        let series = SeriesSpec {
+            kind,
+            data,
+            x,
+            y,
+            time: kind != SeriesKind::Histogram && f.bool("time")?.unwrap_or(false),
+            label: f.string("label")?,
+            format,
+            width: f.number("width")?,
+            bins: if kind == SeriesKind::Histogram { f.parsed("bins")? } else { None }.unwrap_or(BinRule::Auto),
+            norm: if kind == SeriesKind::Histogram { f.parsed("norm")? } else { None }.unwrap_or(Normalization::Count),
+        };
+        f.finish()?;
+        Ok(series)
+    }
+}
+
+// Reads each CSV file once
+fn table<'t>(tables: &'t mut HashMap<DataSource, Table>, source: &DataSource) -> Result<&'t Table, Box<dyn Error>> {
+    if !tables.contains_key(source) {
+        let path = source.path.to_string_lossy();
+        tables.insert(source.clone(), Table::from_path(&path, source.header)?);
+    }
+    Ok(&tables[source])
+}
+
+/// `#rrggbb`, `#rgb`, a color name (`black`, `white`, `red`, `green`, `blue`,
+/// `cyan`, `magenta`, `yellow`) or a format-string letter (`b g r c m y k w`).
+pub fn parse_color(s: &str) -> Result<RGBColor, Box<dyn Error>> {
+    let s = s.trim();
+    if let Some(hex) = s.strip_prefix('#') {
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(format!("Expected #rrggbb or #rgb, got '{}'", s).into());
+        }
+        let digit = |i: usize, len: usize| u8::from_str_radix(&hex[i..i + len], 16);
+        return match hex.len() {
+            6 => Ok(RGBColor(digit(0, 2)?, digit(2, 2)?, digit(4, 2)?)),
+            3 => Ok(RGBColor(digit(0, 1)? * 17, digit(1, 1)? * 17, digit(2, 1)? * 17)),
+            _ => Err(format!("Expected #rrggbb or #rgb, got '{}'", s).into()),
+        };
+    }
+    let letter = match s.to_ascii_lowercase().as_str() {
+        "black" => "k",
+        "white" => "w",
+        "red" => "r",
+        "green" => "g",
+        "blue" => "b",
+        "cyan" => "c",
+        "magenta" => "m",
+        "yellow" => "y",
+        _ if s.len() == 1 => s,
+        _ => return Err(format!("Unknown color '{}'", s).into()),
+    };
+    letter
+        .parse::<LineFormat>()
+        .ok()
+        .and_then(|f| f.color)
+        .ok_or_else(|| format!("Unknown color '{}'", s).into())
+}
+
+/// A spec with its data loaded, ready to draw or save.
+pub struct Plot {
+    title: Option<String>,
+    size: FigureSize,
+    theme: Theme,
+    grid: (usize, usize),
+    output: PathBuf,
+    panels: Vec<Panel>,
+}
+
+struct Panel {
+    title: String,
+    x_label: String,
+    y_label: String,
+    legend: Option<SeriesLabelPosition>,
+    kind: PanelKind,
+}
+
+enum PanelKind {
+    Lines {
+        x: (Range<f64>, AxisScale),
+        y: (Range<f64>, AxisScale),
+        series: Vec<LineSpec>,
+    },
+    Histogram {
+        edges: Vec<f64>,
+        datasets: Vec<HistDataset>,
+        layout: HistLayout,
+    },
+}
+
+impl Plot {
+    /// Figure size and resolution.
+    pub fn size(&self) -> FigureSize {
+        self.size
+    }
+
+    /// Where the spec asks for the image to be written.
+    pub fn output(&self) -> &Path {
+        &self.output
+    }
+
+    /// The title and series colors of each panel, in order.
+    pub fn panel_colors(&self) -> Vec<(&str, Vec<RGBColor>)> {
+        let rgb = |style: &ShapeStyle| {
+            let (r, g, b) = style.color.rgb();
+            RGBColor(r, g, b)
+        };
+        self.panels
+            .iter()
+            .map(|panel| {
+                let colors = match &panel.kind {
+                    PanelKind::Lines { series, .. } => series.iter().map(|s| rgb(&s.style)).collect(),
+                    PanelKind::Histogram { datasets, .. } => datasets.iter().map(|d| rgb(&d.style)).collect(),
+                };
+                (panel.title.as_str(), colors)
+            })
+            .collect()
+    }
+}
+
+impl Figure for Plot {
+    fn draw<DB>(&self, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        with_theme(self.theme, || {
+            root.fill(&self.theme.background)?;
+            let area = match &self.title {
+                Some(title) => root.titled(title, self.theme.text(self.theme.title_size * 1.15))?,
+                None => root.clone(),
+            };
+            for (cell, panel) in area.split_evenly(self.grid).iter().zip(&self.panels) {
+                match &panel.kind {
+                    PanelKind::Lines { x, y, series } => {
+                        let x = AxisSpec::new(x.0.clone(), &panel.x_label).scale(x.1);
+                        let y = AxisSpec::new(y.0.clone(), &panel.y_label).scale(y.1);
+                        draw_lines(cell, &panel.title, &x, &y, series, panel.legend.clone())?;
+                    }
+                    PanelKind::Histogram { edges, datasets, layout } => {
+                        draw_histograms(cell, &panel.title, &panel.x_label, &panel.y_label, edges, datasets, *layout)?;
+                    }
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+// Reads the keys of one spec table, remembering which were used so that
+// unknown (usually misspelled) keys can be reported by `finish`
+struct Fields<'v> {
+    what: String,
+    entries: &'v [(String, Value)],
+    used: Vec<&'v str>,
+}
+
+impl<'v> Fields<'v> {
+    fn new<S: Into<String>>(value: &'v Value, what: S) -> Result<Self, Box<dyn Error>> {
+        let what = what.into();
+        match value {
+            Value::Table(entries) => Ok(Self {
+                what,
+                entries,
+                used: Vec::new(),
+            }),
+            other => Err(format!("{} must be a table, not {}", what, other.type_name()).into()),
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<&'v Value> {
+        let (k, v) = self.entries.iter().find(|(k, _)| k == key)?;
+        self.used.push(k);
+        Some(v)
+    }
+
+    fn invalid(&self, key: &str, msg: &str) -> Box<dyn Error> {
+        format!("{}: '{}' {}", self.what, key, msg).into()
+    }
+
+    fn missing(&self, key: &str) -> Box<dyn Error> {
+        format!("{}: missing '{}'", self.what, key).into()
+    }
+
+    fn string(&mut self, key: &str) -> Result<Option<String>, Box<dyn Error>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s.clone())),
+            Some(v) => Err(self.invalid(key, &format!("must be a string, not {}", v.type_name()))),
+        }
+    }
+
+    fn number(&mut self, key: &str) -> Result<Option<f64>, Box<dyn Error>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::Number(v)) => Ok(Some(*v)),
+            Some(v) => Err(self.invalid(key, &format!("must be a number, not {}", v.type_name()))),
+        }
+    }
+
+    fn bool(&mut self, key: &str) -> Result<Option<bool>, Box<dyn Error>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::Bool(b)) => Ok(Some(*b)),
+            Some(v) => Err(self.invalid(key, &format!("must be true or false, not {}", v.type_name()))),
+        }
+    }
+
+    // A string (or number) parsed as `T`
+    fn parsed<T>(&mut self, key: &str) -> Result<Option<T>, Box<dyn Error>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let text = match self.get(key) {
+            None => return Ok(None),
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Number(v)) => v.to_string(),
+            Some(v) => return Err(self.invalid(key, &format!("must be a string, not {}", v.type_name()))),
+        };
+        text.parse::<T>().map(Some).map_err(|e| self.invalid(key, &format!("is invalid: {}", e)))
+    }
+
+    fn pair(&mut self, key: &str) -> Result<Option<(f64, f64)>, Box<dyn Error>> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::Array(items)) => match items.as_slice() {
+                [Value::Number(a), Value::Number(b)] => Ok(Some((*a, *b))),
+                _ => Err(self.invalid(key, "must be an array of two numbers")),
+            },
+            Some(v) => Err(self.invalid(key, &format!("must be an array of two numbers, not {}", v.type_name()))),
+        }
+    }
+
+    // `[min, max]` or `"min:max"`, where either side may be left out of the string
+    fn limits(&mut self, key: &str) -> Result<(Option<f64>, Option<f64>), Box<dyn Error>> {
+        if let Some(Value::String(s)) = self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v) {
+            self.get(key);
+            return parse_limits(s).map_err(|e| self.invalid(key, &format!("is invalid: {}", e)));
+        }
+        Ok(self.pair(key)?.map_or((None, None), |(lo, hi)| (Some(lo), Some(hi))))
+    }
+
+    // An array of tables (`[[key]]`); a single table counts as one
+    fn tables(&mut self, key: &str) -> Result<Vec<&'v Value>, Box<dyn Error>> {
+        match self.get(key) {
+            None => Ok(Vec::new()),
+            Some(Value::Array(items)) => Ok(items.iter().collect()),
+            Some(table @ Value::Table(_)) => Ok(vec![table]),
+            Some(v) => Err(self.invalid(key, &format!("must be an array of tables, not {}", v.type_name()))),
+        }
+    }
+
+    fn finish(self) -> Result<(), Box<dyn Error>> {
+        match self.entries.iter().find(|(k, _)| !self.used.contains(&k.as_str())) {
+            Some((k, _)) => Err(format!("{}: unknown key '{}'", self.what, k).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(toml: &str) -> Result<PlotSpec, Box<dyn Error>> {
+        PlotSpec::from_value(&parse_toml(toml)?, Path::new(""), PathBuf::from("plot.png"))
+    }
+
+    #[test]
+    fn parse_color_forms() {
+        assert_eq!(parse_color("#ff8000").unwrap(), RGBColor(255, 128, 0));
+        assert_eq!(parse_color(" #F80 ").unwrap(), RGBColor(255, 136, 0));
+        assert_eq!(parse_color("Blue").unwrap(), BLUE);
+        assert_eq!(parse_color("k").unwrap(), BLACK);
+        for bad in ["#ff80", "#gg0000", "#+f+f+f", "#é00", "#ff€0", "teal", "z"] {
+            assert!(parse_color(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn histogram_panels_need_one_bin_rule_and_norm() {
+        let panel = |bins: &str, norm: &str| {
+            format!(
+                "[[panel]]\n[[panel.series]]\ntype = 'hist'\npath = 'a.csv'\ncolumn = 'v'\n\
+                 [[panel.series]]\ntype = 'hist'\npath = 'b.csv'\ncolumn = 'v'\nbins = '{bins}'\nnorm = '{norm}'\n"
+            )
+        };
+        assert!(spec(&panel("auto", "count")).is_ok());
+        let err = spec(&panel("fd", "count")).err().unwrap().to_string();
+        assert!(err.contains("same 'bins'"), "{err}");
+        let err = spec(&panel("auto", "pdf")).err().unwrap().to_string();
+        assert!(err.contains("same 'norm'"), "{err}");
+    }
+}
@@ -0,0 +1,439 @@
+use std::cmp::Ordering;
+
+pub fn mean(x: &[f64]) -> f64 {
+    x.iter().sum::<f64>() / (x.len().max(1) as f64)
+}
+
+pub fn std_dev(x: &[f64]) -> f64 {
+    if x.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(x);
+    let var = x.iter().map(|v| (v - m) * (v - m)).sum::<f64>() / ((x.len() - 1) as f64);
+    var.sqrt()
+}
+
+/// Pearson correlation of two equally long samples (NaN when either is constant).
+pub fn correlation(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len().min(y.len());
+    let (x, y) = (&x[..n], &y[..n]);
+    let (mx, my) = (mean(x), mean(y));
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (a, b) in x.iter().zip(y) {
+        let (dx, dy) = (a - mx, b - my);
+        sxy += dx * dy;
+        sxx += dx * dx;
+        syy += dy * dy;
+    }
+    sxy / (sxx * syy).sqrt()
+}
+
+/// Pearson correlations between every pair of `columns`.
+pub fn correlation_matrix(columns: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    columns.iter().map(|a| columns.iter().map(|b| correlation(a, b)).collect()).collect()
+}
+
+/// Sample quantile definitions from Hyndman & Fan (1996), numbered as in R's `quantile(type = …)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum QuantileMethod {
+    /// Inverse of the empirical CDF (SAS-3).
+    Type1,
+    /// Like type 1, averaging at discontinuities (SAS-5).
+    Type2,
+    /// Nearest even order statistic (SAS-2).
+    Type3,
+    /// Linear interpolation of the empirical CDF (SAS-1, California).
+    Type4,
+    /// Piecewise linear with knots at the bin midpoints (hydrology).
+    Type5,
+    /// `p(n + 1)`: Minitab, SPSS, Excel `PERCENTILE.EXC`.
+    Type6,
+    /// `1 + p(n − 1)`: R, NumPy and Excel `PERCENTILE.INC` default.
+    #[default]
+    Type7,
+    /// Approximately median-unbiased; recommended by Hyndman & Fan.
+    Type8,
+    /// Approximately unbiased for normal data.
+    Type9,
+}
+
+impl QuantileMethod {
+    pub const ALL: [QuantileMethod; 9] = [
+        QuantileMethod::Type1,
+        QuantileMethod::Type2,
+        QuantileMethod::Type3,
+        QuantileMethod::Type4,
+        QuantileMethod::Type5,
+        QuantileMethod::Type6,
+        QuantileMethod::Type7,
+        QuantileMethod::Type8,
+        QuantileMethod::Type9,
+    ];
+}
+
+/// Linear-interpolation quantile (type 7) of already sorted data.
+pub fn quantile(sorted: &[f64], q: f64) -> f64 {
+    quantile_with(sorted, q, QuantileMethod::Type7)
+}
+
+/// Quantile `q` of already sorted data with the given Hyndman–Fan method.
+pub fn quantile_with(sorted: &[f64], q: f64, method: QuantileMethod) -> f64 {
+    if sorted.is_empty() || !(0.0..=1.0).contains(&q) {
+        return f64::NAN;
+    }
+    let n = sorted.len();
+    let nf = n as f64;
+    // Order statistic x_k (1-based), clamped to the sample
+    let x = |k: i64| sorted[(k.clamp(1, n as i64) - 1) as usize];
+
+    // Discontinuous methods: x_j or x_{j+1} depending on the fractional part g.
+    // As in R, g within FUZZ of zero counts as zero, so 100 · 0.07 = 7.000000000000001
+    // is treated as the discontinuity at 7.
+    const FUZZ: f64 = 4.0 * f64::EPSILON;
+    let discrete = |m: f64, pick: &dyn Fn(i64, f64) -> f64| {
+        let h = nf * q + m;
+        let j = (h + FUZZ).floor();
+        pick(j as i64, h - j)
+    };
+    match method {
+        QuantileMethod::Type1 => discrete(0.0, &|j, g| if g > FUZZ { x(j + 1) } else { x(j) }),
+        QuantileMethod::Type2 => discrete(0.0, &|j, g| if g > FUZZ { x(j + 1) } else { 0.5 * (x(j) + x(j + 1)) }),
+        QuantileMethod::Type3 => discrete(-0.5, &|j, g| if g.abs() <= FUZZ && j % 2 == 0 { x(j) } else { x(j + 1) }),
+        _ => {
+            let m = match method {
+                QuantileMethod::Type4 => 0.0,
+                QuantileMethod::Type5 => 0.5,
+                QuantileMethod::Type6 => q,
+                QuantileMethod::Type7 => 1.0 - q,
+                QuantileMethod::Type8 => (q + 1.0) / 3.0,
+                _ => q / 4.0 + 3.0 / 8.0,
+            };
+            let h = (nf * q + m).clamp(1.0, nf);
+            let j = h.floor();
+            let g = h - j;
+            let j = j as i64;
+            if g == 0.0 {
+                x(j)
+            } else {
+                x(j) + g * (x(j + 1) - x(j))
+            }
+        }
+    }
+}
+
+/// Several quantiles of unsorted data, sorting only once.
+pub fn quantiles(data: &[f64], qs: &[f64], method: QuantileMethod) -> Vec<f64> {
+    let v = sorted(data);
+    qs.iter().map(|&q| quantile_with(&v, q, method)).collect()
+}
+
+/// Interquartile range of unsorted data with the given quantile method.
+pub fn iqr(x: &[f64], method: QuantileMethod) -> f64 {
+    match quantiles(x, &[0.25, 0.75], method).as_slice() {
+        [q1, q3] => q3 - q1,
+        _ => f64::NAN,
+    }
+}
+
+/// Sorted copy of `x` (NaNs compare equal to everything).
+pub fn sorted(x: &[f64]) -> Vec<f64> {
+    let mut v = x.to_vec();
+    v.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    v
+}
+
+pub fn data_min_max(x: &[f64]) -> (f64, f64) {
+    let mut mn = f64::INFINITY;
+    let mut mx = f64::NEG_INFINITY;
+    for &v in x {
+        mn = mn.min(v);
+        mx = mx.max(v);
+    }
+    if mn == mx {
+        (mn - 1.0, mx + 1.0)
+    } else {
+        (mn, mx)
+    }
+}
+
+/// Natural log of the gamma function (Lanczos approximation, g = 7).
+pub fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const C: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Reflection formula
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).abs().ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut a = C[0];
+    let t = x + G + 0.5;
+    for (i, &c) in C.iter().enumerate().skip(1) {
+        a += c / (x + i as f64);
+    }
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+}
+
+/// Regularized upper incomplete gamma function Q(a, x) = Γ(a, x) / Γ(a).
+pub fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    if a <= 0.0 {
+        return 0.0;
+    }
+    let ln_pre = a * x.ln() - x - ln_gamma(a);
+    if x < a + 1.0 {
+        // Series for P(a, x)
+        let (mut ap, mut sum, mut del) = (a, 1.0 / a, 1.0 / a);
+        for _ in 0..500 {
+            ap += 1.0;
+            del *= x / ap;
+            sum += del;
+            if del.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        (1.0 - sum * ln_pre.exp()).clamp(0.0, 1.0)
+    } else {
+        // Continued fraction for Q(a, x) (modified Lentz)
+        let tiny = 1e-300;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..500 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let del = d * c;
+            h *= del;
+            if (del - 1.0).abs() < 1e-15 {
+                break;
+            }
+        }
+        (ln_pre.exp() * h).clamp(0.0, 1.0)
+    }
+}
+
+/// Regularized lower incomplete gamma function P(a, x) = 1 − Q(a, x).
+pub fn gamma_p(a: f64, x: f64) -> f64 {
+    1.0 - gamma_q(a, x)
+}
+
+/// Error function (via the incomplete gamma function, accurate to ~1e-15).
+pub fn erf(x: f64) -> f64 {
+    let p = gamma_p(0.5, x * x);
+    if x < 0.0 {
+        -p
+    } else {
+        p
+    }
+}
+
+/// CDF of the normal distribution N(mu, sigma).
+pub fn normal_cdf(x: f64, mu: f64, sigma: f64) -> f64 {
+    let z = (x - mu) / (sigma * std::f64::consts::SQRT_2);
+    if z < -0.5 {
+        // Avoid cancellation in the lower tail
+        0.5 * gamma_q(0.5, z * z)
+    } else {
+        0.5 * (1.0 + erf(z))
+    }
+}
+
+/// Upper tail probability of the chi-square distribution with `dof` degrees of freedom.
+pub fn chi_square_sf(x: f64, dof: f64) -> f64 {
+    gamma_q(dof / 2.0, x / 2.0)
+}
+
+/// Digamma function ψ(x) = d/dx ln Γ(x), for x > 0.
+pub fn digamma(mut x: f64) -> f64 {
+    let mut acc = 0.0;
+    while x < 6.0 {
+        acc -= 1.0 / x;
+        x += 1.0;
+    }
+    let f = 1.0 / (x * x);
+    acc + x.ln() - 0.5 / x - f * (1.0 / 12.0 - f * (1.0 / 120.0 - f * (1.0 / 252.0 - f * (1.0 / 240.0 - f / 132.0))))
+}
+
+/// Trigamma function ψ'(x), for x > 0.
+pub fn trigamma(mut x: f64) -> f64 {
+    let mut acc = 0.0;
+    while x < 6.0 {
+        acc += 1.0 / (x * x);
+        x += 1.0;
+    }
+    let f = 1.0 / (x * x);
+    acc + 1.0 / x + f / 2.0 + f / x * (1.0 / 6.0 - f * (1.0 / 30.0 - f * (1.0 / 42.0 - f / 30.0)))
+}
+
+/// Quantile function (inverse CDF) of N(mu, sigma).
+///
+/// Acklam's rational approximation refined with one Halley step (~1e-15 relative error).
+pub fn normal_quantile(p: f64, mu: f64, sigma: f64) -> f64 {
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    let z = if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    };
+    // Halley refinement
+    let e = normal_cdf(z, 0.0, 1.0) - p;
+    let u = e * (2.0 * std::f64::consts::PI).sqrt() * (z * z / 2.0).exp();
+    let z = z - u / (1.0 + z * u / 2.0);
+    mu + sigma * z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn one_to_ten() -> Vec<f64> {
+        (1..=10).map(f64::from).collect()
+    }
+
+    // R: quantile(1:10, c(0.1, 0.5, 0.9), type = k)
+    fn check_type(method: QuantileMethod, expected: [f64; 3]) {
+        let got = quantiles(&one_to_ten(), &[0.1, 0.5, 0.9], method);
+        for (g, e) in got.iter().zip(expected) {
+            assert!((g - e).abs() < 1e-6, "{:?}: got {:?}, expected {:?}", method, got, expected);
+        }
+    }
+
+    #[test]
+    fn quantile_type1() {
+        check_type(QuantileMethod::Type1, [1.0, 5.0, 9.0]);
+        // Inverse of the ECDF: the next order statistic past np = 2.5
+        assert_eq!(quantile_with(&one_to_ten(), 0.25, QuantileMethod::Type1), 3.0);
+    }
+
+    #[test]
+    fn quantile_type2() {
+        check_type(QuantileMethod::Type2, [1.5, 5.5, 9.5]);
+        // np = 2 is a discontinuity, so x2 and x3 are averaged
+        assert_eq!(quantile_with(&one_to_ten(), 0.2, QuantileMethod::Type2), 2.5);
+    }
+
+    #[test]
+    fn quantile_type3() {
+        check_type(QuantileMethod::Type3, [1.0, 5.0, 9.0]);
+        // np − 1/2 = 2 lands on an even order statistic, which is kept
+        assert_eq!(quantile_with(&one_to_ten(), 0.25, QuantileMethod::Type3), 2.0);
+    }
+
+    #[test]
+    fn discontinuous_types_absorb_rounding_error() {
+        // R: quantile(1:100, c(0.07, 0.29), type = k). 100 · 0.07 lands one ulp above 7,
+        // within the fuzz; 100 · 0.29 lands 3.6e-15 below 29, outside it.
+        let v = (1..=100).map(f64::from).collect::<Vec<_>>();
+        let at = |q, method| quantile_with(&v, q, method);
+        assert_eq!(at(0.07, QuantileMethod::Type1), 7.0);
+        assert_eq!(at(0.07, QuantileMethod::Type2), 7.5);
+        assert_eq!(at(0.07, QuantileMethod::Type3), 7.0);
+        assert_eq!(at(0.29, QuantileMethod::Type1), 29.0);
+        assert_eq!(at(0.29, QuantileMethod::Type2), 29.0);
+        assert_eq!(at(0.29, QuantileMethod::Type3), 29.0);
+    }
+
+    #[test]
+    fn quantile_type4() {
+        check_type(QuantileMethod::Type4, [1.0, 5.0, 9.0]);
+    }
+
+    #[test]
+    fn quantile_type5() {
+        check_type(QuantileMethod::Type5, [1.5, 5.5, 9.5]);
+    }
+
+    #[test]
+    fn quantile_type6() {
+        check_type(QuantileMethod::Type6, [1.1, 5.5, 9.9]);
+    }
+
+    #[test]
+    fn quantile_type7() {
+        check_type(QuantileMethod::Type7, [1.9, 5.5, 9.1]);
+        assert_eq!(iqr(&one_to_ten(), QuantileMethod::Type7), 4.5);
+    }
+
+    #[test]
+    fn quantile_type8() {
+        check_type(QuantileMethod::Type8, [1.366_667, 5.5, 9.633_333]);
+    }
+
+    #[test]
+    fn quantile_type9() {
+        check_type(QuantileMethod::Type9, [1.4, 5.5, 9.6]);
+    }
+
+    #[test]
+    fn quantile_of_empty_or_out_of_range_is_nan() {
+        assert!(quantile(&[], 0.5).is_nan());
+        assert!(quantile(&one_to_ten(), 1.5).is_nan());
+        assert_eq!(quantile(&one_to_ten(), 0.0), 1.0);
+        assert_eq!(quantile(&one_to_ten(), 1.0), 10.0);
+    }
+}
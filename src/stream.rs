@@ -0,0 +1,612 @@
+// Incremental histograms and quantile sketches for data that does not fit in memory.
+//
+// Both accumulators take values one at a time and can be merged, so a large
+// log can be split across threads or files and combined at the end.
+
+use crate::histogram::{bins_sturges, normalized_heights, Normalization, MAX_BINS};
+use std::error::Error;
+
+/// How a [`StreamingHistogram`] lays out its bins.
+#[derive(Clone, Debug, PartialEq)]
+enum Layout {
+    /// Caller-supplied edges; values outside go to underflow/overflow.
+    Fixed(Vec<f64>),
+    /// Bins `[k·width, (k+1)·width)` created as values arrive. `first` is the
+    /// index `k` of `counts[0]`; `width` doubles when more than `max_bins` are needed.
+    Auto { width: f64, first: i64, max_bins: usize },
+}
+
+/// Histogram accumulated one value at a time, with running moments.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamingHistogram {
+    layout: Layout,
+    counts: Vec<u64>,
+    underflow: u64,
+    overflow: u64,
+    n: u64,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl StreamingHistogram {
+    fn with_layout(layout: Layout, bins: usize) -> Self {
+        Self {
+            layout,
+            counts: vec![0; bins],
+            underflow: 0,
+            overflow: 0,
+            n: 0,
+            mean: 0.0,
+            m2: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    /// Bins on fixed `edges` (same bin rules as `counts_from_edges`).
+    pub fn fixed(edges: Vec<f64>) -> Self {
+        let bins = edges.len().saturating_sub(1);
+        Self::with_layout(Layout::Fixed(edges), bins)
+    }
+
+    /// Bins of `width` aligned to multiples of `width`, added as values arrive.
+    /// Once more than 1000 bins would be needed, the width doubles (see [`Self::max_bins`]).
+    pub fn auto(width: f64) -> Self {
+        Self::with_layout(
+            Layout::Auto {
+                width: width.abs().max(1e-300),
+                first: 0,
+                max_bins: 1000,
+            },
+            0,
+        )
+    }
+
+    /// Upper bound on the number of bins of an auto-expanding histogram.
+    pub fn max_bins(mut self, n: usize) -> Self {
+        if let Layout::Auto { max_bins, .. } = &mut self.layout {
+            *max_bins = n.max(2);
+        }
+        self.coarsen_to_fit();
+        self
+    }
+
+    pub fn push(&mut self, v: f64) {
+        if !v.is_finite() {
+            return;
+        }
+        // Welford's running mean and variance
+        self.n += 1;
+        let d = v - self.mean;
+        self.mean += d / self.n as f64;
+        self.m2 += d * (v - self.mean);
+        self.min = self.min.min(v);
+        self.max = self.max.max(v);
+
+        match &mut self.layout {
+            Layout::Fixed(edges) => {
+                let nb = self.counts.len();
+                if nb == 0 || v < edges[0] {
+                    self.underflow += 1;
+                } else if v > edges[nb] {
+                    self.overflow += 1;
+                } else {
+                    let i = edges.partition_point(|&e| e <= v).saturating_sub(1).min(nb - 1);
+                    self.counts[i] += 1;
+                }
+            }
+            Layout::Auto { .. } => self.add_auto(v, 1),
+        }
+    }
+
+    // Adds `count` to the auto bin containing `v`, growing the layout first
+    fn add_auto(&mut self, v: f64, count: u64) {
+        self.ensure_bin(v);
+        // ensure_bin may have doubled the width
+        if let Layout::Auto { width, first, .. } = self.layout {
+            let k = (v / width).floor() as i64;
+            self.counts[(k - first) as usize] += count;
+        }
+    }
+
+    /// Grows the auto layout to include the bin of `v`. The width is doubled
+    /// first until the current bins and that one fit in `max_bins`, so a far
+    /// outlier never allocates more than `max_bins` bins.
+    fn ensure_bin(&mut self, v: f64) {
+        loop {
+            let Layout::Auto { width, first, max_bins } = self.layout else {
+                return;
+            };
+            let k = (v / width).floor() as i64;
+            if self.counts.is_empty() {
+                self.layout = Layout::Auto { width, first: k, max_bins };
+                self.counts.push(0);
+                return;
+            }
+            let last = first + self.counts.len() as i64 - 1;
+            let span = i128::from(last.max(k)) - i128::from(first.min(k)) + 1;
+            if span > max_bins as i128 {
+                self.double_width();
+                continue;
+            }
+            if k < first {
+                self.counts.splice(0..0, std::iter::repeat_n(0, (first - k) as usize));
+                self.layout = Layout::Auto { width, first: k, max_bins };
+            } else if k > last {
+                self.counts.resize((k - first + 1) as usize, 0);
+            }
+            return;
+        }
+    }
+
+    // Doubles the auto bin width until the bins fit in `max_bins`
+    fn coarsen_to_fit(&mut self) {
+        while let Layout::Auto { max_bins, .. } = self.layout {
+            if self.counts.len() <= max_bins {
+                break;
+            }
+            self.double_width();
+        }
+    }
+
+    fn double_width(&mut self) {
+        let Layout::Auto { width, first, .. } = &mut self.layout else {
+            return;
+        };
+        let new_first = first.div_euclid(2);
+        let last = *first + self.counts.len() as i64 - 1;
+        let mut merged = vec![0u64; (last.div_euclid(2) - new_first + 1) as usize];
+        for (i, &c) in self.counts.iter().enumerate() {
+            let k = *first + i as i64;
+            merged[(k.div_euclid(2) - new_first) as usize] += c;
+        }
+        *width *= 2.0;
+        *first = new_first;
+        self.counts = merged;
+    }
+
+    pub fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) {
+        for v in values {
+            self.push(v);
+        }
+    }
+
+    /// Adds the contents of `other`. Fixed histograms need identical edges;
+    /// auto histograms need bin widths that differ by a power of two (the finer
+    /// one is coarsened).
+    pub fn merge(&mut self, other: &StreamingHistogram) -> Result<(), Box<dyn Error>> {
+        match (&self.layout, &other.layout) {
+            (Layout::Fixed(a), Layout::Fixed(b)) => {
+                if a != b {
+                    return Err("Cannot merge histograms with different edges".into());
+                }
+                for (c, o) in self.counts.iter_mut().zip(&other.counts) {
+                    *c += o;
+                }
+            }
+            (Layout::Auto { width: wa, .. }, Layout::Auto { width: wb, .. }) => {
+                let ratio = (wa.max(*wb) / wa.min(*wb)).log2();
+                if (ratio - ratio.round()).abs() > 1e-9 {
+                    return Err("Auto histogram widths must differ by a power of two".into());
+                }
+                let mut other = other.clone();
+                while other.width() < self.width() * (1.0 - 1e-9) {
+                    other.double_width();
+                }
+                while self.width() < other.width() * (1.0 - 1e-9) {
+                    self.double_width();
+                }
+                // Bins are aligned to multiples of the width, so re-adding each
+                // bin at its center lands it in the matching bin of `self`
+                if let Layout::Auto { width, first, .. } = other.layout {
+                    for (i, &c) in other.counts.iter().enumerate() {
+                        if c > 0 {
+                            self.add_auto((first + i as i64) as f64 * width + width / 2.0, c);
+                        }
+                    }
+                }
+            }
+            _ => return Err("Cannot merge a fixed-edge histogram with an auto-expanding one".into()),
+        }
+
+        // Chan et al. parallel combination of the moments
+        if other.n > 0 {
+            let (na, nb) = (self.n as f64, other.n as f64);
+            let d = other.mean - self.mean;
+            let n = na + nb;
+            self.mean += d * nb / n;
+            self.m2 += other.m2 + d * d * na * nb / n;
+            self.n += other.n;
+            self.min = self.min.min(other.min);
+            self.max = self.max.max(other.max);
+        }
+        self.underflow += other.underflow;
+        self.overflow += other.overflow;
+        Ok(())
+    }
+
+    // Current bin width (auto) or NaN (fixed)
+    fn width(&self) -> f64 {
+        match self.layout {
+            Layout::Auto { width, .. } => width,
+            Layout::Fixed(_) => f64::NAN,
+        }
+    }
+
+    /// Current bin edges.
+    pub fn edges(&self) -> Vec<f64> {
+        match &self.layout {
+            Layout::Fixed(edges) => edges.clone(),
+            Layout::Auto { width, first, .. } => (0..=self.counts.len())
+                .map(|i| (*first + i as i64) as f64 * width)
+                .collect(),
+        }
+    }
+
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Bar heights under `norm`, ready for `draw_histogram` with [`Self::edges`].
+    /// Underflow and overflow are not part of the total.
+    pub fn heights(&self, norm: Normalization) -> Vec<f64> {
+        let counts = self.counts.iter().map(|&c| c as usize).collect::<Vec<_>>();
+        normalized_heights(&counts, &self.edges(), norm)
+    }
+
+    /// Values below the first edge / above the last edge of a fixed histogram.
+    pub fn out_of_range(&self) -> (u64, u64) {
+        (self.underflow, self.overflow)
+    }
+
+    pub fn len(&self) -> u64 {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Sample standard deviation (n − 1 denominator).
+    pub fn std_dev(&self) -> f64 {
+        if self.n < 2 {
+            0.0
+        } else {
+            (self.m2 / (self.n - 1) as f64).sqrt()
+        }
+    }
+
+    pub fn min_max(&self) -> (f64, f64) {
+        (self.min, self.max)
+    }
+}
+
+/// Merging t-digest (Dunning & Ertl, 2019) for approximate streaming quantiles.
+///
+/// Centroids shrink toward both tails, so extreme quantiles (p99.9) stay
+/// accurate; `compression` bounds the number of centroids kept.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TDigest {
+    compression: f64,
+    /// (mean, weight), sorted by mean
+    centroids: Vec<(f64, f64)>,
+    buffer: Vec<(f64, f64)>,
+    total: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for TDigest {
+    fn default() -> Self {
+        Self::new(200.0)
+    }
+}
+
+impl TDigest {
+    pub fn new(compression: f64) -> Self {
+        Self {
+            compression: compression.max(10.0),
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            total: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn push(&mut self, v: f64) {
+        self.push_weighted(v, 1.0);
+    }
+
+    fn push_weighted(&mut self, v: f64, w: f64) {
+        if !v.is_finite() || w <= 0.0 {
+            return;
+        }
+        self.buffer.push((v, w));
+        self.min = self.min.min(v);
+        self.max = self.max.max(v);
+        if self.buffer.len() >= 5 * self.compression as usize {
+            self.flush();
+        }
+    }
+
+    pub fn extend<I: IntoIterator<Item = f64>>(&mut self, values: I) {
+        for v in values {
+            self.push(v);
+        }
+    }
+
+    /// Adds every centroid of `other`.
+    pub fn merge(&mut self, other: &TDigest) {
+        for &(m, w) in other.centroids.iter().chain(&other.buffer) {
+            self.push_weighted(m, w);
+        }
+    }
+
+    // Merges the buffer into the centroids under the k2 scale function
+    fn flush(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut all = std::mem::take(&mut self.centroids);
+        all.append(&mut self.buffer);
+        all.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let total = all.iter().map(|c| c.1).sum::<f64>();
+
+        // k2 scale: centroid sizes shrink like q(1 − q) toward both tails
+        let norm = self.compression / (4.0 * (total / self.compression).max(1.0).ln() + 24.0);
+        let k = |q: f64| norm * (q / (1.0 - q)).ln();
+        let k_inv = |k: f64| 1.0 / (1.0 + (-k / norm).exp());
+
+        let mut out: Vec<(f64, f64)> = Vec::with_capacity(self.compression as usize * 2);
+        let mut so_far = 0.0;
+        let mut limit = 0.0;
+        let mut cur = all[0];
+        for &(m, w) in &all[1..] {
+            if so_far + cur.1 + w <= limit {
+                let cw = cur.1 + w;
+                cur = (cur.0 + (m - cur.0) * w / cw, cw);
+            } else {
+                so_far += cur.1;
+                out.push(cur);
+                limit = total * k_inv(k(so_far / total) + 1.0);
+                cur = (m, w);
+            }
+        }
+        out.push(cur);
+
+        self.centroids = out;
+        self.total = total;
+    }
+
+    /// Number of values (total weight) seen, including those not yet merged.
+    pub fn count(&self) -> f64 {
+        self.total + self.buffer.iter().map(|c| c.1).sum::<f64>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.centroids.is_empty() && self.buffer.is_empty()
+    }
+
+    pub fn min_max(&self) -> (f64, f64) {
+        (self.min, self.max)
+    }
+
+    /// Approximate quantile `q` in [0, 1].
+    pub fn quantile(&mut self, q: f64) -> f64 {
+        self.flush();
+        let c = &self.centroids;
+        if c.is_empty() || !(0.0..=1.0).contains(&q) {
+            return f64::NAN;
+        }
+        if c.len() == 1 {
+            return c[0].0;
+        }
+        let target = q * self.total;
+
+        // Half of the first/last centroid interpolates out to the min/max
+        let first_half = c[0].1 / 2.0;
+        if target < first_half {
+            return self.min + (c[0].0 - self.min) * target / first_half;
+        }
+        let mut cum = first_half;
+        for win in c.windows(2) {
+            let step = (win[0].1 + win[1].1) / 2.0;
+            if target < cum + step {
+                let t = (target - cum) / step;
+                return win[0].0 + t * (win[1].0 - win[0].0);
+            }
+            cum += step;
+        }
+        let last = c[c.len() - 1];
+        let t = ((target - cum) / (last.1 / 2.0)).clamp(0.0, 1.0);
+        last.0 + t * (self.max - last.0)
+    }
+
+    /// Approximate interquartile range.
+    pub fn iqr(&mut self) -> f64 {
+        self.quantile(0.75) - self.quantile(0.25)
+    }
+
+    /// Freedman–Diaconis bin width `2·IQR / n^(1/3)` from the sketch (zero when the IQR is).
+    pub fn fd_bin_width(&mut self) -> f64 {
+        let n = self.count().max(2.0);
+        2.0 * self.iqr() / n.powf(1.0 / 3.0)
+    }
+
+    /// Freedman–Diaconis bin count over the observed range, at most [`MAX_BINS`];
+    /// Sturges when the IQR is zero (cf. `histogram::bins_fd`).
+    pub fn bins_fd(&mut self) -> usize {
+        let width = self.fd_bin_width();
+        if width.is_nan() || width <= 0.0 {
+            return bins_sturges(self.count() as usize);
+        }
+        ((self.max - self.min) / width).ceil().clamp(1.0, MAX_BINS as f64) as usize
+    }
+
+    /// Larger of the Sturges and Freedman–Diaconis counts (cf. `histogram::bins_auto`).
+    pub fn bins_auto(&mut self) -> usize {
+        let n = self.count() as usize;
+        bins_sturges(n).max(self.bins_fd()).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::{quantile_with, sorted, QuantileMethod};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use rand_distr::{Distribution, LogNormal, Normal};
+
+    fn normal_sample(seed: u64, n: usize) -> Vec<f64> {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        Normal::new(10.0, 3.0).unwrap().sample_iter(&mut rng).take(n).collect()
+    }
+
+    fn assert_same_histogram(a: &StreamingHistogram, b: &StreamingHistogram) {
+        assert_eq!(a.edges(), b.edges());
+        assert_eq!(a.counts(), b.counts());
+        assert_eq!(a.out_of_range(), b.out_of_range());
+        assert_eq!(a.len(), b.len());
+        assert!((a.mean() - b.mean()).abs() < 1e-9);
+        assert!((a.std_dev() - b.std_dev()).abs() < 1e-9);
+        assert_eq!(a.min_max(), b.min_max());
+    }
+
+    #[test]
+    fn fixed_merge_equals_sequential_pushes() {
+        let data = normal_sample(1, 2_000);
+        let edges = (0..=20).map(|i| i as f64).collect::<Vec<_>>();
+        let mut whole = StreamingHistogram::fixed(edges.clone());
+        whole.extend(data.iter().copied());
+
+        let (left, right) = data.split_at(700);
+        let mut merged = StreamingHistogram::fixed(edges.clone());
+        merged.extend(left.iter().copied());
+        let mut other = StreamingHistogram::fixed(edges);
+        other.extend(right.iter().copied());
+        merged.merge(&other).unwrap();
+        assert_same_histogram(&merged, &whole);
+    }
+
+    #[test]
+    fn auto_merge_equals_sequential_pushes() {
+        let data = normal_sample(2, 2_000);
+        let mut whole = StreamingHistogram::auto(0.5);
+        whole.extend(data.iter().copied());
+
+        let (left, right) = data.split_at(1_234);
+        let mut merged = StreamingHistogram::auto(0.5);
+        merged.extend(left.iter().copied());
+        // A finer histogram is coarsened to the width of `merged`
+        let mut other = StreamingHistogram::auto(0.125);
+        other.extend(right.iter().copied());
+        merged.merge(&other).unwrap();
+        assert_same_histogram(&merged, &whole);
+    }
+
+    #[test]
+    fn merge_rejects_mismatched_layouts() {
+        let mut fixed = StreamingHistogram::fixed(vec![0.0, 1.0, 2.0]);
+        assert!(fixed.merge(&StreamingHistogram::fixed(vec![0.0, 2.0])).is_err());
+        assert!(fixed.merge(&StreamingHistogram::auto(1.0)).is_err());
+        assert!(StreamingHistogram::auto(1.0).merge(&StreamingHistogram::auto(0.3)).is_err());
+    }
+
+    #[test]
+    fn auto_coarsening_keeps_totals() {
+        let data = normal_sample(3, 5_000);
+        let mut h = StreamingHistogram::auto(0.001).max_bins(50);
+        h.extend(data.iter().copied());
+        assert!(h.counts().len() <= 50);
+        assert_eq!(h.counts().iter().sum::<u64>(), 5_000);
+        assert_eq!(h.len(), 5_000);
+
+        // Every value still falls inside its bin's edges
+        let edges = h.edges();
+        let (lo, hi) = h.min_max();
+        assert!(edges[0] <= lo && hi < edges[edges.len() - 1]);
+    }
+
+    #[test]
+    fn far_outlier_does_not_blow_up_auto_bins() {
+        let mut h = StreamingHistogram::auto(1.0).max_bins(100);
+        h.extend([0.5, 1.5, 2.5]);
+        h.push(1e15);
+        h.push(-1e15);
+        assert!(h.counts().len() <= 100, "{} bins", h.counts().len());
+        assert_eq!(h.counts().iter().sum::<u64>(), 5);
+    }
+
+    #[test]
+    fn tdigest_quantiles_are_close_to_exact() {
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+        let data = LogNormal::new(0.0, 0.8)
+            .unwrap()
+            .sample_iter(&mut rng)
+            .take(100_000)
+            .collect::<Vec<f64>>();
+        let exact = sorted(&data);
+        let mut digest = TDigest::default();
+        digest.extend(data.iter().copied());
+
+        for q in [0.001, 0.01, 0.1, 0.25, 0.5, 0.75, 0.9, 0.99, 0.999] {
+            let e = quantile_with(&exact, q, QuantileMethod::Type7);
+            let d = digest.quantile(q);
+            // The rank error of a t-digest shrinks toward the tails
+            let rank = exact.partition_point(|&v| v < d) as f64 / exact.len() as f64;
+            assert!((rank - q).abs() < 0.005 * (q * (1.0 - q)).sqrt().max(0.02), "q = {}: {} vs exact {}", q, d, e);
+        }
+        assert_eq!(digest.min_max(), (exact[0], exact[exact.len() - 1]));
+    }
+
+    #[test]
+    fn tdigest_merge_matches_single_digest() {
+        let data = normal_sample(5, 50_000);
+        let mut whole = TDigest::default();
+        whole.extend(data.iter().copied());
+        let mut merged = TDigest::default();
+        for chunk in data.chunks(7_000) {
+            let mut part = TDigest::default();
+            part.extend(chunk.iter().copied());
+            merged.merge(&part);
+        }
+        assert_eq!(merged.count(), whole.count());
+        for q in [0.01, 0.5, 0.99] {
+            assert!((merged.quantile(q) - whole.quantile(q)).abs() < 0.05, "q = {}", q);
+        }
+    }
+
+    #[test]
+    fn tdigest_bin_counts_are_bounded() {
+        let mut repeated = TDigest::default();
+        repeated.extend(std::iter::repeat_n(1.0, 100).chain([2.0, 100.0]));
+        assert_eq!(repeated.iqr(), 0.0);
+        assert_eq!(repeated.bins_fd(), bins_sturges(102));
+        assert_eq!(repeated.bins_auto(), 8);
+
+        let mut skewed = TDigest::default();
+        skewed.extend((0..1_000).map(|i| i as f64 * 1e-9).chain([1e9]));
+        assert_eq!(skewed.bins_fd(), MAX_BINS);
+
+        assert_eq!(TDigest::default().bins_auto(), 1);
+    }
+
+    #[test]
+    fn tdigest_count_includes_buffered_values() {
+        let mut digest = TDigest::new(100.0);
+        assert!(digest.is_empty());
+        digest.extend((0..10).map(f64::from));
+        assert_eq!(digest.count(), 10.0);
+        digest.extend((0..1_000).map(f64::from));
+        assert_eq!(digest.count(), 1_010.0);
+        assert_eq!(digest.quantile(0.0), 0.0);
+    }
+}
@@ -0,0 +1,281 @@
+// Themes: background, grid, axis and text colors, font sizes, palette, colormap and line widths.
+//
+// Like the DPI in `units`, the theme is set for the duration of a draw (with
+// `with_theme`) and read by the drawing helpers through `theme()`, so every
+// chart in a figure, and every figure an example run writes, looks the same.
+// Sizes are in points and converted with `pt` at the current DPI.
+
+use crate::color::{Colormap, BASIC_PALETTE, DARK_PALETTE, GRAY_PALETTE, OKABE_ITO};
+use crate::fonts::register_fonts;
+use crate::units::{pt, pt_f64};
+use plotters::chart::{MeshStyle, SecondaryMeshStyle, SeriesLabelStyle};
+use plotters::coord::ranged1d::{Ranged, ValueFormatter};
+use plotters::coord::{CoordTranslate, Shift};
+use plotters::prelude::*;
+use std::cell::Cell;
+use std::error::Error;
+use std::str::FromStr;
+
+/// How charts look. Sizes are in points.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub name: &'static str,
+    pub background: RGBColor,
+    /// Text, and the default for outlines such as box plot whiskers.
+    pub foreground: RGBColor,
+    pub axis: RGBColor,
+    /// Grid lines at the tick labels.
+    pub grid: RGBAColor,
+    /// Grid lines between the tick labels.
+    pub minor_grid: RGBAColor,
+    /// One of the bundled [`crate::fonts::FAMILIES`].
+    pub font_family: &'static str,
+    pub title_size: f64,
+    /// Tick labels and legend entries.
+    pub label_size: f64,
+    /// Axis descriptions.
+    pub desc_size: f64,
+    pub line_width: f64,
+    pub axis_width: f64,
+    pub tick_size: f64,
+    pub margin: f64,
+    pub x_label_area: f64,
+    pub y_label_area: f64,
+    /// Series colors, used in order and repeated.
+    pub palette: &'static [RGBColor],
+    /// Colors for continuous values, such as heatmap cells.
+    pub colormap: Colormap,
+}
+
+impl Theme {
+    /// Names accepted by [`Theme::from_str`].
+    pub const NAMES: [&'static str; 5] = ["light", "dark", "print", "presentation", "publication"];
+
+    /// Black on white with a light grid; the default.
+    pub const fn light() -> Self {
+        Self {
+            name: "light",
+            background: WHITE,
+            foreground: BLACK,
+            axis: BLACK,
+            grid: RGBAColor(0, 0, 0, 0.2),
+            minor_grid: RGBAColor(0, 0, 0, 0.1),
+            font_family: "sans-serif",
+            title_size: 10.0,
+            label_size: 6.0,
+            desc_size: 6.5,
+            line_width: 0.75,
+            axis_width: 0.25,
+            tick_size: 1.25,
+            margin: 5.0,
+            x_label_area: 14.5,
+            y_label_area: 19.0,
+            palette: &BASIC_PALETTE,
+            colormap: Colormap::VIRIDIS,
+        }
+    }
+
+    /// Light text and lines on a dark gray background.
+    pub const fn dark() -> Self {
+        Self {
+            name: "dark",
+            background: RGBColor(30, 32, 38),
+            foreground: RGBColor(225, 225, 225),
+            axis: RGBColor(170, 170, 170),
+            grid: RGBAColor(255, 255, 255, 0.18),
+            minor_grid: RGBAColor(255, 255, 255, 0.06),
+            palette: &DARK_PALETTE,
+            ..Self::light()
+        }
+    }
+
+    /// Grayscale series, a faint grid and slightly heavier lines for black-and-white printing.
+    pub const fn print() -> Self {
+        Self {
+            name: "print",
+            grid: RGBAColor(0, 0, 0, 0.15),
+            minor_grid: RGBAColor(0, 0, 0, 0.05),
+            line_width: 1.0,
+            axis_width: 0.5,
+            palette: &GRAY_PALETTE,
+            colormap: Colormap::CIVIDIS,
+            ..Self::light()
+        }
+    }
+
+    /// Large text and thick lines that stay legible on a projector.
+    pub const fn presentation() -> Self {
+        Self {
+            name: "presentation",
+            title_size: 14.0,
+            label_size: 9.0,
+            desc_size: 10.0,
+            line_width: 1.5,
+            axis_width: 0.75,
+            tick_size: 2.0,
+            margin: 8.0,
+            x_label_area: 22.0,
+            y_label_area: 28.0,
+            ..Self::light()
+        }
+    }
+
+    /// Serif text sized for a journal column, with a faint major grid only and
+    /// the colorblind-safe Okabe–Ito palette.
+    pub const fn publication() -> Self {
+        Self {
+            name: "publication",
+            grid: RGBAColor(0, 0, 0, 0.12),
+            minor_grid: RGBAColor(0, 0, 0, 0.0),
+            font_family: "serif",
+            title_size: 9.0,
+            label_size: 7.0,
+            desc_size: 8.0,
+            line_width: 1.0,
+            axis_width: 0.5,
+            x_label_area: 17.0,
+            y_label_area: 22.0,
+            palette: &OKABE_ITO,
+            ..Self::light()
+        }
+    }
+
+    /// This theme with other series colors, e.g. [`crate::color::OKABE_ITO`].
+    pub const fn with_palette(self, palette: &'static [RGBColor]) -> Self {
+        Self { palette, ..self }
+    }
+
+    /// This theme with another colormap.
+    pub const fn with_colormap(self, colormap: Colormap) -> Self {
+        Self { colormap, ..self }
+    }
+
+    /// Text in the theme's font and color at `points` size.
+    pub fn text(&self, points: f64) -> TextStyle<'static> {
+        register_fonts();
+        (self.font_family, pt_f64(points)).into_font().color(&self.foreground)
+    }
+
+    pub fn title_font(&self) -> TextStyle<'static> {
+        self.text(self.title_size)
+    }
+
+    pub fn label_font(&self) -> TextStyle<'static> {
+        self.text(self.label_size)
+    }
+
+    pub fn desc_font(&self) -> TextStyle<'static> {
+        self.text(self.desc_size)
+    }
+
+    /// The `i`-th series color, cycling through the palette.
+    pub fn color(&self, i: usize) -> RGBColor {
+        self.palette[i % self.palette.len()]
+    }
+
+    /// The default series line width in pixels.
+    pub fn stroke(&self) -> u32 {
+        pt(self.line_width)
+    }
+
+    /// A chart builder on `root` with the theme's margin and label areas.
+    pub fn chart<'a, 'b, DB: DrawingBackend>(&self, root: &'a DrawingArea<DB, Shift>) -> ChartBuilder<'a, 'b, DB> {
+        register_fonts();
+        let mut builder = ChartBuilder::on(root);
+        builder
+            .margin(pt(self.margin))
+            .x_label_area_size(pt(self.x_label_area))
+            .y_label_area_size(pt(self.y_label_area));
+        builder
+    }
+
+    /// Applies the grid, axis, tick and text styles to a mesh.
+    pub fn mesh<'m, 'a, 'b, X, Y, DB>(&self, mesh: &'m mut MeshStyle<'a, 'b, X, Y, DB>) -> &'m mut MeshStyle<'a, 'b, X, Y, DB>
+    where
+        X: Ranged,
+        Y: Ranged,
+        DB: DrawingBackend,
+    {
+        mesh.set_all_tick_mark_size(pt(self.tick_size))
+            .bold_line_style(self.grid.stroke_width(pt(self.axis_width)))
+            .light_line_style(self.minor_grid.stroke_width(pt(self.axis_width)))
+            .axis_style(self.axis.stroke_width(pt(self.axis_width)))
+            .label_style(self.label_font())
+            .axis_desc_style(self.desc_font())
+    }
+
+    /// Applies the axis, tick and text styles to secondary axes.
+    pub fn secondary_axes<'m, 'a, 'b, X, Y, DB>(
+        &self,
+        axes: &'m mut SecondaryMeshStyle<'a, 'b, X, Y, DB>,
+    ) -> &'m mut SecondaryMeshStyle<'a, 'b, X, Y, DB>
+    where
+        X: Ranged + ValueFormatter<X::ValueType>,
+        Y: Ranged + ValueFormatter<Y::ValueType>,
+        DB: DrawingBackend,
+    {
+        axes.set_all_tick_mark_size(pt(self.tick_size))
+            .axis_style(self.axis.stroke_width(pt(self.axis_width)))
+            .label_style(self.label_font())
+            .axis_desc_style(self.desc_font())
+    }
+
+    /// Applies the text, background and border styles to a legend.
+    pub fn legend<'m, 'a, 'b, DB, CT>(
+        &self,
+        legend: &'m mut SeriesLabelStyle<'a, 'b, DB, CT>,
+    ) -> &'m mut SeriesLabelStyle<'a, 'b, DB, CT>
+    where
+        DB: DrawingBackend + 'a,
+        CT: CoordTranslate,
+    {
+        legend
+            .label_font(self.label_font())
+            .legend_area_size(pt(7.5))
+            .margin(pt(2.5))
+            .background_style(self.background.mix(0.85))
+            .border_style(self.axis)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::light()
+    }
+}
+
+impl FromStr for Theme {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "light" => Ok(Self::light()),
+            "dark" => Ok(Self::dark()),
+            "print" | "grayscale" | "greyscale" => Ok(Self::print()),
+            "presentation" => Ok(Self::presentation()),
+            "publication" => Ok(Self::publication()),
+            _ => Err(format!("Unknown theme '{}' (expected one of: {})", s, Self::NAMES.join(", ")).into()),
+        }
+    }
+}
+
+thread_local! {
+    static THEME: Cell<Theme> = const { Cell::new(Theme::light()) };
+}
+
+/// The theme of the figure being drawn on this thread (light outside [`with_theme`]).
+pub fn theme() -> Theme {
+    THEME.with(Cell::get)
+}
+
+/// Runs `f` with [`theme`] set to `theme`, restoring the previous one afterwards.
+pub fn with_theme<R>(theme: Theme, f: impl FnOnce() -> R) -> R {
+    struct Restore(Theme);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            THEME.with(|t| t.set(self.0));
+        }
+    }
+    let _restore = Restore(THEME.with(|t| t.replace(theme)));
+    f()
+}
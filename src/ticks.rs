@@ -0,0 +1,209 @@
+// Reusable tick label formatters.
+//
+// A `TickFormat` turns an axis value into label text. Use it with the helpers
+// in `line` (`draw_mesh_f64_with`, `AxisSpec::tick_format`) or directly with
+// plotters: `.y_label_formatter(&|v| fmt.format(*v))`.
+
+const SI_PREFIXES: [&str; 17] = ["y", "z", "a", "f", "p", "n", "µ", "m", "", "k", "M", "G", "T", "P", "E", "Z", "Y"];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TickFormat {
+    /// Fixed number of decimals: `Fixed(2)` gives `3.14`.
+    Fixed(usize),
+    /// Significant digits: `Significant(3)` gives `0.00123`, `12300`.
+    Significant(usize),
+    /// Scientific notation with the given decimals: `1.50e6`.
+    Scientific(usize),
+    /// Engineering notation (exponent a multiple of 3): `15.0e3`.
+    Engineering(usize),
+    /// SI prefix (k, M, G, m, µ, n, ...): `4.7k`, `220n`.
+    Si(usize),
+    /// Fraction shown as a percentage: `0.25` gives `25%` with `Percent(0)`.
+    Percent(usize),
+    /// Thousands separators: `1,234,567.9` with `Thousands(1)`.
+    Thousands(usize),
+    /// Currency symbol prefix with thousands separators: `-$1,200.00`.
+    Currency(String, usize),
+    /// Explicit value → label pairs; other values keep their plain formatting.
+    Map(Vec<(f64, String)>),
+    /// Another format followed by a unit suffix, e.g. `" hPa"`.
+    Unit(Box<TickFormat>, String),
+}
+
+impl TickFormat {
+    /// Appends a unit suffix to every label.
+    pub fn with_unit(self, unit: &str) -> Self {
+        TickFormat::Unit(Box::new(self), unit.to_string())
+    }
+
+    /// Builds a `Map` from `(value, label)` pairs.
+    pub fn map<S: AsRef<str>>(pairs: &[(f64, S)]) -> Self {
+        TickFormat::Map(pairs.iter().map(|(v, l)| (*v, l.as_ref().to_string())).collect())
+    }
+
+    pub fn format(&self, v: f64) -> String {
+        if !v.is_finite() {
+            return format!("{v}");
+        }
+        match self {
+            TickFormat::Fixed(d) => no_neg_zero(format!("{:.*}", d, v)),
+            TickFormat::Significant(d) => significant(v, *d),
+            TickFormat::Scientific(d) => format!("{:.*e}", d, v),
+            TickFormat::Engineering(d) => {
+                if v == 0.0 {
+                    return format!("{:.*}", d, 0.0);
+                }
+                let (m, e) = split_exp3(v, *d);
+                format!("{:.*}e{}", d, m, e)
+            }
+            TickFormat::Si(d) => {
+                if v == 0.0 {
+                    return format!("{:.*}", d, 0.0);
+                }
+                let (m, e) = split_exp3(v, *d);
+                let idx = (e / 3 + 8).clamp(0, 16) as usize;
+                let m = m * 10f64.powi(e - (idx as i32 - 8) * 3);
+                format!("{:.*}{}", d, m, SI_PREFIXES[idx])
+            }
+            TickFormat::Percent(d) => no_neg_zero(format!("{:.*}", d, v * 100.0)) + "%",
+            TickFormat::Thousands(d) => thousands(v, *d),
+            TickFormat::Currency(sym, d) => {
+                let s = thousands(v, *d);
+                match s.strip_prefix('-') {
+                    Some(abs) => format!("-{sym}{abs}"),
+                    None => format!("{sym}{s}"),
+                }
+            }
+            TickFormat::Map(pairs) => pairs
+                .iter()
+                .find(|(k, _)| (k - v).abs() <= 1e-9 * k.abs().max(1.0))
+                .map(|(_, l)| l.clone())
+                .unwrap_or_else(|| format!("{v}")),
+            TickFormat::Unit(inner, unit) => format!("{}{}", inner.format(v), unit),
+        }
+    }
+}
+
+// Turns "-0.00" (a tiny negative rounded away) into "0.00"
+fn no_neg_zero(s: String) -> String {
+    match s.strip_prefix('-') {
+        Some(abs) if abs.chars().all(|c| c == '0' || c == '.') => abs.to_string(),
+        _ => s,
+    }
+}
+
+fn significant(v: f64, digits: usize) -> String {
+    if v == 0.0 {
+        return "0".to_string();
+    }
+    let digits = digits.max(1) as i32;
+    let mut exp = v.abs().log10().floor() as i32;
+    let scale = 10f64.powi(digits - 1 - exp);
+    let rounded = (v * scale).round() / scale;
+    // 9.99 rounds up to 10: one more integer digit leaves one decimal fewer
+    if rounded.abs() >= 10f64.powi(exp + 1) {
+        exp += 1;
+    }
+    let decimals = (digits - 1 - exp).max(0) as usize;
+    format!("{:.*}", decimals, rounded)
+}
+
+// Mantissa in [1, 1000) and exponent multiple of 3, after rounding to `d` decimals
+fn split_exp3(v: f64, d: usize) -> (f64, i32) {
+    let mut e = (v.abs().log10().floor() as i32).div_euclid(3) * 3;
+    let mut m = v / 10f64.powi(e);
+    let p = 10f64.powi(d as i32);
+    if ((m.abs() * p).round() / p) >= 1000.0 {
+        e += 3;
+        m /= 1000.0;
+    }
+    (m, e)
+}
+
+fn thousands(v: f64, d: usize) -> String {
+    let s = format!("{:.*}", d, v.abs());
+    let (int, frac) = match s.split_once('.') {
+        Some((i, f)) => (i.to_string(), Some(f.to_string())),
+        None => (s, None),
+    };
+    let mut grouped = String::new();
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    let out = match frac {
+        Some(f) => format!("{grouped}.{f}"),
+        None => grouped,
+    };
+    if v < 0.0 {
+        no_neg_zero(format!("-{out}"))
+    } else {
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(f: TickFormat, values: &[f64]) -> Vec<String> {
+        values.iter().map(|&v| f.format(v)).collect()
+    }
+
+    #[test]
+    fn fixed_and_percent_drop_negative_zero() {
+        assert_eq!(fmt(TickFormat::Fixed(2), &[1.23456, -0.001, -1.5]), ["1.23", "0.00", "-1.50"]);
+        assert_eq!(fmt(TickFormat::Percent(0), &[0.25, 1.0]), ["25%", "100%"]);
+        assert_eq!(TickFormat::Percent(1).format(-0.0001), "0.0%");
+    }
+
+    #[test]
+    fn significant_digits() {
+        assert_eq!(fmt(TickFormat::Significant(3), &[0.00123, 12345.0, 0.0, -2.5]), ["0.00123", "12300", "0", "-2.50"]);
+    }
+
+    #[test]
+    fn significant_rounding_across_power_of_ten() {
+        assert_eq!(TickFormat::Significant(2).format(9.99), "10");
+        assert_eq!(TickFormat::Significant(2).format(0.0999), "0.10");
+        assert_eq!(TickFormat::Significant(1).format(-9.6), "-10");
+        assert_eq!(TickFormat::Significant(3).format(999.7), "1000");
+    }
+
+    #[test]
+    fn scientific_and_engineering() {
+        assert_eq!(TickFormat::Scientific(2).format(1.5e6), "1.50e6");
+        assert_eq!(fmt(TickFormat::Engineering(1), &[15_000.0, 0.0]), ["15.0e3", "0.0"]);
+        assert_eq!(TickFormat::Engineering(2).format(0.00047), "470.00e-6");
+        // Rounding to 1000 moves to the next exponent
+        assert_eq!(TickFormat::Engineering(1).format(999.96), "1.0e3");
+    }
+
+    #[test]
+    fn si_prefixes() {
+        assert_eq!(TickFormat::Si(1).format(4700.0), "4.7k");
+        assert_eq!(TickFormat::Si(0).format(2.2e-7), "220n");
+        assert_eq!(TickFormat::Si(1).format(999.96), "1.0k");
+        assert_eq!(TickFormat::Si(1).format(-3.3e6), "-3.3M");
+        // Beyond the largest prefix the mantissa grows instead
+        assert_eq!(TickFormat::Si(0).format(1e30), "1000000Y");
+    }
+
+    #[test]
+    fn thousands_and_currency() {
+        assert_eq!(fmt(TickFormat::Thousands(1), &[1_234_567.89, 12.0]), ["1,234,567.9", "12.0"]);
+        assert_eq!(TickFormat::Thousands(0).format(-999.6), "-1,000");
+        assert_eq!(TickFormat::Thousands(0).format(-0.4), "0");
+        assert_eq!(fmt(TickFormat::Currency("$".into(), 2), &[-1200.0, 5.0]), ["-$1,200.00", "$5.00"]);
+    }
+
+    #[test]
+    fn map_unit_and_non_finite() {
+        let map = TickFormat::map(&[(0.0, "low"), (1.0, "high")]);
+        assert_eq!(fmt(map, &[1.0 + 1e-12, 0.0, 0.5]), ["high", "low", "0.5"]);
+        assert_eq!(TickFormat::Fixed(0).with_unit(" hPa").format(1013.2), "1013 hPa");
+        assert_eq!(fmt(TickFormat::Fixed(2), &[f64::NAN, f64::NEG_INFINITY]), ["NaN", "-inf"]);
+    }
+}
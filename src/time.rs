@@ -0,0 +1,393 @@
+// Date/time axes for time-series plots.
+//
+// Times are plain `f64` seconds (Unix epoch seconds for timestamps, elapsed
+// seconds for durations) so series keep using the same `(f64, f64)` points as
+// every other helper; only the axis knows how to place and label ticks.
+
+use crate::theme::theme;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use plotters::coord::ranged1d::{KeyPointHint, NoDefaultFormatting, Ranged, ValueFormatter};
+use plotters::coord::types::RangedCoordf64;
+use plotters::prelude::*;
+use std::error::Error;
+use std::ops::Range;
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 3600;
+const DAY: i64 = 86_400;
+
+// Candidate tick intervals, smallest first
+const FIXED_STEPS: [i64; 21] = [
+    1, 2, 5, 10, 15, 30, // seconds
+    MINUTE, 2 * MINUTE, 5 * MINUTE, 10 * MINUTE, 15 * MINUTE, 30 * MINUTE, // minutes
+    HOUR, 2 * HOUR, 3 * HOUR, 6 * HOUR, 12 * HOUR, // hours
+    DAY, 2 * DAY, 7 * DAY, 14 * DAY, // days
+];
+const MONTH_STEPS: [i32; 6] = [1, 2, 3, 6, 12, 24];
+
+/// Whether axis values are points in time or elapsed times.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeKind {
+    /// Unix epoch seconds, labelled as UTC dates/times.
+    Timestamp,
+    /// Elapsed seconds, labelled as `mm:ss`, `h:mm:ss` or `Nd hh:mm`.
+    Duration,
+}
+
+/// Tick interval chosen for a time axis.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeStep {
+    Seconds(i64),
+    Months(i32),
+}
+
+/// A time axis usable in `build_cartesian_2d`, with automatic tick intervals and labels.
+#[derive(Clone, Debug)]
+pub struct TimeAxis {
+    range: Range<f64>,
+    kind: TimeKind,
+    labels: usize,
+}
+
+impl TimeAxis {
+    pub fn timestamps(range: Range<f64>) -> Self {
+        Self {
+            range,
+            kind: TimeKind::Timestamp,
+            labels: 8,
+        }
+    }
+
+    pub fn durations(range: Range<f64>) -> Self {
+        Self {
+            range,
+            kind: TimeKind::Duration,
+            labels: 8,
+        }
+    }
+
+    /// Target number of labelled ticks (default 8). Pass the same value to `x_labels`.
+    pub fn labels(mut self, n: usize) -> Self {
+        self.labels = n.max(2);
+        self
+    }
+
+    pub fn kind(&self) -> TimeKind {
+        self.kind
+    }
+
+    /// Picks the smallest interval giving at most `max_ticks` ticks over the range.
+    pub fn step(&self, max_ticks: usize) -> TimeStep {
+        let span = (self.range.end - self.range.start).abs();
+        let max_ticks = max_ticks.max(2) as f64;
+
+        if let Some(&s) = FIXED_STEPS.iter().find(|&&s| span / s as f64 <= max_ticks) {
+            return TimeStep::Seconds(s);
+        }
+        match self.kind {
+            TimeKind::Timestamp => {
+                let months = span / (30.44 * DAY as f64);
+                let k = MONTH_STEPS
+                    .iter()
+                    .copied()
+                    .find(|&m| months / m as f64 <= max_ticks)
+                    .unwrap_or_else(|| 12 * nice_years(months / 12.0 / max_ticks));
+                TimeStep::Months(k)
+            }
+            TimeKind::Duration => TimeStep::Seconds(DAY * nice_years(span / DAY as f64 / max_ticks) as i64),
+        }
+    }
+
+    /// Tick positions for the given interval.
+    pub fn ticks(&self, step: TimeStep) -> Vec<f64> {
+        let (lo, hi) = (self.range.start.min(self.range.end), self.range.start.max(self.range.end));
+        match step {
+            TimeStep::Seconds(s) => {
+                // Weeks start on Monday (the epoch was a Thursday)
+                let offset = if self.kind == TimeKind::Timestamp && s % (7 * DAY) == 0 { -3 * DAY } else { 0 };
+                let first = ((lo - offset as f64) / s as f64).ceil() as i64;
+                (first..)
+                    .map(|k| (k * s + offset) as f64)
+                    .take_while(|&t| t <= hi)
+                    .collect()
+            }
+            TimeStep::Months(k) => {
+                let Some(start) = to_datetime(lo) else {
+                    return vec![];
+                };
+                let mut idx = start.year() * 12 + start.month0() as i32;
+                idx += (k - idx.rem_euclid(k)) % k;
+                let mut out = Vec::new();
+                loop {
+                    let t = month_start(idx);
+                    if t > hi {
+                        break;
+                    }
+                    if t >= lo {
+                        out.push(t);
+                    }
+                    idx += k;
+                }
+                out
+            }
+        }
+    }
+
+    /// Label for `t` when ticks are `step` apart.
+    pub fn format(&self, t: f64, step: TimeStep) -> String {
+        match self.kind {
+            TimeKind::Timestamp => {
+                let Some(dt) = to_datetime(t) else {
+                    return format!("{t}");
+                };
+                let multi_day = (self.range.end - self.range.start).abs() > DAY as f64;
+                let pattern = match step {
+                    TimeStep::Seconds(s) if s < MINUTE => "%H:%M:%S",
+                    TimeStep::Seconds(s) if s < DAY && multi_day => "%m-%d %H:%M",
+                    TimeStep::Seconds(s) if s < DAY => "%H:%M",
+                    TimeStep::Seconds(_) => "%Y-%m-%d",
+                    TimeStep::Months(k) if k < 12 => "%Y-%m",
+                    TimeStep::Months(_) => "%Y",
+                };
+                dt.format(pattern).to_string()
+            }
+            TimeKind::Duration => {
+                let step_s = match step {
+                    TimeStep::Seconds(s) => s,
+                    TimeStep::Months(_) => DAY,
+                };
+                format_duration(t, step_s, self.range.end.max(self.range.start))
+            }
+        }
+    }
+}
+
+impl Ranged for TimeAxis {
+    type FormatOption = NoDefaultFormatting;
+    type ValueType = f64;
+
+    fn map(&self, value: &f64, limit: (i32, i32)) -> i32 {
+        RangedCoordf64::from(self.range.clone()).map(value, limit)
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<f64> {
+        self.ticks(self.step(hint.max_num_points()))
+    }
+
+    fn range(&self) -> Range<f64> {
+        self.range.clone()
+    }
+}
+
+impl ValueFormatter<f64> for TimeAxis {
+    fn format_ext(&self, value: &f64) -> String {
+        self.format(*value, self.step(self.labels))
+    }
+}
+
+/// Applies the project's mesh styling to a chart whose x axis is a [`TimeAxis`].
+pub fn draw_time_mesh<DB>(
+    chart: &mut ChartContext<'_, DB, Cartesian2d<TimeAxis, RangedCoordf64>>,
+    x_desc: &str,
+    y_desc: &str,
+) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let labels = chart.as_coord_spec().x_spec().labels;
+    theme()
+        .mesh(&mut chart.configure_mesh())
+        .x_desc(x_desc)
+        .y_desc(y_desc)
+        .x_labels(labels)
+        .draw()?;
+    Ok(())
+}
+
+/// Parses a date/time cell into Unix epoch seconds (UTC).
+///
+/// Accepts RFC 3339 (`2024-03-01T12:00:00Z`), `YYYY-MM-DD[ T]HH:MM[:SS]`,
+/// `YYYY-MM-DD`, `YYYY/MM/DD` and plain numeric epoch seconds.
+pub fn parse_timestamp(s: &str) -> Result<f64, Box<dyn Error>> {
+    let s = s.trim();
+    if let Ok(v) = s.parse::<f64>() {
+        return Ok(v);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.timestamp() as f64 + dt.timestamp_subsec_nanos() as f64 * 1e-9);
+    }
+    for fmt in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Ok(dt.and_utc().timestamp() as f64);
+        }
+    }
+    for fmt in ["%Y-%m-%d", "%Y/%m/%d"] {
+        if let Ok(d) = NaiveDate::parse_from_str(s, fmt) {
+            return Ok(d.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp() as f64);
+        }
+    }
+    Err(format!("Unrecognized date/time: {s}").into())
+}
+
+/// Parses an elapsed time (`h:mm:ss`, `mm:ss` or plain seconds) into seconds.
+pub fn parse_duration(s: &str) -> Result<f64, Box<dyn Error>> {
+    let s = s.trim();
+    let parts = s.split(':').map(|p| p.trim().parse::<f64>()).collect::<Result<Vec<_>, _>>()?;
+    match parts.as_slice() {
+        [sec] => Ok(*sec),
+        [m, sec] => Ok(m * 60.0 + sec),
+        [h, m, sec] => Ok(h * 3600.0 + m * 60.0 + sec),
+        _ => Err(format!("Unrecognized duration: {s}").into()),
+    }
+}
+
+fn to_datetime(t: f64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(t.floor() as i64, 0).single()
+}
+
+fn month_start(idx: i32) -> f64 {
+    let (y, m) = (idx.div_euclid(12), idx.rem_euclid(12) as u32 + 1);
+    Utc.with_ymd_and_hms(y, m, 1, 0, 0, 0).unwrap().timestamp() as f64
+}
+
+// 1, 2, 5, 10, 20, 50, ... at least `x`
+fn nice_years(x: f64) -> i32 {
+    let mut base = 1;
+    loop {
+        for m in [1, 2, 5] {
+            if (base * m) as f64 >= x {
+                return base * m;
+            }
+        }
+        base *= 10;
+    }
+}
+
+fn format_duration(t: f64, step: i64, max: f64) -> String {
+    let sign = if t < 0.0 { "-" } else { "" };
+    let total = t.abs().round() as i64;
+    let (d, h, m, s) = (total / DAY, (total % DAY) / HOUR, (total % HOUR) / MINUTE, total % MINUTE);
+
+    if max.abs() >= DAY as f64 {
+        if step % DAY == 0 {
+            format!("{sign}{d}d")
+        } else {
+            format!("{sign}{d}d {h:02}:{m:02}")
+        }
+    } else if max.abs() >= HOUR as f64 {
+        if step % MINUTE == 0 {
+            format!("{sign}{}:{m:02}", d * 24 + h)
+        } else {
+            format!("{sign}{}:{m:02}:{s:02}", d * 24 + h)
+        }
+    } else {
+        format!("{sign}{:02}:{s:02}", total / MINUTE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Weekday;
+
+    fn ts(y: i32, m: u32, d: u32, h: u32) -> f64 {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap().timestamp() as f64
+    }
+
+    fn labels(axis: &TimeAxis, step: TimeStep) -> Vec<String> {
+        axis.ticks(step).into_iter().map(|t| axis.format(t, step)).collect()
+    }
+
+    #[test]
+    fn step_is_smallest_interval_within_tick_budget() {
+        assert_eq!(TimeAxis::step(&TimeAxis::timestamps(0.0..60.0), 8), TimeStep::Seconds(10));
+        assert_eq!(TimeAxis::step(&TimeAxis::timestamps(0.0..DAY as f64), 8), TimeStep::Seconds(3 * HOUR));
+        assert_eq!(TimeAxis::step(&TimeAxis::timestamps(ts(2023, 1, 1, 0)..ts(2024, 1, 1, 0)), 8), TimeStep::Months(2));
+        assert_eq!(TimeAxis::step(&TimeAxis::timestamps(ts(1990, 1, 1, 0)..ts(2020, 1, 1, 0)), 8), TimeStep::Months(60));
+        // Durations never switch to calendar months
+        assert_eq!(TimeAxis::step(&TimeAxis::durations(0.0..30.0 * DAY as f64), 8), TimeStep::Seconds(7 * DAY));
+        assert_eq!(TimeAxis::step(&TimeAxis::durations(0.0..200.0 * DAY as f64), 8), TimeStep::Seconds(50 * DAY));
+    }
+
+    #[test]
+    fn month_ticks_follow_calendar_month_lengths() {
+        let axis = TimeAxis::timestamps(ts(2024, 1, 15, 0)..ts(2024, 5, 10, 0));
+        let ticks = axis.ticks(TimeStep::Months(1));
+        assert_eq!(ticks, vec![ts(2024, 2, 1, 0), ts(2024, 3, 1, 0), ts(2024, 4, 1, 0), ts(2024, 5, 1, 0)]);
+        let days = ticks.windows(2).map(|w| (w[1] - w[0]) / DAY as f64).collect::<Vec<_>>();
+        assert_eq!(days, vec![29.0, 31.0, 30.0]);
+        assert_eq!(labels(&axis, TimeStep::Months(1)), ["2024-02", "2024-03", "2024-04", "2024-05"]);
+
+        // Multi-month steps align to the calendar year, whatever the range start
+        let axis = TimeAxis::timestamps(ts(2023, 11, 15, 0)..ts(2024, 8, 1, 0));
+        assert_eq!(axis.ticks(TimeStep::Months(3)), vec![ts(2024, 1, 1, 0), ts(2024, 4, 1, 0), ts(2024, 7, 1, 0)]);
+    }
+
+    #[test]
+    fn year_ticks_land_on_january_first() {
+        let axis = TimeAxis::timestamps(ts(2019, 6, 1, 0)..ts(2024, 3, 1, 0));
+        assert_eq!(labels(&axis, TimeStep::Months(12)), ["2020", "2021", "2022", "2023", "2024"]);
+        assert_eq!(axis.ticks(TimeStep::Months(24)), vec![ts(2020, 1, 1, 0), ts(2022, 1, 1, 0), ts(2024, 1, 1, 0)]);
+    }
+
+    #[test]
+    fn day_ticks_cross_leap_day() {
+        let axis = TimeAxis::timestamps(ts(2024, 2, 28, 0)..ts(2024, 3, 2, 0));
+        assert_eq!(labels(&axis, TimeStep::Seconds(DAY)), ["2024-02-28", "2024-02-29", "2024-03-01", "2024-03-02"]);
+        let axis = TimeAxis::timestamps(ts(2023, 2, 28, 0)..ts(2023, 3, 2, 0));
+        assert_eq!(labels(&axis, TimeStep::Seconds(DAY)), ["2023-02-28", "2023-03-01", "2023-03-02"]);
+    }
+
+    #[test]
+    fn week_ticks_start_on_monday() {
+        let axis = TimeAxis::timestamps(ts(2024, 2, 20, 0)..ts(2024, 3, 20, 0));
+        let ticks = axis.ticks(TimeStep::Seconds(7 * DAY));
+        assert_eq!(ticks.len(), 4);
+        assert_eq!(ticks[0], ts(2024, 2, 26, 0));
+        assert!(ticks.iter().all(|&t| to_datetime(t).unwrap().weekday() == Weekday::Mon));
+    }
+
+    #[test]
+    fn hour_ticks_are_uniform_across_european_dst_change() {
+        // Clocks in Europe jumped at 01:00 UTC on 2024-03-31; UTC ticks must not
+        let axis = TimeAxis::timestamps(ts(2024, 3, 31, 0)..ts(2024, 3, 31, 3));
+        assert_eq!(labels(&axis, TimeStep::Seconds(HOUR)), ["00:00", "01:00", "02:00", "03:00"]);
+        // Ranges longer than a day add the date
+        let axis = TimeAxis::timestamps(ts(2024, 3, 30, 12)..ts(2024, 3, 31, 13));
+        assert_eq!(labels(&axis, TimeStep::Seconds(6 * HOUR)), ["03-30 12:00", "03-30 18:00", "03-31 00:00", "03-31 06:00", "03-31 12:00"]);
+    }
+
+    #[test]
+    fn parse_timestamp_formats() {
+        let leap_noon = ts(2024, 2, 29, 12);
+        assert_eq!(leap_noon, 1_709_208_000.0);
+        for s in [
+            "2024-02-29T12:00:00Z",
+            "2024-02-29T13:00:00+01:00",
+            "2024-02-29 12:00:00",
+            "2024-02-29T12:00",
+            " 2024-02-29 12:00 ",
+            "1709208000",
+        ] {
+            assert_eq!(parse_timestamp(s).unwrap(), leap_noon, "{s}");
+        }
+        assert_eq!(parse_timestamp("2024-02-29").unwrap(), ts(2024, 2, 29, 0));
+        assert_eq!(parse_timestamp("2024/02/29").unwrap(), ts(2024, 2, 29, 0));
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00.5Z").unwrap(), 0.5);
+        assert!(parse_timestamp("2023-02-29").is_err());
+        assert!(parse_timestamp("yesterday").is_err());
+    }
+
+    #[test]
+    fn format_duration_by_magnitude() {
+        assert_eq!(format_duration(75.0, 15, 600.0), "01:15");
+        assert_eq!(format_duration(-75.0, 15, 600.0), "-01:15");
+        assert_eq!(format_duration(3725.0, MINUTE, 7200.0), "1:02");
+        assert_eq!(format_duration(3725.0, 5, 7200.0), "1:02:05");
+        assert_eq!(format_duration(90_000.0, DAY, 2.0 * DAY as f64), "1d");
+        assert_eq!(format_duration(90_000.0, HOUR, 2.0 * DAY as f64), "1d 01:00");
+        assert_eq!(parse_duration("1:02:05").unwrap(), 3725.0);
+        assert_eq!(parse_duration("01:15").unwrap(), 75.0);
+    }
+}
@@ -0,0 +1,191 @@
+// Physical figure sizes and point-based styling.
+//
+// Figures are sized in inches or centimetres at a DPI. Fonts, line widths,
+// margins and marker sizes are given in points (1/72 inch) and turned into
+// pixels with `pt` at the DPI of the figure being drawn, which
+// `Canvas::draw` and `save_figure` set for the duration of the draw. Styles
+// are converted when they are built, so build them inside the draw.
+
+use std::cell::Cell;
+use std::error::Error;
+
+pub const POINTS_PER_INCH: f64 = 72.0;
+pub const CM_PER_INCH: f64 = 2.54;
+
+/// Resolution used when none is given.
+pub const DEFAULT_DPI: f64 = 300.0;
+
+/// Width and height in inches, and the resolution they are rendered at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FigureSize {
+    pub width: f64,
+    pub height: f64,
+    pub dpi: f64,
+}
+
+impl FigureSize {
+    /// `width` × `height` inches at [`DEFAULT_DPI`].
+    pub const fn inches(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            dpi: DEFAULT_DPI,
+        }
+    }
+
+    /// `width` × `height` centimetres at [`DEFAULT_DPI`].
+    pub const fn cm(width: f64, height: f64) -> Self {
+        Self::inches(width / CM_PER_INCH, height / CM_PER_INCH)
+    }
+
+    /// The size that `pixels` cover at `dpi`.
+    pub fn from_pixels(pixels: (u32, u32), dpi: f64) -> Self {
+        Self {
+            width: pixels.0 as f64 / dpi,
+            height: pixels.1 as f64 / dpi,
+            dpi,
+        }
+    }
+
+    pub const fn with_dpi(mut self, dpi: f64) -> Self {
+        self.dpi = dpi;
+        self
+    }
+
+    /// Image size in pixels.
+    pub fn pixels(&self) -> (u32, u32) {
+        let px = |inches: f64| (inches * self.dpi).round().max(1.0) as u32;
+        (px(self.width), px(self.height))
+    }
+
+    /// Parses `WxH` with an `in` or `cm` suffix (`8x5in`, `20x12.5cm`), or in
+    /// pixels without one (`2400x1600`, or with `px`), at `dpi`.
+    pub fn parse(s: &str, dpi: f64) -> Result<Self, Box<dyn Error>> {
+        let dpi = check_dpi(dpi)?;
+        let s = s.trim();
+        let (dims, per_inch) = if let Some(d) = s.strip_suffix("in") {
+            (d, 1.0)
+        } else if let Some(d) = s.strip_suffix("cm") {
+            (d, CM_PER_INCH)
+        } else {
+            (s.strip_suffix("px").unwrap_or(s), dpi)
+        };
+        let (w, h) = dims.split_once(['x', 'X']).ok_or_else(|| format!("Expected WxH, got '{}'", s))?;
+        let (w, h) = (w.trim().parse::<f64>()?, h.trim().parse::<f64>()?);
+        if !(w > 0.0 && h > 0.0 && w.is_finite() && h.is_finite()) {
+            return Err(format!("Figure size must be positive, got '{}'", s).into());
+        }
+        Ok(Self {
+            width: w / per_inch,
+            height: h / per_inch,
+            dpi,
+        })
+    }
+}
+
+/// Returns `dpi` if it is a usable resolution.
+pub fn check_dpi(dpi: f64) -> Result<f64, Box<dyn Error>> {
+    if dpi > 0.0 && dpi.is_finite() {
+        Ok(dpi)
+    } else {
+        Err(format!("DPI must be positive, got {}", dpi).into())
+    }
+}
+
+thread_local! {
+    static DPI: Cell<f64> = const { Cell::new(DEFAULT_DPI) };
+}
+
+/// Resolution of the figure being drawn on this thread ([`DEFAULT_DPI`] outside a draw).
+pub fn dpi() -> f64 {
+    DPI.with(Cell::get)
+}
+
+/// Runs `f` with [`dpi`] set to `dpi`, restoring the previous value afterwards.
+pub fn with_dpi<R>(dpi: f64, f: impl FnOnce() -> R) -> R {
+    struct Restore(f64);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            DPI.with(|d| d.set(self.0));
+        }
+    }
+    let _restore = Restore(DPI.with(|d| d.replace(dpi)));
+    f()
+}
+
+/// `points` in pixels at the current DPI, unrounded.
+pub fn pt_f64(points: f64) -> f64 {
+    points * dpi() / POINTS_PER_INCH
+}
+
+/// `points` in whole pixels at the current DPI; positive sizes stay at least one pixel.
+pub fn pt(points: f64) -> u32 {
+    if points > 0.0 {
+        pt_f64(points).round().max(1.0) as u32
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_with_dpi_restores_each_level() {
+        assert_eq!(dpi(), DEFAULT_DPI);
+        assert_eq!(pt(72.0), 300);
+        with_dpi(144.0, || {
+            assert_eq!(pt(72.0), 144);
+            let inner = with_dpi(72.0, || {
+                assert_eq!(pt(10.0), 10);
+                with_dpi(36.0, || pt(72.0))
+            });
+            assert_eq!(inner, 36);
+            // Back to the outer figure once the inner draw returns
+            assert_eq!(dpi(), 144.0);
+            assert_eq!(pt(0.5), 1);
+        });
+        assert_eq!(dpi(), DEFAULT_DPI);
+    }
+
+    #[test]
+    fn with_dpi_restores_after_a_panic() {
+        let r = std::panic::catch_unwind(|| with_dpi(50.0, || panic!("draw failed")));
+        assert!(r.is_err());
+        assert_eq!(dpi(), DEFAULT_DPI);
+    }
+
+    #[test]
+    fn dpi_is_per_thread() {
+        with_dpi(96.0, || {
+            let other = std::thread::spawn(|| pt(72.0)).join().unwrap();
+            assert_eq!(other, 300);
+            assert_eq!(pt(72.0), 96);
+        });
+    }
+
+    #[test]
+    fn pt_rounds_and_keeps_positive_sizes_visible() {
+        with_dpi(72.0, || {
+            assert_eq!(pt(0.01), 1);
+            assert_eq!(pt(2.5), 3);
+            assert_eq!(pt(0.0), 0);
+            assert_eq!(pt(-1.0), 0);
+            assert_eq!(pt(f64::NAN), 0);
+            assert_eq!(pt_f64(0.25), 0.25);
+        });
+    }
+
+    #[test]
+    fn parse_figure_sizes() {
+        assert_eq!(FigureSize::parse("8x5in", 300.0).unwrap(), FigureSize::inches(8.0, 5.0));
+        assert_eq!(FigureSize::parse("2400x1500", 300.0).unwrap().pixels(), (2400, 1500));
+        assert_eq!(FigureSize::parse("800x500px", 100.0).unwrap(), FigureSize::inches(8.0, 5.0).with_dpi(100.0));
+        let cm = FigureSize::parse("25.4X12.7cm", 300.0).unwrap();
+        assert!((cm.width - 10.0).abs() < 1e-12 && (cm.height - 5.0).abs() < 1e-12);
+        assert!(FigureSize::parse("8in", 300.0).is_err());
+        assert!(FigureSize::parse("0x5in", 300.0).is_err());
+        assert!(FigureSize::parse("8x5in", 0.0).is_err());
+    }
+}
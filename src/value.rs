@@ -0,0 +1,631 @@
+// Readers for the TOML and JSON subsets used by plot specification files.
+//
+// Both formats produce the same `Value` tree, so a spec can be written in
+// either. The TOML reader covers what configuration files need: tables,
+// arrays of tables, dotted keys, inline tables, arrays, strings, numbers and
+// booleans. Dates and multi-line strings are not supported (quote dates).
+
+use std::error::Error;
+
+/// A parsed value. Tables keep their keys in file order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    String(String),
+    Number(f64),
+    Bool(bool),
+    Array(Vec<Value>),
+    Table(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Entry `key` of a table.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Table(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Name of the value's type, for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "a string",
+            Value::Number(_) => "a number",
+            Value::Bool(_) => "a boolean",
+            Value::Array(_) => "an array",
+            Value::Table(_) => "a table",
+        }
+    }
+}
+
+/// Parses a TOML document into a table.
+pub fn parse_toml(src: &str) -> Result<Value, Box<dyn Error>> {
+    let mut cur = Cursor::new(src);
+    let mut root = Vec::new();
+    let mut path: Vec<String> = Vec::new();
+    // Headers of `[tables]` seen so far; each may appear only once
+    let mut defined: Vec<Vec<String>> = Vec::new();
+    loop {
+        cur.skip_blank();
+        match cur.peek() {
+            None => break,
+            Some('[') => {
+                cur.bump();
+                let array = cur.eat('[');
+                cur.skip_spaces();
+                let keys = cur.toml_key()?;
+                cur.skip_spaces();
+                cur.expect(']')?;
+                if array {
+                    cur.expect(']')?;
+                }
+                cur.end_of_line()?;
+                if array {
+                    // A new element: tables under the previous one may be defined again
+                    defined.retain(|d| !d.starts_with(&keys));
+                } else if defined.contains(&keys) {
+                    return Err(cur.error(&format!("table [{}] is defined twice", keys.join("."))));
+                } else {
+                    defined.push(keys.clone());
+                }
+                open_table(&mut root, &keys, array).map_err(|e| cur.error(&e))?;
+                path = keys;
+            }
+            Some(_) => {
+                let keys = cur.toml_key()?;
+                cur.skip_spaces();
+                cur.expect('=')?;
+                cur.skip_spaces();
+                let value = cur.toml_value()?;
+                cur.end_of_line()?;
+                let table = table_at(&mut root, &path).map_err(|e| cur.error(&e))?;
+                insert(table, &keys, value).map_err(|e| cur.error(&e))?;
+            }
+        }
+    }
+    Ok(Value::Table(root))
+}
+
+/// Parses a JSON document. `null` is not supported.
+pub fn parse_json(src: &str) -> Result<Value, Box<dyn Error>> {
+    let mut cur = Cursor::new(src);
+    cur.skip_whitespace();
+    let value = cur.json_value()?;
+    cur.skip_whitespace();
+    if cur.peek().is_some() {
+        return Err(cur.error("unexpected text after the JSON value"));
+    }
+    Ok(value)
+}
+
+// The table that `path` names, creating missing tables on the way; arrays of
+// tables resolve to their last element, as TOML headers do
+fn table_at<'t>(mut table: &'t mut Vec<(String, Value)>, path: &[String]) -> Result<&'t mut Vec<(String, Value)>, String> {
+    for key in path {
+        let idx = match table.iter().position(|(k, _)| k == key) {
+            Some(i) => i,
+            None => {
+                table.push((key.clone(), Value::Table(Vec::new())));
+                table.len() - 1
+            }
+        };
+        table = match &mut table[idx].1 {
+            Value::Table(t) => t,
+            Value::Array(items) => match items.last_mut() {
+                Some(Value::Table(t)) => t,
+                _ => return Err(format!("'{}' is not an array of tables", key)),
+            },
+            other => return Err(format!("'{}' is {}, not a table", key, other.type_name())),
+        };
+    }
+    Ok(table)
+}
+
+// Handles a `[keys]` or `[[keys]]` header
+fn open_table(root: &mut Vec<(String, Value)>, keys: &[String], array: bool) -> Result<(), String> {
+    let (last, parent) = keys.split_last().ok_or("empty table header")?;
+    let parent = table_at(root, parent)?;
+    match (parent.iter_mut().find(|(k, _)| k == last), array) {
+        (None, false) => parent.push((last.clone(), Value::Table(Vec::new()))),
+        (None, true) => parent.push((last.clone(), Value::Array(vec![Value::Table(Vec::new())]))),
+        (Some((_, Value::Table(_))), false) => {}
+        (Some((_, Value::Array(items))), true) => items.push(Value::Table(Vec::new())),
+        (Some((_, other)), _) => return Err(format!("'{}' is already defined as {}", last, other.type_name())),
+    }
+    Ok(())
+}
+
+// Sets a possibly dotted key
+fn insert(table: &mut Vec<(String, Value)>, keys: &[String], value: Value) -> Result<(), String> {
+    let (last, parent) = keys.split_last().ok_or("empty key")?;
+    let table = table_at(table, parent)?;
+    if table.iter().any(|(k, _)| k == last) {
+        return Err(format!("duplicate key '{}'", last));
+    }
+    table.push((last.clone(), value));
+    Ok(())
+}
+
+// JSON's number grammar: `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`
+fn is_json_number(word: &str) -> bool {
+    fn digits(s: &str) -> (&str, &str) {
+        let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        s.split_at(end)
+    }
+    let s = word.strip_prefix('-').unwrap_or(word);
+    let (int, mut rest) = digits(s);
+    if int.is_empty() || (int.len() > 1 && int.starts_with('0')) {
+        return false;
+    }
+    if let Some(r) = rest.strip_prefix('.') {
+        let (frac, r) = digits(r);
+        if frac.is_empty() {
+            return false;
+        }
+        rest = r;
+    }
+    if let Some(r) = rest.strip_prefix(['e', 'E']) {
+        let (exp, r) = digits(r.strip_prefix(['+', '-']).unwrap_or(r));
+        if exp.is_empty() {
+            return false;
+        }
+        rest = r;
+    }
+    rest.is_empty()
+}
+
+struct Cursor<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Box<dyn Error>> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", c)))
+        }
+    }
+
+    fn error(&self, msg: &str) -> Box<dyn Error> {
+        let line = self.src[..self.pos].matches('\n').count() + 1;
+        let found = match self.peek() {
+            Some(c) if !c.is_whitespace() => format!(" at '{}'", c),
+            Some(_) => String::new(),
+            None => " at end of input".to_string(),
+        };
+        format!("line {}: {}{}", line, msg, found).into()
+    }
+
+    // Spaces and tabs within a line
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    // Whitespace, newlines and `#` comments
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('#') {
+                break;
+            }
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.bump();
+            }
+        }
+    }
+
+    fn end_of_line(&mut self) -> Result<(), Box<dyn Error>> {
+        self.skip_spaces();
+        if self.peek() == Some('#') {
+            while !matches!(self.peek(), None | Some('\n')) {
+                self.bump();
+            }
+        }
+        self.eat('\r');
+        match self.peek() {
+            None => Ok(()),
+            Some('\n') => {
+                self.bump();
+                Ok(())
+            }
+            Some(_) => Err(self.error("expected end of line")),
+        }
+    }
+
+    // `a`, `"quoted key"` or `a.b.c`
+    fn toml_key(&mut self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut keys = Vec::new();
+        loop {
+            let key = match self.peek() {
+                Some('"') => self.basic_string()?,
+                Some('\'') => self.literal_string()?,
+                _ => {
+                    let start = self.pos;
+                    while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                        self.bump();
+                    }
+                    if self.pos == start {
+                        return Err(self.error("expected a key"));
+                    }
+                    self.src[start..self.pos].to_string()
+                }
+            };
+            keys.push(key);
+            self.skip_spaces();
+            if !self.eat('.') {
+                return Ok(keys);
+            }
+            self.skip_spaces();
+        }
+    }
+
+    fn toml_value(&mut self) -> Result<Value, Box<dyn Error>> {
+        match self.peek() {
+            Some('"') => Ok(Value::String(self.basic_string()?)),
+            Some('\'') => Ok(Value::String(self.literal_string()?)),
+            Some('[') => {
+                self.bump();
+                let mut items = Vec::new();
+                loop {
+                    self.skip_blank();
+                    if self.eat(']') {
+                        return Ok(Value::Array(items));
+                    }
+                    items.push(self.toml_value()?);
+                    self.skip_blank();
+                    if !self.eat(',') {
+                        self.skip_blank();
+                        self.expect(']')?;
+                        return Ok(Value::Array(items));
+                    }
+                }
+            }
+            Some('{') => {
+                self.bump();
+                let mut entries = Vec::new();
+                self.skip_spaces();
+                if self.eat('}') {
+                    return Ok(Value::Table(entries));
+                }
+                loop {
+                    self.skip_spaces();
+                    let keys = self.toml_key()?;
+                    self.skip_spaces();
+                    self.expect('=')?;
+                    self.skip_spaces();
+                    let value = self.toml_value()?;
+                    insert(&mut entries, &keys, value).map_err(|e| self.error(&e))?;
+                    self.skip_spaces();
+                    if !self.eat(',') {
+                        self.expect('}')?;
+                        return Ok(Value::Table(entries));
+                    }
+                }
+            }
+            _ => self.toml_scalar(),
+        }
+    }
+
+    // Booleans and numbers (with `_` separators, `inf` and `nan`)
+    fn toml_scalar(&mut self) -> Result<Value, Box<dyn Error>> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.' | '_')) {
+            self.bump();
+        }
+        let word = &self.src[start..self.pos];
+        match word {
+            "true" => return Ok(Value::Bool(true)),
+            "false" => return Ok(Value::Bool(false)),
+            "" => return Err(self.error("expected a value")),
+            _ => {}
+        }
+        word.replace('_', "").parse::<f64>().map(Value::Number).map_err(|_| {
+            self.pos = start;
+            self.error(&format!("invalid value '{}' (strings need quotes)", word))
+        })
+    }
+
+    fn json_value(&mut self) -> Result<Value, Box<dyn Error>> {
+        match self.peek() {
+            Some('"') => Ok(Value::String(self.basic_string()?)),
+            Some('[') => {
+                self.bump();
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.eat(']') {
+                    return Ok(Value::Array(items));
+                }
+                loop {
+                    self.skip_whitespace();
+                    items.push(self.json_value()?);
+                    self.skip_whitespace();
+                    if !self.eat(',') {
+                        self.expect(']')?;
+                        return Ok(Value::Array(items));
+                    }
+                }
+            }
+            Some('{') => {
+                self.bump();
+                let mut entries = Vec::new();
+                self.skip_whitespace();
+                if self.eat('}') {
+                    return Ok(Value::Table(entries));
+                }
+                loop {
+                    self.skip_whitespace();
+                    if self.peek() != Some('"') {
+                        return Err(self.error("expected a quoted key"));
+                    }
+                    let key = self.basic_string()?;
+                    self.skip_whitespace();
+                    self.expect(':')?;
+                    self.skip_whitespace();
+                    let value = self.json_value()?;
+                    insert(&mut entries, &[key], value).map_err(|e| self.error(&e))?;
+                    self.skip_whitespace();
+                    if !self.eat(',') {
+                        self.expect('}')?;
+                        return Ok(Value::Table(entries));
+                    }
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')) {
+                    self.bump();
+                }
+                match &self.src[start..self.pos] {
+                    "true" => Ok(Value::Bool(true)),
+                    "false" => Ok(Value::Bool(false)),
+                    "null" => {
+                        self.pos = start;
+                        Err(self.error("null is not supported; leave the key out instead"))
+                    }
+                    word => Some(word)
+                        .filter(|w| is_json_number(w))
+                        .and_then(|w| w.parse::<f64>().ok())
+                        .filter(|v| v.is_finite())
+                        .map(Value::Number)
+                        .ok_or_else(|| {
+                            self.pos = start;
+                            self.error("expected a value")
+                        }),
+                }
+            }
+        }
+    }
+
+    // `"..."` with JSON/TOML escapes
+    fn basic_string(&mut self) -> Result<String, Box<dyn Error>> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            let c = match self.peek() {
+                None | Some('\n') => return Err(self.error("unterminated string")),
+                Some(c) => c,
+            };
+            self.bump();
+            match c {
+                '"' => return Ok(out),
+                '\\' => {
+                    let c = match self.bump() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape(4)?,
+                        Some('U') => self.unicode_escape(8)?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    out.push(c);
+                }
+                c => out.push(c),
+            }
+        }
+    }
+
+    // `\uXXXX` (combining UTF-16 surrogate pairs) or `\UXXXXXXXX`
+    fn unicode_escape(&mut self, digits: usize) -> Result<char, Box<dyn Error>> {
+        let code = self.hex(digits)?;
+        if (0xd800..0xdc00).contains(&code) && self.src[self.pos..].starts_with("\\u") {
+            self.pos += 2;
+            let low = self.hex(4)?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(self.error("invalid surrogate pair"));
+            }
+            return char::from_u32(0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00))
+                .ok_or_else(|| self.error("invalid surrogate pair"));
+        }
+        char::from_u32(code).ok_or_else(|| self.error("invalid unicode escape"))
+    }
+
+    fn hex(&mut self, digits: usize) -> Result<u32, Box<dyn Error>> {
+        let end = self.pos + digits;
+        let code = self
+            .src
+            .get(self.pos..end)
+            .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+            .and_then(|h| u32::from_str_radix(h, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.pos = end;
+        Ok(code)
+    }
+
+    // `'...'` without escapes
+    fn literal_string(&mut self) -> Result<String, Box<dyn Error>> {
+        self.expect('\'')?;
+        let start = self.pos;
+        loop {
+            if matches!(self.peek(), None | Some('\n')) {
+                return Err(self.error("unterminated string"));
+            }
+            if self.bump() == Some('\'') {
+                return Ok(self.src[start..self.pos - 1].to_string());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(v: f64) -> Value {
+        Value::Number(v)
+    }
+
+    fn text(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+
+    fn table(entries: &[(&str, Value)]) -> Value {
+        Value::Table(entries.iter().map(|(k, v)| (k.to_string(), v.clone())).collect())
+    }
+
+    fn json_str(src: &str) -> Result<String, Box<dyn Error>> {
+        match parse_json(src)? {
+            Value::String(s) => Ok(s),
+            other => panic!("{src} parsed as {other:?}"),
+        }
+    }
+
+    #[test]
+    fn toml_document() {
+        let src = r#"
+            # Figure
+            title = "Weekly report"   # trailing comment
+            size = [8, 5.5]
+            dpi = 1_200
+            axis.log = true
+
+            [data.sales]
+            path = 'sales.csv'
+            cols = { x = "day", y = "total" }
+
+            [[series]]
+            kind = "line"
+
+            [[series]]
+            kind = "hist"
+            bins."rule" = "fd"
+        "#;
+        let expected = table(&[
+            ("title", text("Weekly report")),
+            ("size", Value::Array(vec![num(8.0), num(5.5)])),
+            ("dpi", num(1200.0)),
+            ("axis", table(&[("log", Value::Bool(true))])),
+            (
+                "data",
+                table(&[("sales", table(&[("path", text("sales.csv")), ("cols", table(&[("x", text("day")), ("y", text("total"))]))]))]),
+            ),
+            (
+                "series",
+                Value::Array(vec![
+                    table(&[("kind", text("line"))]),
+                    table(&[("kind", text("hist")), ("bins", table(&[("rule", text("fd"))]))]),
+                ]),
+            ),
+        ]);
+        assert_eq!(parse_toml(src).unwrap(), expected);
+    }
+
+    #[test]
+    fn toml_tables_are_defined_once() {
+        assert!(parse_toml("[t]\na = 1\n[t]\nb = 2\n").is_err());
+        assert!(parse_toml("[a.b]\n[a.b]\n").is_err());
+        assert!(parse_toml("a = 1\na = 2\n").is_err());
+        assert!(parse_toml("[t]\na = 1\n[t.a]\n").is_err());
+        // A super-table may be defined after its sub-table
+        assert!(parse_toml("[a.b]\nx = 1\n[a]\ny = 2\n").is_ok());
+        // Each array element has its own sub-tables
+        let src = "[[fruit]]\n[fruit.color]\nname = 'red'\n[[fruit]]\n[fruit.color]\nname = 'yellow'\n";
+        let fruit = parse_toml(src).unwrap().get("fruit").cloned().unwrap();
+        let Value::Array(items) = fruit else { panic!("not an array") };
+        assert_eq!(items[1], table(&[("color", table(&[("name", text("yellow"))]))]));
+    }
+
+    #[test]
+    fn toml_errors_name_the_line() {
+        let err = parse_toml("a = 1\nb = red\n").unwrap_err().to_string();
+        assert!(err.starts_with("line 2: invalid value 'red'"), "{err}");
+        assert!(parse_toml("s = \"open\n").is_err());
+        assert!(parse_toml("a = 1 b = 2\n").is_err());
+    }
+
+    #[test]
+    fn json_document_matches_toml() {
+        let json = r#"{"title": "Report", "size": [8, 5.5], "axis": {"log": true}, "dpi": -1.5e2}"#;
+        let toml = "title = 'Report'\nsize = [8, 5.5]\ndpi = -1.5e2\n[axis]\nlog = true\n";
+        let (json, toml) = (parse_json(json).unwrap(), parse_toml(toml).unwrap());
+        for key in ["title", "size", "axis", "dpi"] {
+            assert_eq!(json.get(key), toml.get(key), "{key}");
+        }
+        assert!(parse_json(r#"{"a": 1, "a": 2}"#).is_err());
+        assert!(parse_json(r#"{"a": null}"#).is_err());
+        assert!(parse_json("[1, 2] 3").is_err());
+    }
+
+    #[test]
+    fn json_numbers_follow_the_grammar() {
+        for (src, v) in [("0", 0.0), ("-0", 0.0), ("0.5", 0.5), ("10", 10.0), ("1e5", 1e5), ("-1.5E-3", -1.5e-3), ("2e+2", 200.0)] {
+            assert_eq!(parse_json(src).unwrap(), num(v), "{src}");
+        }
+        for src in ["01", "-01", "00.5", "+1", ".5", "1.", "1e", "1e+", "-", "inf", "NaN", "0x10", "1_000"] {
+            assert!(parse_json(src).is_err(), "{src}");
+        }
+        assert!(parse_json(r#"{"a": 01}"#).is_err());
+    }
+
+    #[test]
+    fn unicode_escapes() {
+        assert_eq!(json_str(r#""\u00e9\u00C9 \t\"\\\/""#).unwrap(), "éÉ \t\"\\/");
+        assert_eq!(json_str(r#""\uD83D\uDE00""#).unwrap(), "😀");
+        // Lone or mismatched surrogates
+        assert!(json_str(r#""\uD83D""#).is_err());
+        assert!(json_str(r#""\uDE00""#).is_err());
+        assert!(json_str(r#""\uD83D\u0041""#).is_err());
+        assert!(json_str(r#""\uD83D\uD83D""#).is_err());
+        // Exactly four hex digits, no sign
+        assert!(json_str(r#""\u+041""#).is_err());
+        assert!(json_str(r#""\u04""#).is_err());
+        assert_eq!(parse_toml("s = \"\\U0001F600\"\n").unwrap().get("s"), Some(&text("😀")));
+    }
+}
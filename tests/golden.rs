@@ -0,0 +1,233 @@
+//! Golden-image tests: every offline example of the example binaries, and
+//! every plot spec in `specs/`, is rendered in memory with the default seed and
+//! compared with `tests/golden/<name>.png`.
+//!
+//! References are stored at half resolution (2×2 box filter) to keep the
+//! repository small; renders are downscaled the same way before comparing.
+//! Run `UPDATE_GOLDEN=1 cargo test --test golden` to rewrite them after an
+//! intended visual change. On a mismatch, `<name>.actual.png`,
+//! `<name>.expected.png` and `<name>.diff.png` are written to
+//! `target/golden-report/` (or `$GOLDEN_REPORT_DIR`).
+//!
+//! Text is drawn with the fonts bundled in `fonts/` (see `rust_plot::fonts`),
+//! so the references do not depend on the fonts installed on the machine.
+
+#[allow(dead_code)]
+#[path = "../src/bin/heatmap.rs"]
+mod heatmap;
+#[allow(dead_code)]
+#[path = "../src/bin/histogram.rs"]
+mod histogram;
+#[allow(dead_code)]
+#[path = "../src/bin/line.rs"]
+mod line;
+
+use image::codecs::png::{CompressionType, FilterType, PngEncoder};
+use image::{ColorType, ImageEncoder, RgbImage};
+use rust_plot::render::{Canvas, Example, Figure, RenderedImage};
+use rust_plot::spec::PlotSpec;
+use rust_plot::rng::{set_seed, DEFAULT_SEED};
+use std::path::{Path, PathBuf};
+
+/// Linear downscale factor of the stored references.
+const SCALE: u32 = 2;
+
+/// Per-pixel YIQ colour distance (0–1, as in pixelmatch) above which a pixel counts as changed.
+const PIXEL_THRESHOLD: f64 = 0.1;
+
+/// Fraction of changed pixels tolerated before an image fails.
+const MAX_CHANGED_FRACTION: f64 = 0.001;
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden")
+}
+
+fn report_dir() -> PathBuf {
+    std::env::var_os("GOLDEN_REPORT_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join("target").join("golden-report"))
+}
+
+// Averages SCALE×SCALE blocks; trailing rows/columns that do not fill a block are dropped
+fn downscale(img: &RenderedImage) -> RgbImage {
+    let (w, h) = (img.size.0 / SCALE, img.size.1 / SCALE);
+    RgbImage::from_fn(w, h, |x, y| {
+        let mut sum = [0u32; 3];
+        for dy in 0..SCALE {
+            for dx in 0..SCALE {
+                let i = (((y * SCALE + dy) * img.size.0 + x * SCALE + dx) * 3) as usize;
+                for (c, s) in sum.iter_mut().enumerate() {
+                    *s += img.rgb[i + c] as u32;
+                }
+            }
+        }
+        image::Rgb(sum.map(|s| (s / (SCALE * SCALE)) as u8))
+    })
+}
+
+// Squared YIQ distance normalized to 0–1 (Kotsarenko & Ramos, as used by pixelmatch)
+fn yiq_distance(a: &image::Rgb<u8>, b: &image::Rgb<u8>) -> f64 {
+    let yiq = |p: &image::Rgb<u8>| {
+        let [r, g, b] = p.0.map(|c| c as f64);
+        (
+            r * 0.298_895_31 + g * 0.586_622_47 + b * 0.114_482_23,
+            r * 0.595_977_99 - g * 0.274_176_10 - b * 0.321_801_89,
+            r * 0.211_470_17 - g * 0.522_617_11 + b * 0.311_146_94,
+        )
+    };
+    let (a, b) = (yiq(a), yiq(b));
+    let d = 0.5053 * (a.0 - b.0).powi(2) + 0.299 * (a.1 - b.1).powi(2) + 0.1957 * (a.2 - b.2).powi(2);
+    d / 35_215.0
+}
+
+/// Changed-pixel count and a diff image: the expected image faded to grey with changed pixels in red.
+fn compare(actual: &RgbImage, expected: &RgbImage) -> (usize, RgbImage) {
+    let mut changed = 0;
+    let diff = RgbImage::from_fn(expected.width(), expected.height(), |x, y| {
+        let (a, e) = (actual.get_pixel(x, y), expected.get_pixel(x, y));
+        if yiq_distance(a, e) > PIXEL_THRESHOLD * PIXEL_THRESHOLD {
+            changed += 1;
+            image::Rgb([255, 0, 0])
+        } else {
+            let [r, g, b] = e.0.map(|c| c as u32);
+            let grey = (255 - (255 - (r * 30 + g * 59 + b * 11) / 100) / 4) as u8;
+            image::Rgb([grey; 3])
+        }
+    });
+    (changed, diff)
+}
+
+fn write_report(name: &str, actual: &RgbImage, expected: Option<&RgbImage>, diff: Option<&RgbImage>) -> PathBuf {
+    let dir = report_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    actual.save(dir.join(format!("{}.actual.png", name))).unwrap();
+    if let Some(expected) = expected {
+        expected.save(dir.join(format!("{}.expected.png", name))).unwrap();
+    }
+    if let Some(diff) = diff {
+        diff.save(dir.join(format!("{}.diff.png", name))).unwrap();
+    }
+    dir
+}
+
+/// Checks one rendered image against its reference, returning a failure message.
+fn check_image(img: &RenderedImage, update: bool) -> Option<String> {
+    let actual = downscale(img);
+    let path = golden_dir().join(format!("{}.png", img.name));
+    if update {
+        std::fs::create_dir_all(golden_dir()).unwrap();
+        // References are committed, so spend time on compression
+        let file = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+        PngEncoder::new_with_quality(file, CompressionType::Best, FilterType::Adaptive)
+            .write_image(&actual, actual.width(), actual.height(), ColorType::Rgb8)
+            .unwrap();
+        return None;
+    }
+
+    let expected = match image::open(&path) {
+        Ok(expected) => expected.to_rgb8(),
+        Err(e) => {
+            let dir = write_report(&img.name, &actual, None, None);
+            return Some(format!(
+                "{}: cannot read reference {} ({}); actual written to {}",
+                img.name,
+                path.display(),
+                e,
+                dir.display()
+            ));
+        }
+    };
+    if expected.dimensions() != actual.dimensions() {
+        let dir = write_report(&img.name, &actual, Some(&expected), None);
+        return Some(format!(
+            "{}: size {:?} differs from reference {:?}; see {}",
+            img.name,
+            actual.dimensions(),
+            expected.dimensions(),
+            dir.display()
+        ));
+    }
+
+    let (changed, diff) = compare(&actual, &expected);
+    let fraction = changed as f64 / (actual.width() * actual.height()) as f64;
+    if fraction > MAX_CHANGED_FRACTION {
+        let dir = write_report(&img.name, &actual, Some(&expected), Some(&diff));
+        return Some(format!(
+            "{}: {} pixels ({:.3}%) changed, tolerance {:.3}%; see {}",
+            img.name,
+            changed,
+            100.0 * fraction,
+            100.0 * MAX_CHANGED_FRACTION,
+            dir.display()
+        ));
+    }
+    None
+}
+
+fn check_gallery(examples: &[Example]) {
+    set_seed(DEFAULT_SEED);
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+    let mut failures = Vec::new();
+    for example in examples {
+        if example.needs_network {
+            eprintln!("skipping example {} (needs network)", example.name);
+            continue;
+        }
+        let mut canvas = Canvas::in_memory();
+        if let Err(e) = (example.run)(&mut canvas) {
+            failures.push(format!("example {} failed to render: {}", example.name, e));
+            continue;
+        }
+        failures.extend(canvas.images().iter().filter_map(|img| check_image(img, update)));
+    }
+    assert!(failures.is_empty(), "golden image mismatches:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn heatmap_examples_match_golden() {
+    check_gallery(heatmap::EXAMPLES);
+}
+
+#[test]
+fn histogram_examples_match_golden() {
+    check_gallery(histogram::EXAMPLES);
+}
+
+#[test]
+fn line_examples_match_golden() {
+    check_gallery(line::EXAMPLES);
+}
+
+/// TOML and JSON versions of a spec share the reference `spec_<stem>.png`.
+#[test]
+fn specs_match_golden() {
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("specs");
+    let mut paths = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "toml" || e == "json"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert!(!paths.is_empty(), "no specs in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for path in paths {
+        let plot = match PlotSpec::from_path(&path).and_then(|spec| spec.load()) {
+            Ok(plot) => plot,
+            Err(e) => {
+                failures.push(format!("{}: {}", path.display(), e));
+                continue;
+            }
+        };
+        let name = format!("spec_{}", path.file_stem().unwrap().to_string_lossy());
+        let mut canvas = Canvas::in_memory();
+        if let Err(e) = canvas.draw(&name, plot.size(), |root| plot.draw(root)) {
+            failures.push(format!("{} failed to render: {}", path.display(), e));
+            continue;
+        }
+        failures.extend(canvas.images().iter().filter_map(|img| check_image(img, update)));
+    }
+    assert!(failures.is_empty(), "golden image mismatches:\n{}", failures.join("\n"));
+}